@@ -0,0 +1,286 @@
+/*
+ * Procedural macros: functions from TokenStream to TokenStream, run by the compiler
+ * - must live in their own crate with `proc-macro = true`
+ * - such a crate can export only macros (no traits, no functions)
+ * Three kinds:
+ * - #[proc_macro_derive(Name)]: fn(item) -> extra items appended after the item
+ * - #[proc_macro_attribute]: fn(attr args, item) -> replacement for the item
+ * - #[proc_macro]: fn(input) -> replacement for the invocation
+ *
+ * No syn/quote here: input is walked as TokenTree (Group, Ident, Punct, Literal),
+ * output is either parsed from a String or assembled from TokenTrees by hand
+ */
+
+extern crate proc_macro;
+
+use proc_macro::{Delimiter, Group, Ident, Punct, Spacing, Span, TokenStream, TokenTree};
+
+//Errors are reported by expanding into compile_error!, which fails at the call site
+fn compile_error(message: &str) -> TokenStream {
+    format!("compile_error!({:?});", message).parse().unwrap()
+}
+
+fn is_punct(token: &TokenTree, ch: char) -> bool {
+    matches!(token, TokenTree::Punct(punct) if punct.as_char() == ch)
+}
+
+fn is_ident(token: &TokenTree, name: &str) -> bool {
+    matches!(token, TokenTree::Ident(ident) if ident.to_string() == name)
+}
+
+//Skips outer attributes (#[...]) and visibility (pub, pub(crate), ...)
+fn skip_attributes_and_visibility(mut tokens: &[TokenTree]) -> &[TokenTree] {
+    while let [hash, TokenTree::Group(group), rest @ ..] = tokens {
+        if !is_punct(hash, '#') || group.delimiter() != Delimiter::Bracket {
+            break;
+        }
+        tokens = rest;
+    }
+    if let [pub_kw, rest @ ..] = tokens {
+        if is_ident(pub_kw, "pub") {
+            tokens = rest;
+            if let [TokenTree::Group(group), rest @ ..] = tokens {
+                if group.delimiter() == Delimiter::Parenthesis {
+                    tokens = rest;
+                }
+            }
+        }
+    }
+    tokens
+}
+
+//Splits on commas that are not nested inside <...>
+//(commas inside (), [] and {} are already hidden in Groups)
+fn split_top_level_commas(tokens: Vec<TokenTree>) -> Vec<Vec<TokenTree>> {
+    let mut parts = vec![Vec::new()];
+    let mut depth = 0;
+    let mut previous_is_dash = false;
+    for token in tokens {
+        if let TokenTree::Punct(punct) = &token {
+            match punct.as_char() {
+                '<' => depth += 1,
+                //`->` in fn types is not a closing angle bracket
+                '>' if !previous_is_dash => depth -= 1,
+                ',' if depth == 0 => {
+                    parts.push(Vec::new());
+                    continue;
+                }
+                _ => {}
+            }
+        }
+        previous_is_dash = is_punct(&token, '-');
+        parts.last_mut().unwrap().push(token);
+    }
+    //trailing comma leaves an empty part
+    parts.retain(|part| !part.is_empty());
+    parts
+}
+
+//Prints type tokens the way rustfmt would: `HashMap<String, u32>`, `&'a mut T`, `fn(i32) -> i32`,
+//`[u8; 4]`, `Box<dyn Fn() + Send>`
+//(TokenStream::to_string puts spaces between all tokens)
+fn type_to_string(tokens: &[TokenTree]) -> String {
+    fn write(tokens: &[TokenTree], out: &mut String) {
+        let mut last_was_word = false;
+        let mut last_was_keyword = false;
+        for (index, token) in tokens.iter().enumerate() {
+            match token {
+                TokenTree::Ident(_) | TokenTree::Literal(_) => {
+                    if last_was_word {
+                        out.push(' ');
+                    }
+                    let word = token.to_string();
+                    last_was_keyword = ["mut", "dyn", "impl", "const"].contains(&word.as_str());
+                    out.push_str(&word);
+                    last_was_word = true;
+                }
+                TokenTree::Punct(punct) => {
+                    let next_is_gt =
+                        matches!(tokens.get(index + 1), Some(next) if is_punct(next, '>'));
+                    match punct.as_char() {
+                        ',' => out.push_str(", "),
+                        ';' => out.push_str("; "),
+                        '+' => out.push_str(" + "),
+                        '-' if next_is_gt => out.push_str(" -"),
+                        '>' if out.ends_with(" -") => out.push_str("> "),
+                        ch => out.push(ch),
+                    }
+                    //lifetime: ' glued to the following identifier
+                    last_was_word = false;
+                }
+                TokenTree::Group(group) => {
+                    let (open, close) = match group.delimiter() {
+                        Delimiter::Parenthesis => ("(", ")"),
+                        Delimiter::Bracket => ("[", "]"),
+                        Delimiter::Brace => ("{", "}"),
+                        Delimiter::None => ("", ""),
+                    };
+                    //`Fn(i32)` and `[u8; 4]`, but `&mut [u8]`
+                    if last_was_word && last_was_keyword {
+                        out.push(' ');
+                    }
+                    out.push_str(open);
+                    write(&group.stream().into_iter().collect::<Vec<_>>(), out);
+                    out.push_str(close);
+                    last_was_word = false;
+                }
+            }
+        }
+    }
+
+    let mut out = String::new();
+    write(tokens, &mut out);
+    out
+}
+
+/// `#[derive(Describe)]` on a struct implements `Describe` (which must be in scope)
+/// with the struct name and a `(field name, field type)` pair per field.
+#[proc_macro_derive(Describe)]
+pub fn derive_describe(input: TokenStream) -> TokenStream {
+    let tokens: Vec<TokenTree> = input.into_iter().collect();
+    let tokens = skip_attributes_and_visibility(&tokens);
+
+    let (name, body) = match tokens {
+        [keyword, TokenTree::Ident(name), body @ ..] if is_ident(keyword, "struct") => (name, body),
+        [keyword, ..] if is_ident(keyword, "enum") || is_ident(keyword, "union") => {
+            return compile_error("Describe can only be derived for structs");
+        }
+        _ => return compile_error("expected a struct"),
+    };
+
+    let fields: Vec<(String, String)> = match body {
+        [first, ..] if is_punct(first, '<') => {
+            return compile_error("Describe doesn't support generic structs");
+        }
+        //struct Unit;
+        [semicolon] if is_punct(semicolon, ';') => Vec::new(),
+        //struct Named { a: A, b: B }
+        [TokenTree::Group(group)] if group.delimiter() == Delimiter::Brace => {
+            let mut fields = Vec::new();
+            for field in split_top_level_commas(group.stream().into_iter().collect()) {
+                match skip_attributes_and_visibility(&field) {
+                    [TokenTree::Ident(name), colon, ty @ ..] if is_punct(colon, ':') => {
+                        let name = name.to_string();
+                        //raw identifiers: r#type -> type
+                        let name = name.trim_start_matches("r#").to_owned();
+                        fields.push((name, type_to_string(ty)));
+                    }
+                    _ => return compile_error("expected `name: Type` field"),
+                }
+            }
+            fields
+        }
+        //struct Tuple(A, B);
+        [TokenTree::Group(group), semicolon]
+            if group.delimiter() == Delimiter::Parenthesis && is_punct(semicolon, ';') =>
+        {
+            split_top_level_commas(group.stream().into_iter().collect())
+                .iter()
+                .enumerate()
+                .map(|(index, field)| {
+                    (
+                        index.to_string(),
+                        type_to_string(skip_attributes_and_visibility(field)),
+                    )
+                })
+                .collect()
+        }
+        _ => return compile_error("Describe doesn't support where clauses"),
+    };
+
+    let entries: Vec<String> = fields
+        .iter()
+        .map(|(field, ty)| format!("({:?}, {:?})", field, ty))
+        .collect();
+    let output = format!(
+        "impl Describe for {name} {{
+            fn type_name() -> &'static str {{
+                {name:?}
+            }}
+            fn fields() -> &'static [(&'static str, &'static str)] {{
+                &[{entries}]
+            }}
+        }}",
+        name = name.to_string(),
+        entries = entries.join(", ")
+    );
+    output.parse().unwrap()
+}
+
+/// `#[log_entry]` prints `> entering <name>` to stderr when the function is called.
+/// `#[log_entry(path::to::logger)]` calls `logger("<name>")` instead.
+#[proc_macro_attribute]
+pub fn log_entry(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut tokens: Vec<TokenTree> = item.into_iter().collect();
+
+    let name = tokens.windows(2).find_map(|pair| match pair {
+        [keyword, TokenTree::Ident(name)] if is_ident(keyword, "fn") => Some(name.to_string()),
+        _ => None,
+    });
+    let body = match (name.as_ref(), tokens.pop()) {
+        (Some(_), Some(TokenTree::Group(body))) if body.delimiter() == Delimiter::Brace => body,
+        _ => return compile_error("log_entry can only be applied to functions with a body"),
+    };
+    let name = name.unwrap();
+
+    let log: TokenStream = if attr.is_empty() {
+        format!("eprintln!(\"> entering {{}}\", {:?});", name)
+            .parse()
+            .unwrap()
+    } else {
+        format!("({})({:?});", attr, name).parse().unwrap()
+    };
+
+    //{ log; { original body } }: inner block keeps the original tail expression
+    let mut new_body = log;
+    new_body.extend(Some(TokenTree::Group(Group::new(
+        Delimiter::Brace,
+        body.stream(),
+    ))));
+    let mut new_body = Group::new(Delimiter::Brace, new_body);
+    //errors inside the body still point at the original code
+    new_body.set_span(body.span());
+
+    tokens.push(TokenTree::Group(new_body));
+    tokens.into_iter().collect()
+}
+
+/// `screaming_consts! { max_speed: u32 = 120, pub greeting: &str = "hi" }` expands to
+/// `const MAX_SPEED: u32 = 120; pub const GREETING: &str = "hi";`
+///
+/// Declarative macros can't build new identifiers, procedural ones can.
+#[proc_macro]
+pub fn screaming_consts(input: TokenStream) -> TokenStream {
+    let mut output = TokenStream::new();
+    for item in split_top_level_commas(input.into_iter().collect()) {
+        let rest = skip_attributes_and_visibility(&item);
+        let visibility = &item[..item.len() - rest.len()];
+
+        let (name, ty, value) = match rest {
+            [TokenTree::Ident(name), colon, rest @ ..] if is_punct(colon, ':') => {
+                //types never contain `=`, so the first one separates the type from the value
+                match rest.iter().position(|token| is_punct(token, '=')) {
+                    Some(eq) if eq > 0 && eq + 1 < rest.len() => {
+                        (name, &rest[..eq], &rest[eq + 1..])
+                    }
+                    _ => return compile_error("expected `name: Type = value`"),
+                }
+            }
+            _ => return compile_error("expected `name: Type = value`"),
+        };
+
+        //output assembled from TokenTrees, new identifier keeps the span of the original one
+        let constant = Ident::new(&name.to_string().to_uppercase(), name.span());
+        output.extend(visibility.iter().cloned());
+        output.extend(vec![
+            TokenTree::Ident(Ident::new("const", Span::call_site())),
+            TokenTree::Ident(constant),
+            TokenTree::Punct(Punct::new(':', Spacing::Alone)),
+        ]);
+        output.extend(ty.iter().cloned());
+        output.extend(Some(TokenTree::Punct(Punct::new('=', Spacing::Alone))));
+        output.extend(value.iter().cloned());
+        output.extend(Some(TokenTree::Punct(Punct::new(';', Spacing::Alone))));
+    }
+    output
+}
@@ -0,0 +1,462 @@
+/*
+ * Future: poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Output>
+ * - Poll::Ready(value) when done
+ * - Poll::Pending otherwise, after arranging for cx.waker().wake() to be called later
+ * async fn/async block: compiler-generated state machine implementing Future,
+ * every .await is a point where it can return Pending
+ * Nothing runs until an executor polls the future: futures are lazy
+ *
+ * Executor: polls futures, sleeps when nothing is ready, re-polls a task when its Waker is called
+ * Waker: built from Arc<impl Wake> (std::task::Wake) - no unsafe RawWaker vtable needed
+ */
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+use std::time::{Duration, Instant};
+
+//1. Running a single future to completion on the current thread
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    //poll requires a pinned future: Box::pin makes it Unpin-independent
+    let mut future = Box::pin(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(value) => return value,
+            //park returns when unpark was called (or spuriously: then just poll again)
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+//2. Executor for many tasks: run queue + Waker that pushes the task back into the queue
+mod executor {
+    use std::collections::VecDeque;
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Condvar, Mutex};
+    use std::task::{Context, Poll, Wake, Waker};
+
+    type BoxFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+    struct Queue {
+        tasks: Mutex<VecDeque<Arc<Task>>>,
+        //signalled when a task is pushed, e.g. by a timer thread
+        ready: Condvar,
+        //spawned but not finished tasks
+        pending: AtomicUsize,
+    }
+
+    impl Queue {
+        fn push(&self, task: Arc<Task>) {
+            self.tasks.lock().unwrap().push_back(task);
+            self.ready.notify_one();
+        }
+    }
+
+    struct Task {
+        //None once finished: a late wake() finds nothing to poll
+        future: Mutex<Option<BoxFuture>>,
+        queue: Arc<Queue>,
+    }
+
+    impl Wake for Task {
+        fn wake(self: Arc<Self>) {
+            let queue = self.queue.clone();
+            queue.push(self);
+        }
+    }
+
+    //Cloneable handle, so tasks can spawn more tasks
+    #[derive(Clone)]
+    pub struct Spawner {
+        queue: Arc<Queue>,
+    }
+
+    impl Spawner {
+        pub fn spawn<F: Future<Output = ()> + Send + 'static>(&self, future: F) {
+            self.queue.pending.fetch_add(1, Ordering::SeqCst);
+            self.queue.push(Arc::new(Task {
+                future: Mutex::new(Some(Box::pin(future))),
+                queue: self.queue.clone(),
+            }));
+        }
+    }
+
+    //Single-threaded: all futures are polled on the thread that calls run()
+    pub struct Executor {
+        queue: Arc<Queue>,
+    }
+
+    impl Executor {
+        pub fn new() -> Executor {
+            Executor {
+                queue: Arc::new(Queue {
+                    tasks: Mutex::new(VecDeque::new()),
+                    ready: Condvar::new(),
+                    pending: AtomicUsize::new(0),
+                }),
+            }
+        }
+
+        pub fn spawner(&self) -> Spawner {
+            Spawner {
+                queue: self.queue.clone(),
+            }
+        }
+
+        //Runs until every spawned task has finished
+        pub fn run(&self) {
+            while self.queue.pending.load(Ordering::SeqCst) > 0 {
+                let task = {
+                    let mut tasks = self.queue.tasks.lock().unwrap();
+                    loop {
+                        match tasks.pop_front() {
+                            Some(task) => break task,
+                            //nothing ready: sleep until some waker pushes a task
+                            None => tasks = self.queue.ready.wait(tasks).unwrap(),
+                        }
+                    }
+                };
+
+                let mut slot = task.future.lock().unwrap();
+                if let Some(mut future) = slot.take() {
+                    let waker = Waker::from(task.clone());
+                    let mut cx = Context::from_waker(&waker);
+                    match future.as_mut().poll(&mut cx) {
+                        Poll::Pending => *slot = Some(future),
+                        Poll::Ready(()) => {
+                            self.queue.pending.fetch_sub(1, Ordering::SeqCst);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+//3. Leaf futures: the ones that actually register wakers
+#[derive(Default)]
+struct TimerState {
+    completed: bool,
+    waker: Option<Waker>,
+}
+
+//Completes after `duration`, a helper thread does the waiting
+struct Timer {
+    state: Arc<Mutex<TimerState>>,
+}
+
+impl Timer {
+    fn new(duration: Duration) -> Timer {
+        let state = Arc::new(Mutex::new(TimerState::default()));
+        let thread_state = state.clone();
+        thread::spawn(move || {
+            thread::sleep(duration);
+            let mut state = thread_state.lock().unwrap();
+            state.completed = true;
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        });
+        Timer { state }
+    }
+}
+
+impl Future for Timer {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        let mut state = self.state.lock().unwrap();
+        if state.completed {
+            Poll::Ready(())
+        } else {
+            //the latest waker wins: the future could have moved to another task
+            state.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+//Returns Pending once and wakes itself right away: lets other tasks run
+struct YieldNow {
+    yielded: bool,
+}
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        if self.yielded {
+            return Poll::Ready(());
+        }
+        self.yielded = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+fn yield_now() -> YieldNow {
+    YieldNow { yielded: false }
+}
+
+//4. Combinators: futures that poll other futures
+enum MaybeDone<F: Future> {
+    Running(Pin<Box<F>>),
+    Done(F::Output),
+    Taken,
+}
+
+impl<F: Future> MaybeDone<F> {
+    //true once the output is available
+    fn poll(&mut self, cx: &mut Context) -> bool {
+        if let MaybeDone::Running(future) = self {
+            match future.as_mut().poll(cx) {
+                Poll::Ready(value) => *self = MaybeDone::Done(value),
+                Poll::Pending => return false,
+            }
+        }
+        true
+    }
+
+    fn take(&mut self) -> F::Output {
+        match std::mem::replace(self, MaybeDone::Taken) {
+            MaybeDone::Done(value) => value,
+            _ => panic!("output is not ready or already taken"),
+        }
+    }
+}
+
+//Both futures make progress concurrently, output when both are done
+struct Join<A: Future, B: Future> {
+    a: MaybeDone<A>,
+    b: MaybeDone<B>,
+}
+
+//MaybeDone boxes the futures, so Join is Unpin and can be accessed through &mut
+impl<A: Future, B: Future> Unpin for Join<A, B> {}
+
+impl<A: Future, B: Future> Future for Join<A, B> {
+    type Output = (A::Output, B::Output);
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        //poll both every time: the same waker is registered with both
+        let a_done = self.a.poll(cx);
+        let b_done = self.b.poll(cx);
+        if a_done && b_done {
+            Poll::Ready((self.a.take(), self.b.take()))
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+fn join<A: Future, B: Future>(a: A, b: B) -> Join<A, B> {
+    Join {
+        a: MaybeDone::Running(Box::pin(a)),
+        b: MaybeDone::Running(Box::pin(b)),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Either<L, R> {
+    Left(L),
+    Right(R),
+}
+
+//First future to finish wins, the other one is dropped (cancelled)
+struct Select<A, B> {
+    a: Pin<Box<A>>,
+    b: Pin<Box<B>>,
+}
+
+impl<A: Future, B: Future> Future for Select<A, B> {
+    type Output = Either<A::Output, B::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        if let Poll::Ready(value) = self.a.as_mut().poll(cx) {
+            return Poll::Ready(Either::Left(value));
+        }
+        if let Poll::Ready(value) = self.b.as_mut().poll(cx) {
+            return Poll::Ready(Either::Right(value));
+        }
+        Poll::Pending
+    }
+}
+
+fn select<A: Future, B: Future>(a: A, b: B) -> Select<A, B> {
+    Select {
+        a: Box::pin(a),
+        b: Box::pin(b),
+    }
+}
+
+fn blocking() {
+    assert_eq!(block_on(async { 1 + 2 }), 3);
+
+    //futures are lazy: nothing is printed or computed until polled
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let task_log = log.clone();
+    let future = async move {
+        task_log.lock().unwrap().push("polled");
+        42
+    };
+    assert!(log.lock().unwrap().is_empty());
+    assert_eq!(block_on(future), 42);
+    assert_eq!(*log.lock().unwrap(), ["polled"]);
+
+    //waiting for a timer parks the thread instead of spinning
+    let start = Instant::now();
+    block_on(Timer::new(Duration::from_millis(20)));
+    assert!(start.elapsed() >= Duration::from_millis(20));
+}
+
+fn executor() {
+    use executor::Executor;
+
+    let executor = Executor::new();
+    let spawner = executor.spawner();
+    let log = Arc::new(Mutex::new(Vec::new()));
+
+    //tasks interleave at every .await that returns Pending
+    for name in ["a", "b"] {
+        let log = log.clone();
+        spawner.spawn(async move {
+            for step in 0..2 {
+                log.lock().unwrap().push(format!("{}{}", name, step));
+                yield_now().await;
+            }
+        });
+    }
+
+    //tasks can spawn tasks; this one is woken from the timer thread
+    let nested_log = log.clone();
+    let nested_spawner = spawner.clone();
+    spawner.spawn(async move {
+        Timer::new(Duration::from_millis(10)).await;
+        nested_spawner.spawn(async move {
+            nested_log.lock().unwrap().push("nested".to_owned());
+        });
+    });
+
+    executor.run();
+    assert_eq!(*log.lock().unwrap(), ["a0", "b0", "a1", "b1", "nested"]);
+}
+
+fn combinators() {
+    //join: both run concurrently, the shorter timer finishes first
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let (slow_log, fast_log) = (log.clone(), log.clone());
+    let slow = async move {
+        Timer::new(Duration::from_millis(60)).await;
+        slow_log.lock().unwrap().push("slow");
+        1
+    };
+    let fast = async move {
+        Timer::new(Duration::from_millis(10)).await;
+        fast_log.lock().unwrap().push("fast");
+        2
+    };
+    assert_eq!(block_on(join(slow, fast)), (1, 2));
+    assert_eq!(*log.lock().unwrap(), ["fast", "slow"]);
+
+    //select: the loser is dropped before it completes
+    let winner = block_on(select(
+        async {
+            Timer::new(Duration::from_millis(10)).await;
+            "fast"
+        },
+        async {
+            Timer::new(Duration::from_secs(5)).await;
+            "slow"
+        },
+    ));
+    assert_eq!(winner, Either::Left("fast"));
+
+    //timeout is select with a timer
+    let timed_out = block_on(select(
+        Timer::new(Duration::from_secs(5)),
+        Timer::new(Duration::from_millis(10)),
+    ));
+    assert_eq!(timed_out, Either::Right(()));
+}
+
+/*
+ * What async fn compiles to: an enum with one variant per .await point,
+ * holding the locals that are alive across it
+ */
+fn state_machine() {
+    async fn add_slowly(a: u32, b: u32) -> u32 {
+        Timer::new(Duration::from_millis(5)).await;
+        let sum = a + b;
+        Timer::new(Duration::from_millis(5)).await;
+        sum
+    }
+
+    enum AddSlowly {
+        Start { a: u32, b: u32 },
+        FirstWait { timer: Timer, a: u32, b: u32 },
+        SecondWait { timer: Timer, sum: u32 },
+        Done,
+    }
+
+    //all fields are Unpin (no self-references), so no unsafe pin projections needed
+    impl Future for AddSlowly {
+        type Output = u32;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<u32> {
+            let this = self.get_mut();
+            loop {
+                match std::mem::replace(this, AddSlowly::Done) {
+                    AddSlowly::Start { a, b } => {
+                        let timer = Timer::new(Duration::from_millis(5));
+                        *this = AddSlowly::FirstWait { timer, a, b };
+                    }
+                    AddSlowly::FirstWait { mut timer, a, b } => {
+                        if Pin::new(&mut timer).poll(cx).is_pending() {
+                            *this = AddSlowly::FirstWait { timer, a, b };
+                            return Poll::Pending;
+                        }
+                        let timer = Timer::new(Duration::from_millis(5));
+                        *this = AddSlowly::SecondWait { timer, sum: a + b };
+                    }
+                    AddSlowly::SecondWait { mut timer, sum } => {
+                        if Pin::new(&mut timer).poll(cx).is_pending() {
+                            *this = AddSlowly::SecondWait { timer, sum };
+                            return Poll::Pending;
+                        }
+                        return Poll::Ready(sum);
+                    }
+                    AddSlowly::Done => panic!("polled after completion"),
+                }
+            }
+        }
+    }
+
+    assert_eq!(block_on(add_slowly(2, 3)), 5);
+    assert_eq!(block_on(AddSlowly::Start { a: 2, b: 3 }), 5);
+    //interchangeable wherever a Future is expected
+    assert_eq!(
+        block_on(join(add_slowly(1, 1), AddSlowly::Start { a: 2, b: 2 })),
+        (2, 4)
+    );
+}
+
+pub fn main() {
+    blocking();
+    executor();
+    combinators();
+    state_machine();
+}
@@ -36,10 +36,16 @@ fn configuration() {
     #[cfg(not(target_os = "linux"))]
     fn are_you_not_on_linux() {}
 
+    //cfg! only evaluates to true/false, both branches must still compile
+    #[cfg(target_os = "linux")]
+    are_you_on_linux();
+    #[cfg(not(target_os = "linux"))]
+    are_you_not_on_linux();
+
     if cfg!(target_os = "linux") {
-        are_you_not_on_linux();
+        println!("linux");
     } else {
-        // are_you_on_linux();
+        println!("not linux");
     }
 }
 
@@ -0,0 +1,32 @@
+/*
+ * Chat binary: cargo run --bin chat -- serve [ADDRESS]   runs the server
+ *              cargo run --bin chat -- [ADDRESS]         connects the terminal client
+ * ADDRESS defaults to rust_by_example::capstone_chat::DEFAULT_ADDRESS
+ */
+
+use rust_by_example::capstone_chat::{client, Server, DEFAULT_ADDRESS};
+use std::env;
+use std::process;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let result = match args[..] {
+        ["serve"] | ["serve", _] => {
+            let address = args.get(1).copied().unwrap_or(DEFAULT_ADDRESS);
+            Server::bind(address).and_then(|server| {
+                println!("listening on {}", server.local_addr()?);
+                server.serve()
+            })
+        }
+        [] | [_] => client::run(args.first().copied().unwrap_or(DEFAULT_ADDRESS)),
+        _ => {
+            eprintln!("usage: chat [ADDRESS] | chat serve [ADDRESS]");
+            process::exit(2);
+        }
+    };
+    if let Err(err) = result {
+        eprintln!("chat: {}", err);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,90 @@
+/*
+ * Third binary of the package: cargo run --bin kvs -- [--file PATH] COMMAND
+ * Storage is rust_by_example::kv_store, this is argument handling and exit codes only
+ *
+ * Exit code: 0 done, 1 key not found (get, rm), 2 usage or store error
+ */
+
+use rust_by_example::kv_store::{Error, KvStore};
+use std::env;
+use std::process;
+
+const USAGE: &str = "usage: kvs [--file PATH] COMMAND
+commands:
+  get KEY          print the value of KEY
+  set KEY VALUE    store VALUE under KEY
+  rm KEY           remove KEY
+  list             print all keys, sorted
+  compact          rewrite the log without overwritten and removed records
+the log file defaults to ./kvs.log";
+
+fn usage(message: &str) -> ! {
+    eprintln!("kvs: {}\n{}", message, USAGE);
+    process::exit(2);
+}
+
+fn fail(path: &str, err: Error) -> ! {
+    match err {
+        //the io::Error says more than "log file error"
+        Error::Io(err) => eprintln!("kvs: {}: {}", path, err),
+        err => eprintln!("kvs: {}: {}", path, err),
+    }
+    process::exit(2);
+}
+
+fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let mut path = String::from("kvs.log");
+    if args.first().map(String::as_str) == Some("--file") {
+        if args.len() < 2 {
+            usage("--file needs a path");
+        }
+        path = args.remove(1);
+        args.remove(0);
+    }
+    if args.first().map(String::as_str) == Some("--help") {
+        println!("{}", USAGE);
+        return;
+    }
+
+    //checked before opening: a typo shouldn't create a log file
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args[..] {
+        ["get", _] | ["set", _, _] | ["rm", _] | ["list"] | ["compact"] => {}
+        [] => usage("missing command"),
+        [command, ..] => usage(&format!("bad arguments for {}", command)),
+    }
+
+    let mut store = KvStore::open(&path).unwrap_or_else(|err| fail(&path, err));
+    if let Some(recovery) = store.recovery() {
+        eprintln!(
+            "kvs: {}: dropped {} bytes of an incomplete last record ({})",
+            path, recovery.dropped_bytes, recovery.reason
+        );
+    }
+
+    let result = match args[..] {
+        ["get", key] => store
+            .get(key)
+            .and_then(|value| value.ok_or_else(|| Error::KeyNotFound(key.to_string())))
+            .map(|value| println!("{}", value)),
+        ["set", key, value] => store.set(key, value),
+        ["rm", key] => store.remove(key),
+        ["list"] => {
+            for key in store.keys() {
+                println!("{}", key);
+            }
+            Ok(())
+        }
+        _ => store.compact(),
+    };
+
+    match result {
+        Ok(()) => {}
+        Err(err @ Error::KeyNotFound(_)) => {
+            eprintln!("kvs: {}", err);
+            process::exit(1);
+        }
+        Err(err) => fail(&path, err),
+    }
+}
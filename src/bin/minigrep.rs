@@ -0,0 +1,60 @@
+/*
+ * Second binary of the package: cargo run --bin minigrep -- [OPTIONS] QUERY [PATH...]
+ * Only the process boundary lives here (environment, terminal, exit code),
+ * the rest is rust_by_example::minigrep
+ *
+ * Exit code like grep: 0 something matched, 1 nothing matched, 2 an error occurred
+ */
+
+use rust_by_example::minigrep::{self, Color, Config};
+use std::env;
+use std::io::{self, Write};
+use std::process;
+
+fn main() {
+    //IGNORE_CASE=1 minigrep ...: set at all, whatever the value
+    let ignore_case = env::var_os("IGNORE_CASE").is_some();
+    let config = Config::parse(env::args().skip(1), ignore_case).unwrap_or_else(|err| {
+        eprintln!("minigrep: {}\n{}", err, minigrep::USAGE);
+        process::exit(2);
+    });
+    if config.help {
+        println!("{}", minigrep::USAGE);
+        return;
+    }
+
+    let color = match config.color {
+        Color::Always => true,
+        Color::Never => false,
+        //no escape sequences when piped into another program or a file
+        Color::Auto => termion::is_tty(&io::stdout()),
+    };
+
+    //one lock and one buffer for all output instead of a flush per println!
+    let stdout = io::stdout();
+    let mut out = io::BufWriter::new(stdout.lock());
+    let result = minigrep::run(&config, color, &mut out).and_then(|summary| {
+        out.flush()?;
+        Ok(summary)
+    });
+
+    let code = match result {
+        Ok(summary) => {
+            for err in &summary.errors {
+                eprintln!("minigrep: {}", err);
+            }
+            match (summary.errors.is_empty(), summary.matched) {
+                (false, _) => 2,
+                (true, true) => 0,
+                (true, false) => 1,
+            }
+        }
+        //a closed pipe (minigrep ... | head) is not worth a message
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => 0,
+        Err(err) => {
+            eprintln!("minigrep: {}", err);
+            2
+        }
+    };
+    process::exit(code);
+}
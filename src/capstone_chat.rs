@@ -0,0 +1,67 @@
+/*
+ * Capstone: multi-client TCP chat over loopback, std only (plus termion for the client)
+ * - hub: one thread owns the room, connection threads talk to it through an mpsc channel
+ * - server: a thread per client reading lines, a writer thread per client draining its outbox
+ * - client: terminal UI with an output pane and an input line, `cargo run --bin chat`
+ * Pieces from earlier chapters: networking (TcpListener, TcpStream), std_library_types
+ * (mpsc channels, Arc<Mutex<_>>), capstone_interpreter::repl (LineEditor)
+ *
+ * Protocol, one line each way:
+ *   client: a nickname first, then messages, /list or /quit
+ *   server: "<nick> text" for messages, "* ..." for notices, "! ..." for errors
+ */
+
+pub mod client;
+mod hub;
+mod server;
+
+pub use hub::MAX_NICK;
+pub use crate::capstone_http::ShutdownHandle;
+pub use server::{Server, WELCOME};
+
+use std::io::{BufRead, BufReader, Lines, Write};
+use std::net::TcpStream;
+use std::thread;
+
+//where `chat serve` listens and the client connects when not told otherwise
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:7878";
+
+fn next(lines: &mut Lines<BufReader<TcpStream>>) -> String {
+    lines.next().unwrap().unwrap()
+}
+
+pub fn main() {
+    let server = Server::bind("127.0.0.1:0").unwrap();
+    let address = server.local_addr().unwrap();
+    let handle = server.shutdown_handle().unwrap();
+    let running = thread::spawn(move || server.serve());
+
+    let connect = |nick: &str| {
+        let stream = TcpStream::connect(address).unwrap();
+        let mut lines = BufReader::new(stream.try_clone().unwrap()).lines();
+        assert_eq!(next(&mut lines), WELCOME);
+        writeln!(&stream, "{}", nick).unwrap();
+        assert_eq!(next(&mut lines), format!("* {} joined", nick));
+        (stream, lines)
+    };
+    let (alice, mut alice_lines) = connect("alice");
+    let (bob, mut bob_lines) = connect("bob");
+    assert_eq!(next(&mut alice_lines), "* bob joined");
+
+    writeln!(&alice, "hello bob").unwrap();
+    //everyone gets every message, the sender included
+    assert_eq!(next(&mut alice_lines), "<alice> hello bob");
+    assert_eq!(next(&mut bob_lines), "<alice> hello bob");
+
+    writeln!(&bob, "/list").unwrap();
+    assert_eq!(next(&mut bob_lines), "* online: alice, bob");
+    writeln!(&bob, "/quit").unwrap();
+    assert_eq!(next(&mut bob_lines), "* bye");
+    assert!(bob_lines.next().is_none());
+    assert_eq!(next(&mut alice_lines), "* bob left");
+
+    handle.shutdown();
+    running.join().unwrap().unwrap();
+    assert_eq!(next(&mut alice_lines), "* server shutting down");
+    assert!(alice_lines.next().is_none());
+}
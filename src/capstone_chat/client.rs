@@ -0,0 +1,210 @@
+/*
+ * Terminal client: cargo run --bin chat -- [ADDRESS]
+ *   +--------------------------------+
+ *   | * alice joined                 |  output pane: the newest lines that fit
+ *   | <alice> hi                     |
+ *   |--------------------------------|
+ *   | > typed text_                  |  input pane: the REPL's LineEditor
+ *   +--------------------------------+
+ * Two feeder threads, network lines and key presses, send into one channel;
+ * the main thread owns the screen and redraws after every event
+ * Piped stdin (no terminal): plain lines both ways, for scripts
+ */
+
+use crate::capstone_interpreter::repl::{Action, LineEditor};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::mpsc;
+use std::thread;
+use termion::event::Key;
+use termion::input::TermRead;
+use termion::raw::IntoRawMode;
+use termion::screen::IntoAlternateScreen;
+
+const PROMPT: &str = "> ";
+//older lines are dropped, the pane only ever shows the end
+const SCROLLBACK: usize = 1000;
+
+enum UiEvent {
+    Line(String),
+    Key(Key),
+    Disconnected,
+}
+
+/// Screen contents, independent of the terminal.
+#[derive(Default)]
+pub struct Panes {
+    lines: Vec<String>,
+    pub editor: LineEditor,
+}
+
+//cut to `width` characters, not bytes: a multibyte character is one column here
+fn fit(line: &str, width: usize) -> &str {
+    match line.char_indices().nth(width) {
+        Some((end, _)) => &line[..end],
+        None => line,
+    }
+}
+
+impl Panes {
+    pub fn push(&mut self, line: String) {
+        self.lines.push(line);
+        if self.lines.len() > SCROLLBACK {
+            self.lines.remove(0);
+        }
+    }
+
+    /// The lines an output pane of `rows` rows shows, oldest first.
+    pub fn visible(&self, rows: usize) -> &[String] {
+        &self.lines[self.lines.len().saturating_sub(rows)..]
+    }
+
+    /// Output pane, ruler, input line; leaves the cursor in the input line.
+    pub fn render<W: Write>(&self, out: &mut W, width: u16, height: u16) -> io::Result<()> {
+        let rows = height.saturating_sub(2);
+        write!(out, "{}", termion::clear::All)?;
+        for (row, line) in self.visible(rows as usize).iter().enumerate() {
+            write!(
+                out,
+                "{}{}",
+                termion::cursor::Goto(1, row as u16 + 1),
+                fit(line, width as usize)
+            )?;
+        }
+        write!(
+            out,
+            "{}{}",
+            termion::cursor::Goto(1, rows + 1),
+            "-".repeat(width as usize)
+        )?;
+        //long input: only its end is shown
+        let input = self.editor.line();
+        let room = (width as usize).saturating_sub(PROMPT.len() + 1);
+        let skip = self.editor.cursor().saturating_sub(room);
+        let shown: String = input.chars().skip(skip).take(room).collect();
+        write!(
+            out,
+            "{}{}{}{}",
+            termion::cursor::Goto(1, rows + 2),
+            PROMPT,
+            shown,
+            termion::cursor::Goto(
+                (PROMPT.len() + self.editor.cursor() - skip) as u16 + 1,
+                rows + 2
+            )
+        )?;
+        out.flush()
+    }
+}
+
+fn run_piped(stream: TcpStream) -> io::Result<()> {
+    let reader = stream.try_clone()?;
+    let printer = thread::spawn(move || -> io::Result<()> {
+        for line in BufReader::new(reader).lines() {
+            println!("{}", line?);
+        }
+        Ok(())
+    });
+    let mut writer = &stream;
+    for line in io::stdin().lock().lines() {
+        writeln!(writer, "{}", line?)?;
+    }
+    writeln!(writer, "/quit")?;
+    printer.join().unwrap()
+}
+
+pub fn run<A: ToSocketAddrs>(address: A) -> io::Result<()> {
+    let stream = TcpStream::connect(address)?;
+    if !termion::is_tty(&io::stdin()) {
+        return run_piped(stream);
+    }
+
+    let (events, receiver) = mpsc::channel();
+    let network = events.clone();
+    let reader = stream.try_clone()?;
+    thread::spawn(move || {
+        for line in BufReader::new(reader).lines().map_while(Result::ok) {
+            if network.send(UiEvent::Line(line)).is_err() {
+                return;
+            }
+        }
+        let _ = network.send(UiEvent::Disconnected);
+    });
+    //blocked in stdin forever after the last key: it dies with the process
+    thread::spawn(move || {
+        for key in io::stdin().keys().map_while(Result::ok) {
+            if events.send(UiEvent::Key(key)).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut panes = Panes::default();
+    {
+        //raw mode and alternate screen are undone when these are dropped, errors included
+        let mut screen = io::stdout().into_raw_mode()?.into_alternate_screen()?;
+        let mut writer = &stream;
+        loop {
+            let (width, height) = termion::terminal_size()?;
+            panes.render(&mut screen, width, height)?;
+            match receiver.recv() {
+                Ok(UiEvent::Line(line)) => panes.push(line),
+                Ok(UiEvent::Key(key)) => match panes.editor.key(key) {
+                    Action::Submit(line) => writeln!(writer, "{}", line)?,
+                    //the server answers with "* bye" and closes: that ends the loop
+                    Action::Exit => writeln!(writer, "/quit")?,
+                    Action::Continue => {}
+                },
+                Ok(UiEvent::Disconnected) | Err(_) => break,
+            }
+        }
+    }
+    //the last words stay visible on the normal screen
+    for line in panes.visible(3) {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_pane_shows_the_newest_lines() {
+        let mut panes = Panes::default();
+        for i in 0..5 {
+            panes.push(format!("line {}", i));
+        }
+        assert_eq!(panes.visible(2), ["line 3", "line 4"]);
+        assert_eq!(panes.visible(10).len(), 5);
+        assert!(panes.visible(0).is_empty());
+
+        for i in 0..SCROLLBACK {
+            panes.push(i.to_string());
+        }
+        assert_eq!(panes.lines.len(), SCROLLBACK);
+        assert_eq!(panes.visible(1), [(SCROLLBACK - 1).to_string()]);
+    }
+
+    #[test]
+    fn lines_are_cut_by_characters() {
+        assert_eq!(fit("hello", 3), "hel");
+        assert_eq!(fit("hi", 3), "hi");
+        assert_eq!(fit("äöü", 2), "äö");
+    }
+
+    #[test]
+    fn render_places_input_below_the_ruler() {
+        let mut panes = Panes::default();
+        panes.push("* alice joined".to_string());
+        for c in "hey".chars() {
+            panes.editor.key(Key::Char(c));
+        }
+        let mut out = Vec::new();
+        panes.render(&mut out, 10, 4).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        //rows 1-2 output, row 3 ruler, row 4 input with the cursor after "hey"
+        assert!(out.contains("\x1b[1;1H* alice jo\x1b[3;1H----------\x1b[4;1H> hey\x1b[4;6H"));
+    }
+}
@@ -0,0 +1,225 @@
+/*
+ * Broadcast hub: one thread owns the room, everyone else talks to it through a channel
+ * - connection threads send Events, the hub answers through each client's outbox (a Sender)
+ * - no Mutex around the client list: only the hub thread ever touches it
+ * - every line goes through one queue, so all clients see the same order of messages
+ */
+
+use std::collections::HashMap;
+use std::sync::mpsc::{Receiver, Sender};
+
+pub const MAX_NICK: usize = 16;
+
+pub type ClientId = usize;
+
+pub enum Event {
+    //accepted or not is answered on `reply`, the reason for a refusal on `outbox`
+    Join {
+        id: ClientId,
+        nick: String,
+        outbox: Sender<String>,
+        reply: Sender<bool>,
+    },
+    Message {
+        id: ClientId,
+        text: String,
+    },
+    List {
+        id: ClientId,
+    },
+    //quit: said /quit and gets a goodbye, otherwise the connection just went away
+    Leave {
+        id: ClientId,
+        quit: bool,
+    },
+    Shutdown,
+}
+
+struct Member {
+    nick: String,
+    outbox: Sender<String>,
+}
+
+//control characters would reach every other client's terminal as they are:
+//ESC sequences could move its cursor, recolor it or clear the screen
+fn printable(text: &str) -> String {
+    text.chars().filter(|c| !c.is_control()).collect()
+}
+
+fn check_nick(nick: &str) -> Result<(), String> {
+    if nick.is_empty() {
+        Err("empty nickname".to_string())
+    } else if nick.chars().count() > MAX_NICK {
+        Err(format!("nickname longer than {} characters", MAX_NICK))
+    } else if !nick
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+    {
+        Err("nickname may only contain letters, digits, _ and -".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct Hub {
+    members: HashMap<ClientId, Member>,
+}
+
+impl Hub {
+    //a closed outbox means that client is on its way out: its Leave follows
+    fn broadcast(&self, line: &str) {
+        for member in self.members.values() {
+            let _ = member.outbox.send(line.to_string());
+        }
+    }
+
+    fn tell(&self, id: ClientId, line: String) {
+        if let Some(member) = self.members.get(&id) {
+            let _ = member.outbox.send(line);
+        }
+    }
+
+    /// Handles one event; false once the hub should stop.
+    pub fn handle(&mut self, event: Event) -> bool {
+        match event {
+            Event::Join {
+                id,
+                nick,
+                outbox,
+                reply,
+            } => {
+                let refusal = check_nick(&nick).err().or_else(|| {
+                    self.members
+                        .values()
+                        .any(|member| member.nick == nick)
+                        .then(|| format!("nickname {} is taken", nick))
+                });
+                if let Some(reason) = refusal {
+                    let _ = outbox.send(format!("! {}", reason));
+                    let _ = reply.send(false);
+                    return true;
+                }
+                self.members.insert(id, Member { nick, outbox });
+                let _ = reply.send(true);
+                self.broadcast(&format!("* {} joined", self.members[&id].nick));
+            }
+            Event::Message { id, text } => {
+                if let Some(member) = self.members.get(&id) {
+                    self.broadcast(&format!("<{}> {}", member.nick, printable(&text)));
+                }
+            }
+            Event::List { id } => {
+                let mut nicks: Vec<&str> = self
+                    .members
+                    .values()
+                    .map(|member| member.nick.as_str())
+                    .collect();
+                nicks.sort_unstable();
+                self.tell(id, format!("* online: {}", nicks.join(", ")));
+            }
+            Event::Leave { id, quit } => {
+                //dropping the Member drops the hub's Sender: the writer thread can finish
+                if let Some(member) = self.members.remove(&id) {
+                    if quit {
+                        let _ = member.outbox.send("* bye".to_string());
+                    }
+                    self.broadcast(&format!("* {} left", member.nick));
+                }
+            }
+            Event::Shutdown => {
+                self.broadcast("* server shutting down");
+                self.members.clear();
+                return false;
+            }
+        }
+        true
+    }
+
+    /// The hub thread: runs until Shutdown or until every Sender of `events` is gone.
+    pub fn run(mut self, events: Receiver<Event>) {
+        for event in events {
+            if !self.handle(event) {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::{self, Receiver};
+
+    fn join(hub: &mut Hub, id: ClientId, nick: &str) -> (bool, Receiver<String>) {
+        let (outbox, inbox) = mpsc::channel();
+        let (reply, accepted) = mpsc::channel();
+        hub.handle(Event::Join {
+            id,
+            nick: nick.to_string(),
+            outbox,
+            reply,
+        });
+        (accepted.recv().unwrap(), inbox)
+    }
+
+    fn drain(inbox: &Receiver<String>) -> Vec<String> {
+        inbox.try_iter().collect()
+    }
+
+    #[test]
+    fn nicknames() {
+        assert!(check_nick("ferris_the-crab2").is_ok());
+        assert!(check_nick("").is_err());
+        assert!(check_nick("two words").is_err());
+        assert!(check_nick(&"x".repeat(MAX_NICK + 1)).is_err());
+
+        let mut hub = Hub::default();
+        let (accepted, _alice) = join(&mut hub, 1, "alice");
+        assert!(accepted);
+        let (accepted, refused) = join(&mut hub, 2, "alice");
+        assert!(!accepted);
+        assert_eq!(drain(&refused), ["! nickname alice is taken"]);
+    }
+
+    #[test]
+    fn messages_reach_everyone_in_one_order() {
+        let mut hub = Hub::default();
+        let (_, alice) = join(&mut hub, 1, "alice");
+        let (_, bob) = join(&mut hub, 2, "bob");
+        hub.handle(Event::Message {
+            id: 2,
+            text: "hi".into(),
+        });
+        hub.handle(Event::List { id: 1 });
+        hub.handle(Event::Leave { id: 2, quit: true });
+
+        assert_eq!(
+            drain(&alice),
+            [
+                "* alice joined",
+                "* bob joined",
+                "<bob> hi",
+                "* online: alice, bob",
+                "* bob left"
+            ]
+        );
+        assert_eq!(drain(&bob), ["* bob joined", "<bob> hi", "* bye"]);
+        //the hub let go of bob's outbox
+        assert!(bob.recv().is_err());
+    }
+
+    #[test]
+    fn control_characters_are_not_passed_on() {
+        let mut hub = Hub::default();
+        let (_, alice) = join(&mut hub, 1, "alice");
+        hub.handle(Event::Message {
+            id: 1,
+            text: "\u{1b}[2Jcleared\u{7}\tscreen \u{9b}31m".into(),
+        });
+        assert_eq!(
+            drain(&alice),
+            ["* alice joined", "<alice> [2Jclearedscreen 31m"]
+        );
+    }
+}
@@ -0,0 +1,166 @@
+/*
+ * Chat server: one thread per client connection, plus one writer thread per client
+ * - the connection thread reads lines and turns them into hub Events
+ * - the writer thread drains the client's outbox into the socket, so a slow client
+ *   never blocks the hub; it ends when the last Sender of the outbox is dropped
+ * - shutdown: capstone_http's ShutdownHandle, a flag plus a throwaway connection to wake
+ *   accept; then the hub says goodbye and every socket's read side is shut down
+ *   (the one place with shared state: Arc<Mutex<HashMap>> of open connections)
+ */
+
+use super::hub::{ClientId, Event, Hub};
+use crate::capstone_http::ShutdownHandle;
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+pub const WELCOME: &str = "* welcome, enter a nickname";
+
+type Connections = Arc<Mutex<HashMap<ClientId, TcpStream>>>;
+
+pub struct Server {
+    listener: TcpListener,
+    handle: ShutdownHandle,
+    connections: Connections,
+}
+
+impl Server {
+    pub fn bind<A: ToSocketAddrs>(address: A) -> io::Result<Server> {
+        let listener = TcpListener::bind(address)?;
+        Ok(Server {
+            handle: ShutdownHandle::new(&listener)?,
+            listener,
+            connections: Arc::default(),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    pub fn shutdown_handle(&self) -> io::Result<ShutdownHandle> {
+        Ok(self.handle.clone())
+    }
+
+    /// Accepts clients until shut down; returns once every client thread has finished.
+    pub fn serve(self) -> io::Result<()> {
+        let (events, receiver) = mpsc::channel();
+        let hub = thread::Builder::new()
+            .name("hub".to_string())
+            .spawn(move || Hub::default().run(receiver))?;
+
+        let mut clients = Vec::new();
+        for (id, stream) in self.listener.incoming().enumerate() {
+            if self.handle.is_stopping() {
+                break;
+            }
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(err) => {
+                    eprintln!("accept failed: {}", err);
+                    continue;
+                }
+            };
+            self.connections
+                .lock()
+                .unwrap()
+                .insert(id, stream.try_clone()?);
+            let events = events.clone();
+            let connections = Arc::clone(&self.connections);
+            clients.push(thread::spawn(move || {
+                if let Err(err) = handle_client(id, stream, &events) {
+                    eprintln!("client {} failed: {}", id, err);
+                }
+                connections.lock().unwrap().remove(&id);
+            }));
+            //forget the threads of clients that are gone
+            clients.retain(|client| !client.is_finished());
+        }
+
+        //the goodbye is queued in every outbox before the reads are cut
+        let _ = events.send(Event::Shutdown);
+        hub.join().unwrap();
+        for stream in self.connections.lock().unwrap().values() {
+            let _ = stream.shutdown(Shutdown::Read);
+        }
+        for client in clients {
+            client.join().unwrap();
+        }
+        Ok(())
+    }
+}
+
+fn handle_client(id: ClientId, stream: TcpStream, events: &Sender<Event>) -> io::Result<()> {
+    //short interactive lines: send each right away instead of waiting to batch them (Nagle)
+    stream.set_nodelay(true)?;
+    let (outbox, inbox) = mpsc::channel::<String>();
+    let mut socket = stream.try_clone()?;
+    let writer = thread::spawn(move || -> io::Result<()> {
+        for line in inbox {
+            socket.write_all(format!("{}\n", line).as_bytes())?;
+        }
+        Ok(())
+    });
+    let _ = outbox.send(WELCOME.to_string());
+
+    let mut joined = false;
+    let mut quit = false;
+    for line in BufReader::new(&stream).lines() {
+        //reset by the client or cut by shutdown: both mean this client is gone
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        //telnet and nc on some systems send \r\n
+        let line = line.trim_end_matches('\r');
+        if line == "/quit" {
+            quit = true;
+            break;
+        }
+
+        let event = if !joined {
+            let (reply, accepted) = mpsc::channel();
+            let join = Event::Join {
+                id,
+                nick: line.to_string(),
+                outbox: outbox.clone(),
+                reply,
+            };
+            if events.send(join).is_err() {
+                break;
+            }
+            //no answer: the hub has stopped
+            joined = accepted.recv().unwrap_or(false);
+            continue;
+        } else if line == "/list" {
+            Event::List { id }
+        } else if line.starts_with('/') {
+            let _ = outbox.send(format!("! unknown command {}", line));
+            continue;
+        } else if line.trim().is_empty() {
+            continue;
+        } else {
+            Event::Message {
+                id,
+                text: line.to_string(),
+            }
+        };
+        if events.send(event).is_err() {
+            break;
+        }
+    }
+
+    if joined {
+        let _ = events.send(Event::Leave { id, quit });
+    } else if quit {
+        let _ = outbox.send("* bye".to_string());
+    }
+    drop(outbox);
+    //a write error only means the client went away first
+    let _ = writer.join().unwrap();
+    let _ = stream.shutdown(Shutdown::Both);
+    Ok(())
+}
@@ -0,0 +1,69 @@
+/*
+ * Capstone: multithreaded HTTP/1.1 static file server, std only
+ * - thread_pool: fixed workers fed through an mpsc channel, Drop drains and joins
+ * - http: request parser over any BufRead, response writer over any Write
+ * - server: TcpListener accept loop, files served from a root directory
+ * Pieces from earlier chapters: networking (TcpListener), std_io (fs, BufReader),
+ * error_hierarchies (ParseError with source), std_library_types (Arc, channels)
+ *
+ * Not covered: keep-alive, chunked bodies, percent-decoding, TLS
+ */
+
+pub mod http;
+mod server;
+mod thread_pool;
+
+pub use server::{Server, ShutdownHandle};
+pub use thread_pool::ThreadPool;
+
+use std::fs;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::thread;
+
+//One raw request, the whole response back (the server closes the connection)
+fn fetch(address: std::net::SocketAddr, request: &str) -> String {
+    let mut stream = TcpStream::connect(address).unwrap();
+    stream.write_all(request.as_bytes()).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+}
+
+pub fn main() {
+    let root = std::env::temp_dir().join(format!("rbe-capstone-http-{}", std::process::id()));
+    fs::create_dir_all(root.join("docs")).unwrap();
+    fs::write(root.join("index.html"), "<h1>hello</h1>").unwrap();
+    fs::write(root.join("docs/notes.txt"), "served by a thread pool").unwrap();
+
+    //port 0: the OS picks a free one
+    let server = Server::bind("127.0.0.1:0", &root, 4).unwrap();
+    let address = server.local_addr().unwrap();
+    let handle = server.shutdown_handle().unwrap();
+    let running = thread::spawn(move || server.serve());
+
+    let index = fetch(address, "GET / HTTP/1.1\r\nHost: localhost\r\n\r\n");
+    assert!(index.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(index.contains("Content-Type: text/html; charset=utf-8\r\n"));
+    assert!(index.ends_with("\r\n\r\n<h1>hello</h1>"));
+
+    let notes = fetch(address, "GET /docs/notes.txt HTTP/1.1\r\n\r\n");
+    assert!(notes.ends_with("served by a thread pool"));
+
+    let missing = fetch(address, "GET /nothing.html HTTP/1.1\r\n\r\n");
+    assert!(missing.starts_with("HTTP/1.1 404 Not Found\r\n"));
+
+    //requests in parallel: one thread per client, the pool answers them all
+    let clients: Vec<_> = (0..8)
+        .map(|_| thread::spawn(move || fetch(address, "HEAD /docs/notes.txt HTTP/1.1\r\n\r\n")))
+        .collect();
+    for client in clients {
+        let response = client.join().unwrap();
+        assert!(response.contains("Content-Length: 23\r\n"));
+        assert!(response.ends_with("\r\n\r\n"));
+    }
+
+    handle.shutdown();
+    running.join().unwrap().unwrap();
+    fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,343 @@
+/*
+ * Minimal HTTP/1.1 messages
+ *   request:  METHOD SP PATH SP HTTP/1.1 CRLF, headers (Name: value CRLF)*, CRLF, body
+ *   response: HTTP/1.1 SP STATUS SP REASON CRLF, headers, CRLF, body
+ * Body length comes from Content-Length only (no chunked encoding),
+ * one request per connection (responses say Connection: close)
+ */
+
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, Read, Write};
+
+//limits: a client can't make the server buffer unbounded input
+const MAX_LINE: usize = 8 * 1024;
+const MAX_HEADERS: usize = 100;
+const MAX_BODY: usize = 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub version: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// Header value by case-insensitive name.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Debug)]
+pub enum ParseError {
+    Io(io::Error),
+    //connection closed before a complete request line
+    Empty,
+    BadRequestLine(String),
+    BadHeader(String),
+    UnsupportedVersion(String),
+    TooLarge,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Io(_) => write!(f, "error reading the request"),
+            ParseError::Empty => write!(f, "empty request"),
+            ParseError::BadRequestLine(line) => write!(f, "malformed request line {:?}", line),
+            ParseError::BadHeader(line) => write!(f, "malformed header {:?}", line),
+            ParseError::UnsupportedVersion(version) => {
+                write!(f, "unsupported version {}", version)
+            }
+            ParseError::TooLarge => write!(f, "request is too large"),
+        }
+    }
+}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ParseError {
+    fn from(err: io::Error) -> ParseError {
+        ParseError::Io(err)
+    }
+}
+
+//One line without the CRLF (a bare LF is accepted too), None at end of stream
+fn read_line<R: BufRead>(reader: &mut R) -> Result<Option<String>, ParseError> {
+    let mut line = Vec::new();
+    let read = reader
+        .take(MAX_LINE as u64 + 1)
+        .read_until(b'\n', &mut line)?;
+    if read == 0 {
+        return Ok(None);
+    }
+    if line.len() > MAX_LINE {
+        return Err(ParseError::TooLarge);
+    }
+    while line
+        .last()
+        .is_some_and(|&byte| byte == b'\n' || byte == b'\r')
+    {
+        line.pop();
+    }
+    String::from_utf8(line)
+        .map(Some)
+        .map_err(|err| ParseError::BadHeader(String::from_utf8_lossy(err.as_bytes()).into()))
+}
+
+/// Reads one request from `reader`.
+///
+/// ```
+/// use rust_by_example::capstone_http::http::parse_request;
+///
+/// let raw = "POST /echo HTTP/1.1\r\nHost: localhost\r\nContent-Length: 5\r\n\r\nhello";
+/// let request = parse_request(&mut raw.as_bytes()).unwrap();
+/// assert_eq!((request.method.as_str(), request.path.as_str()), ("POST", "/echo"));
+/// assert_eq!(request.header("host"), Some("localhost"));
+/// assert_eq!(request.body, b"hello");
+/// ```
+pub fn parse_request<R: BufRead>(reader: &mut R) -> Result<Request, ParseError> {
+    let request_line = read_line(reader)?.ok_or(ParseError::Empty)?;
+    let parts: Vec<&str> = request_line.split(' ').collect();
+    let (method, path, version) = match parts[..] {
+        [method, path, version] if !method.is_empty() && path.starts_with('/') => {
+            (method, path, version)
+        }
+        _ => return Err(ParseError::BadRequestLine(request_line)),
+    };
+    if version != "HTTP/1.1" && version != "HTTP/1.0" {
+        return Err(ParseError::UnsupportedVersion(version.to_string()));
+    }
+
+    let mut headers = Vec::new();
+    loop {
+        let line = read_line(reader)?.ok_or(ParseError::Empty)?;
+        if line.is_empty() {
+            break;
+        }
+        if headers.len() == MAX_HEADERS {
+            return Err(ParseError::TooLarge);
+        }
+        let (name, value) = line
+            .split_once(':')
+            .filter(|(name, _)| !name.is_empty() && !name.contains(' '))
+            .ok_or_else(|| ParseError::BadHeader(line.clone()))?;
+        headers.push((name.to_string(), value.trim().to_string()));
+    }
+
+    let mut request = Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        version: version.to_string(),
+        headers,
+        body: Vec::new(),
+    };
+
+    if let Some(length) = request.header("Content-Length") {
+        let length: usize = length
+            .parse()
+            .map_err(|_| ParseError::BadHeader(format!("Content-Length: {}", length)))?;
+        if length > MAX_BODY {
+            return Err(ParseError::TooLarge);
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body)?;
+        request.body = body;
+    }
+    Ok(request)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+pub fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        413 => "Payload Too Large",
+        500 => "Internal Server Error",
+        505 => "HTTP Version Not Supported",
+        _ => "Unknown",
+    }
+}
+
+impl Response {
+    pub fn new(status: u16) -> Response {
+        Response {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    //plain text response with the reason phrase as body
+    pub fn error(status: u16) -> Response {
+        Response::new(status)
+            .header("Content-Type", "text/plain; charset=utf-8")
+            .body(format!("{} {}\n", status, reason(status)))
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Response {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn body<B: Into<Vec<u8>>>(mut self, body: B) -> Response {
+        self.body = body.into();
+        self
+    }
+
+    /// Writes status line, headers, Content-Length, Connection and the body (unless `head_only`).
+    ///
+    /// ```
+    /// use rust_by_example::capstone_http::http::Response;
+    ///
+    /// let mut out = Vec::new();
+    /// Response::new(200).body("hi").write_to(&mut out, false).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(out).unwrap(),
+    ///     "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nhi"
+    /// );
+    /// ```
+    pub fn write_to<W: Write>(&self, out: &mut W, head_only: bool) -> io::Result<()> {
+        write!(out, "HTTP/1.1 {} {}\r\n", self.status, reason(self.status))?;
+        for (name, value) in &self.headers {
+            write!(out, "{}: {}\r\n", name, value)?;
+        }
+        //HEAD: same headers as GET, Content-Length included, no body
+        write!(out, "Content-Length: {}\r\n", self.body.len())?;
+        write!(out, "Connection: close\r\n\r\n")?;
+        if !head_only {
+            out.write_all(&self.body)?;
+        }
+        out.flush()
+    }
+}
+
+impl From<&ParseError> for Response {
+    fn from(err: &ParseError) -> Response {
+        match err {
+            ParseError::TooLarge => Response::error(413),
+            ParseError::UnsupportedVersion(_) => Response::error(505),
+            //the client stopped sending: its request is cut short, or it took too long
+            ParseError::Io(err) => match err.kind() {
+                io::ErrorKind::UnexpectedEof => Response::error(400),
+                io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => Response::error(408),
+                _ => Response::error(500),
+            },
+            _ => Response::error(400),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(raw: &str) -> Result<Request, ParseError> {
+        parse_request(&mut raw.as_bytes())
+    }
+
+    #[test]
+    fn parses_get_without_body() {
+        let request = parse("GET /index.html HTTP/1.1\r\nAccept: */*\r\n\r\n").unwrap();
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.path, "/index.html");
+        assert_eq!(request.headers, [("Accept".to_string(), "*/*".to_string())]);
+        assert!(request.body.is_empty());
+    }
+
+    #[test]
+    fn accepts_bare_line_feeds() {
+        let request = parse("GET / HTTP/1.0\nX-Test:  padded  \n\n").unwrap();
+        assert_eq!(request.header("x-test"), Some("padded"));
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        assert!(matches!(parse(""), Err(ParseError::Empty)));
+        assert!(matches!(
+            parse("GET\r\n\r\n"),
+            Err(ParseError::BadRequestLine(_))
+        ));
+        assert!(matches!(
+            parse("GET no-slash HTTP/1.1\r\n\r\n"),
+            Err(ParseError::BadRequestLine(_))
+        ));
+        assert!(matches!(
+            parse("GET / HTTP/2\r\n\r\n"),
+            Err(ParseError::UnsupportedVersion(_))
+        ));
+        assert!(matches!(
+            parse("GET / HTTP/1.1\r\nno colon\r\n\r\n"),
+            Err(ParseError::BadHeader(_))
+        ));
+        //headers never terminated
+        assert!(matches!(
+            parse("GET / HTTP/1.1\r\nHost: x\r\n"),
+            Err(ParseError::Empty)
+        ));
+    }
+
+    #[test]
+    fn body_shorter_than_content_length() {
+        let error = parse("POST / HTTP/1.1\r\nContent-Length: 10\r\n\r\nshort").unwrap_err();
+        assert!(
+            matches!(&error, ParseError::Io(err) if err.kind() == io::ErrorKind::UnexpectedEof)
+        );
+        assert_eq!(Response::from(&error).status, 400);
+    }
+
+    #[test]
+    fn io_errors_by_whose_fault_they_are() {
+        let status = |kind: io::ErrorKind| Response::from(&ParseError::Io(kind.into())).status;
+        //a read timeout shows up as WouldBlock on Unix, TimedOut on Windows
+        assert_eq!(status(io::ErrorKind::WouldBlock), 408);
+        assert_eq!(status(io::ErrorKind::TimedOut), 408);
+        assert_eq!(status(io::ErrorKind::UnexpectedEof), 400);
+        assert_eq!(status(io::ErrorKind::PermissionDenied), 500);
+    }
+
+    #[test]
+    fn limits_are_enforced() {
+        let long_line = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(MAX_LINE));
+        assert!(matches!(parse(&long_line), Err(ParseError::TooLarge)));
+        let huge_body = format!(
+            "POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            MAX_BODY + 1
+        );
+        let error = parse(&huge_body).unwrap_err();
+        assert_eq!(Response::from(&error).status, 413);
+    }
+
+    #[test]
+    fn head_response_has_no_body() {
+        let mut out = Vec::new();
+        Response::error(404).write_to(&mut out, true).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.starts_with("HTTP/1.1 404 Not Found\r\n"));
+        assert!(text.contains("Content-Length: 14\r\n"));
+        assert!(text.ends_with("\r\n\r\n"));
+    }
+}
@@ -0,0 +1,202 @@
+/*
+ * Static file server
+ * - the accepting thread only hands connections to the pool, workers parse and respond
+ * - GET and HEAD only, paths are resolved inside the root directory, "/" is index.html
+ * - shutdown: a flag checked after every accept; the handle sets it and connects once
+ *   to wake the blocked accept, then serve() returns and the pool drains and joins
+ */
+
+use super::http::{self, Request, Response};
+use super::ThreadPool;
+use std::fs;
+use std::io::{self, BufReader};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+//a client that connects and stays silent holds a worker at most this long
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub struct Server {
+    listener: TcpListener,
+    root: Arc<PathBuf>,
+    pool: ThreadPool,
+    handle: ShutdownHandle,
+}
+
+/// Stops a running `Server` from another thread; capstone_chat's server stops the same way.
+#[derive(Clone)]
+pub struct ShutdownHandle {
+    address: SocketAddr,
+    stopping: Arc<AtomicBool>,
+}
+
+impl ShutdownHandle {
+    //for the server accepting on `listener`, which checks is_stopping() after every accept
+    pub(crate) fn new(listener: &TcpListener) -> io::Result<ShutdownHandle> {
+        Ok(ShutdownHandle {
+            address: listener.local_addr()?,
+            stopping: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    pub(crate) fn is_stopping(&self) -> bool {
+        self.stopping.load(Ordering::SeqCst)
+    }
+
+    pub fn shutdown(&self) {
+        self.stopping.store(true, Ordering::SeqCst);
+        //accept() is blocking: a throwaway connection makes it return
+        let _ = TcpStream::connect(self.address);
+    }
+}
+
+impl Server {
+    pub fn bind<A: ToSocketAddrs>(address: A, root: &Path, workers: usize) -> io::Result<Server> {
+        let listener = TcpListener::bind(address)?;
+        Ok(Server {
+            handle: ShutdownHandle::new(&listener)?,
+            listener,
+            root: Arc::new(root.to_path_buf()),
+            pool: ThreadPool::new(workers),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    pub fn shutdown_handle(&self) -> io::Result<ShutdownHandle> {
+        Ok(self.handle.clone())
+    }
+
+    /// Accepts connections until shut down; returns after every accepted request is answered.
+    pub fn serve(self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            if self.handle.is_stopping() {
+                break;
+            }
+            let stream = match stream {
+                Ok(stream) => stream,
+                //e.g. the client reset the connection before accept: not fatal for the server
+                Err(err) => {
+                    eprintln!("accept failed: {}", err);
+                    continue;
+                }
+            };
+            let root = Arc::clone(&self.root);
+            self.pool.execute(move || {
+                if let Err(err) = handle_connection(stream, &root) {
+                    eprintln!("connection failed: {}", err);
+                }
+            });
+        }
+        //self.pool is dropped here: queued connections are still served
+        Ok(())
+    }
+}
+
+fn handle_connection(stream: TcpStream, root: &Path) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let (response, head_only) = match http::parse_request(&mut BufReader::new(&stream)) {
+        Ok(request) => (respond(&request, root), request.method == "HEAD"),
+        //closed without sending anything: nobody to answer
+        Err(http::ParseError::Empty) => return Ok(()),
+        Err(err) => (Response::from(&err), false),
+    };
+    //&TcpStream is Write as well: no second handle needed
+    response.write_to(&mut &stream, head_only)
+}
+
+fn respond(request: &Request, root: &Path) -> Response {
+    if request.method != "GET" && request.method != "HEAD" {
+        return Response::error(405).header("Allow", "GET, HEAD");
+    }
+    let mut path = match resolve(root, &request.path) {
+        Some(path) => path,
+        None => return Response::error(403),
+    };
+    if path.is_dir() {
+        path.push("index.html");
+    }
+    match fs::read(&path) {
+        Ok(contents) => Response::new(200)
+            .header("Content-Type", content_type(&path))
+            .body(contents),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Response::error(404),
+        Err(_) => Response::error(500),
+    }
+}
+
+//Maps the request path under root; None if it tries to leave it
+fn resolve(root: &Path, request_path: &str) -> Option<PathBuf> {
+    //the query string doesn't name a file
+    let path = request_path.split('?').next().unwrap_or_default();
+    let mut resolved = root.to_path_buf();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            //no "..", and no separators or drive prefixes a segment could sneak in
+            ".." => return None,
+            _ if segment.contains('\\') || segment.contains(':') => return None,
+            _ => resolved.push(segment),
+        }
+    }
+    Some(resolved)
+}
+
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("html") | Some("htm") => "text/html; charset=utf-8",
+        Some("css") => "text/css",
+        Some("js") => "text/javascript",
+        Some("json") => "application/json",
+        Some("txt") => "text/plain; charset=utf-8",
+        Some("png") => "image/png",
+        Some("svg") => "image/svg+xml",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_inside_root() {
+        let root = Path::new("/srv/www");
+        assert_eq!(resolve(root, "/"), Some(PathBuf::from("/srv/www")));
+        assert_eq!(
+            resolve(root, "/css/./site.css?v=2"),
+            Some(PathBuf::from("/srv/www/css/site.css"))
+        );
+        assert_eq!(resolve(root, "/../etc/passwd"), None);
+        assert_eq!(resolve(root, "/a/../../b"), None);
+        assert_eq!(resolve(root, "/..\\secret"), None);
+    }
+
+    #[test]
+    fn content_type_by_extension() {
+        assert_eq!(
+            content_type(Path::new("index.html")),
+            "text/html; charset=utf-8"
+        );
+        assert_eq!(content_type(Path::new("logo.png")), "image/png");
+        assert_eq!(
+            content_type(Path::new("Makefile")),
+            "application/octet-stream"
+        );
+    }
+
+    #[test]
+    fn only_get_and_head() {
+        let request = http::parse_request(&mut &b"DELETE / HTTP/1.1\r\n\r\n"[..]).unwrap();
+        let response = respond(&request, Path::new("/nonexistent"));
+        assert_eq!(response.status, 405);
+        assert!(response
+            .headers
+            .contains(&("Allow".into(), "GET, HEAD".into())));
+    }
+}
@@ -0,0 +1,167 @@
+/*
+ * Fixed number of worker threads sharing one job queue
+ * - jobs are boxed closures sent through an mpsc channel
+ * - the single Receiver is shared behind Arc<Mutex<..>>: one worker takes a job at a time
+ * - shutdown: dropping the Sender closes the channel, recv() fails once the queue is empty,
+ *   workers leave their loops, Drop joins them
+ */
+
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+struct Worker {
+    id: usize,
+    //Option: Drop takes the handle out to join it
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Worker {
+    fn new(id: usize, receiver: Arc<Mutex<Receiver<Job>>>) -> Worker {
+        let thread = thread::Builder::new()
+            .name(format!("worker-{}", id))
+            .spawn(move || loop {
+                //the guard is a temporary: the lock is released before the job runs
+                let message = receiver.lock().unwrap().recv();
+                match message {
+                    Ok(job) => job(),
+                    //channel closed and drained: shut down
+                    Err(_) => break,
+                }
+            })
+            .expect("failed to spawn a worker thread");
+        Worker {
+            id,
+            thread: Some(thread),
+        }
+    }
+}
+
+/// Runs closures on a fixed set of threads; waits for queued jobs when dropped.
+///
+/// ```
+/// use rust_by_example::capstone_http::ThreadPool;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::sync::Arc;
+///
+/// let counter = Arc::new(AtomicUsize::new(0));
+/// {
+///     let pool = ThreadPool::new(4);
+///     for _ in 0..100 {
+///         let counter = Arc::clone(&counter);
+///         pool.execute(move || {
+///             counter.fetch_add(1, Ordering::SeqCst);
+///         });
+///     }
+/// } //pool dropped: all 100 jobs have run
+/// assert_eq!(counter.load(Ordering::SeqCst), 100);
+/// ```
+pub struct ThreadPool {
+    workers: Vec<Worker>,
+    //Option: Drop takes it out to close the channel before joining
+    sender: Option<Sender<Job>>,
+}
+
+impl ThreadPool {
+    /// Creates a pool with `size` workers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    pub fn new(size: usize) -> ThreadPool {
+        assert!(size > 0, "thread pool needs at least one worker");
+
+        let (sender, receiver) = mpsc::channel();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers = (0..size)
+            .map(|id| Worker::new(id, Arc::clone(&receiver)))
+            .collect();
+        ThreadPool {
+            workers,
+            sender: Some(sender),
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.workers.len()
+    }
+
+    pub fn execute<F>(&self, job: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.sender
+            .as_ref()
+            .expect("sender is only taken in drop")
+            .send(Box::new(job))
+            .expect("all workers have stopped");
+    }
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        drop(self.sender.take());
+        for worker in &mut self.workers {
+            if let Some(thread) = worker.thread.take() {
+                //a panicking job kills its worker: report, keep joining the others
+                if thread.join().is_err() {
+                    eprintln!("worker {} panicked", worker.id);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ThreadPool;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[test]
+    #[should_panic(expected = "at least one worker")]
+    fn empty_pool_is_rejected() {
+        ThreadPool::new(0);
+    }
+
+    #[test]
+    fn jobs_run_in_parallel() {
+        let pool = ThreadPool::new(2);
+        let (done, finished) = mpsc::channel();
+        //each job signals the other, then waits for the other's signal:
+        //only succeeds if both run at the same time
+        let (ping, pinged) = mpsc::channel();
+        let (pong, ponged) = mpsc::channel();
+        for (signal, wait) in [(ping, ponged), (pong, pinged)] {
+            let done = done.clone();
+            pool.execute(move || {
+                signal.send(()).unwrap();
+                let met = wait.recv_timeout(Duration::from_secs(5)).is_ok();
+                done.send(met).unwrap();
+            });
+        }
+        let results: Vec<bool> = finished.iter().take(2).collect();
+        assert_eq!(results, [true, true]);
+        assert_eq!(pool.size(), 2);
+    }
+
+    #[test]
+    fn drop_waits_for_queued_jobs() {
+        let (sender, receiver) = mpsc::channel();
+        {
+            let pool = ThreadPool::new(1);
+            for i in 0..5 {
+                let sender = sender.clone();
+                pool.execute(move || {
+                    std::thread::sleep(Duration::from_millis(5));
+                    sender.send(i).unwrap();
+                });
+            }
+        }
+        drop(sender);
+        //single worker: jobs run in submission order
+        assert_eq!(receiver.iter().collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+    }
+}
@@ -0,0 +1,98 @@
+/*
+ * Capstone: interpreter for a small expression language
+ *   let fib = fn(n) { if n < 2 { n } else { fib(n - 1) + fib(n - 2) } };
+ *   print("fib(10) =", fib(10));
+ * Integers, booleans, strings, blocks, if/else, while, closures, assignment
+ *
+ * source --lexer--> tokens --parser--> Box-based AST --eval--> Value
+ * - lexer: Iterator over tokens with byte spans (traits: Iterator, Display)
+ * - parser: recursive descent, one method per precedence level
+ * - ast: recursive enums behind Box (custom_types::enums)
+ * - eval: match on the tree (flow_of_control::match_and_destructuring),
+ *   environments as Rc<RefCell<Env>> chains
+ * - error: one Error with a Span for all phases, rendered with the source line (error_handling)
+ * - repl: `cargo run -- repl`, line editing with termion
+ * Test suite: every .expr file in tests/expr, with the expected output next to it in .out
+ */
+
+pub mod ast;
+mod error;
+mod eval;
+mod lexer;
+mod parser;
+pub mod repl;
+
+pub use error::{Error, ErrorKind, Result, Span};
+pub use eval::{with_stack, Builtin, Closure, Env, Interpreter, Value, STACK_SIZE};
+pub use lexer::{Lexer, Token, TokenKind};
+pub use parser::parse;
+
+//subcommand: main() starts the REPL instead of the chapters
+pub const REPL_COMMAND: &str = "repl";
+
+/// Everything a program shows: `print` output, then the final value (unless unit)
+/// or the rendered error. Runs `with_stack`: recursion goes thousands of calls deep.
+///
+/// ```
+/// use rust_by_example::capstone_interpreter::run_program;
+///
+/// assert_eq!(run_program("print(1); 1 + 1"), "1\n2\n");
+/// assert_eq!(
+///     run_program("print(1); 1 + true"),
+///     "1\nerror at 1:11: cannot apply `+` to int and bool\n  print(1); 1 + true\n            ^^^^^^^^\n"
+/// );
+/// ```
+pub fn run_program(source: &str) -> String {
+    with_stack(|| {
+        let mut interpreter = Interpreter::new(Vec::new());
+        let result = interpreter.run(source);
+        let mut output = String::from_utf8_lossy(interpreter.output()).into_owned();
+        match result {
+            Ok(Value::Unit) => {}
+            Ok(value) => output.push_str(&format!("{:?}\n", value)),
+            Err(error) => output.push_str(&format!("{}\n", error.render(source))),
+        }
+        output
+    })
+}
+
+pub fn main() {
+    let fib = "
+        let fib = fn(n) { if n < 2 { n } else { fib(n - 1) + fib(n - 2) } };
+        let i = 0;
+        while i < 8 { print(i, fib(i)); i = i + 1; }
+        fib(20)
+    ";
+    let expected = "0 0\n1 1\n2 1\n3 2\n4 3\n5 5\n6 8\n7 13\n6765\n";
+    assert_eq!(run_program(fib), expected);
+
+    //closures: each call to make_counter gets its own n
+    let counters = "
+        let make_counter = fn(step) { let n = 0; fn() { n = n + step; n } };
+        let ones = make_counter(1);
+        let tens = make_counter(10);
+        ones(); ones(); tens();
+        str(ones()) + \"/\" + str(tens())
+    ";
+    assert_eq!(run_program(counters), "\"3/20\"\n");
+
+    //higher-order functions
+    let compose = "
+        let compose = fn(f, g) { fn(x) { f(g(x)) } };
+        let inc = fn(x) { x + 1 };
+        let square = fn(x) { x * x };
+        compose(square, inc)(4) == 25 && compose(inc, square)(4) == 17
+    ";
+    assert_eq!(run_program(compose), "true\n");
+
+    //errors carry a span into the source
+    let source = "let total = 10;\nlet count = 0;\ntotal / count";
+    let error = Interpreter::new(Vec::new()).run(source).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::DivisionByZero);
+    assert_eq!(
+        error.render(source),
+        "error at 3:9: division by zero\n  total / count\n          ^^^^^"
+    );
+    let error = parse("let x = (1 + 2;").unwrap_err();
+    assert_eq!(error.to_string(), "expected `)`, found `;` at 14..15");
+}
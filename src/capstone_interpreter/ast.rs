@@ -0,0 +1,124 @@
+/*
+ * Syntax tree: recursive enums need indirection, Box gives the node a known size
+ * (see custom_types::enums for the same idea in a linked list)
+ * Functions are Rc: every closure created from one `fn` expression shares its body
+ */
+
+use super::error::Span;
+use std::rc::Rc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOp {
+    Negate,
+    Not,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    //short-circuiting: the right side is evaluated only if needed
+    And,
+    Or,
+}
+
+impl UnaryOp {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            UnaryOp::Negate => "-",
+            UnaryOp::Not => "!",
+        }
+    }
+}
+
+impl BinaryOp {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Subtract => "-",
+            BinaryOp::Multiply => "*",
+            BinaryOp::Divide => "/",
+            BinaryOp::Remainder => "%",
+            BinaryOp::Equal => "==",
+            BinaryOp::NotEqual => "!=",
+            BinaryOp::Less => "<",
+            BinaryOp::LessEqual => "<=",
+            BinaryOp::Greater => ">",
+            BinaryOp::GreaterEqual => ">=",
+            BinaryOp::And => "&&",
+            BinaryOp::Or => "||",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Function {
+    pub params: Vec<String>,
+    pub body: Expr,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ExprKind {
+    Int(i64),
+    Str(Rc<str>),
+    Bool(bool),
+    Var(String),
+    Unary {
+        op: UnaryOp,
+        operand: Box<Expr>,
+    },
+    Binary {
+        op: BinaryOp,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+    Assign {
+        name: String,
+        value: Box<Expr>,
+    },
+    //a new scope: { statements; result }
+    Block(Block),
+    If {
+        condition: Box<Expr>,
+        then: Box<Expr>,
+        otherwise: Option<Box<Expr>>,
+    },
+    While {
+        condition: Box<Expr>,
+        body: Box<Expr>,
+    },
+    Function(Rc<Function>),
+    Call {
+        callee: Box<Expr>,
+        args: Vec<Expr>,
+    },
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Stmt {
+    Let { name: String, value: Expr },
+    Expr(Expr),
+}
+
+//also the whole program: a block without braces
+#[derive(Debug, PartialEq)]
+pub struct Block {
+    pub statements: Vec<Stmt>,
+    //last expression without a semicolon: the value of the block
+    pub result: Option<Box<Expr>>,
+}
@@ -0,0 +1,139 @@
+/*
+ * One error type for every phase: lexing, parsing, evaluation
+ * Every error carries the Span of the source text it's about,
+ * render() turns it into a message with the line and a ^^^ marker
+ */
+
+use std::error;
+use std::fmt;
+
+/// Byte range `start..end` in the source text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Span {
+        Span { start, end }
+    }
+
+    //smallest span covering both
+    pub fn to(self, other: Span) -> Span {
+        Span::new(self.start.min(other.start), self.end.max(other.end))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorKind {
+    //lexer
+    UnexpectedChar(char),
+    UnterminatedString,
+    IntegerTooLarge,
+    //parser
+    Expected { expected: String, found: String },
+    InvalidAssignment,
+    TooDeep,
+    //evaluation
+    Undefined(String),
+    TypeMismatch { operation: String, found: String },
+    DivisionByZero,
+    Overflow,
+    NotCallable(String),
+    Arity { expected: usize, found: usize },
+    StackOverflow,
+    //writing print() output failed
+    Io(String),
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ErrorKind::UnexpectedChar(c) => write!(f, "unexpected character {:?}", c),
+            ErrorKind::UnterminatedString => write!(f, "unterminated string"),
+            ErrorKind::IntegerTooLarge => write!(f, "integer literal is too large"),
+            ErrorKind::Expected { expected, found } => {
+                write!(f, "expected {}, found {}", expected, found)
+            }
+            ErrorKind::InvalidAssignment => write!(f, "only variables can be assigned to"),
+            ErrorKind::TooDeep => write!(f, "expression nested too deeply"),
+            ErrorKind::Undefined(name) => write!(f, "undefined variable `{}`", name),
+            ErrorKind::TypeMismatch { operation, found } => {
+                write!(f, "cannot apply {} to {}", operation, found)
+            }
+            ErrorKind::DivisionByZero => write!(f, "division by zero"),
+            ErrorKind::Overflow => write!(f, "integer overflow"),
+            ErrorKind::NotCallable(found) => write!(f, "{} is not callable", found),
+            ErrorKind::Arity { expected, found } => write!(
+                f,
+                "expected {} argument{}, found {}",
+                expected,
+                if *expected == 1 { "" } else { "s" },
+                found
+            ),
+            ErrorKind::StackOverflow => write!(f, "too many nested calls"),
+            ErrorKind::Io(message) => write!(f, "output error: {}", message),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+    pub kind: ErrorKind,
+    pub span: Span,
+}
+
+impl Error {
+    pub fn new(kind: ErrorKind, span: Span) -> Error {
+        Error { kind, span }
+    }
+
+    /// Message with position and the offending source line.
+    ///
+    /// ```
+    /// use rust_by_example::capstone_interpreter::{Error, ErrorKind, Span};
+    ///
+    /// let source = "let x = 1;\nx + y";
+    /// let error = Error::new(ErrorKind::Undefined("y".into()), Span::new(15, 16));
+    /// assert_eq!(
+    ///     error.render(source),
+    ///     "error at 2:5: undefined variable `y`\n  x + y\n      ^"
+    /// );
+    /// ```
+    pub fn render(&self, source: &str) -> String {
+        //the line containing the start of the span
+        let start = self.span.start.min(source.len());
+        let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[start..]
+            .find('\n')
+            .map_or(source.len(), |i| start + i);
+        let line = &source[line_start..line_end];
+        let line_number = source[..line_start].matches('\n').count() + 1;
+        let column = source[line_start..start].chars().count() + 1;
+        //at least one marker, even for an empty span (end of input)
+        let width = source[start..self.span.end.clamp(start, line_end)]
+            .chars()
+            .count()
+            .max(1);
+        format!(
+            "error at {}:{}: {}\n  {}\n  {}{}",
+            line_number,
+            column,
+            self.kind,
+            line,
+            " ".repeat(column - 1),
+            "^".repeat(width)
+        )
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at {}..{}", self.kind, self.span.start, self.span.end)
+    }
+}
+
+impl error::Error for Error {}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,629 @@
+/*
+ * Tree-walking evaluator
+ * Environments form a chain: block -> enclosing block -> ... -> globals
+ * - Rc: a closure keeps the environment it was created in alive after the block ends
+ * - RefCell: that shared environment is still mutable (assignment, let in the REPL)
+ * Closures stored in the environment they capture form an Rc cycle: never freed,
+ * acceptable for an interpreter session (a real one would use a GC or arena)
+ */
+
+use super::ast::{BinaryOp, Block, Expr, ExprKind, Function, Stmt, UnaryOp};
+use super::error::{Error, ErrorKind, Result, Span};
+use super::parser::parse;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Write;
+use std::panic;
+use std::rc::Rc;
+use std::thread;
+
+//calls in progress at once: recursion in the interpreted program is recursion in eval
+const MAX_CALLS: usize = 5_000;
+//levels of nested evaluation, calls included: what actually uses up the Rust stack.
+//A level takes about 5 KiB in a debug build, STACK_SIZE leaves room for 8 KiB
+const MAX_DEPTH: usize = 32 * 1024;
+
+/// Stack for a thread that runs the interpreter, see `with_stack`: enough for MAX_DEPTH levels.
+pub const STACK_SIZE: usize = MAX_DEPTH * 8 * 1024;
+
+/// Runs `f` on a thread with `STACK_SIZE` of stack and waits for it.
+///
+/// `Interpreter::run` on a thread with the default stack (2 MiB for spawned threads, tests
+/// included) overflows it after a few hundred nested calls, long before `MAX_CALLS`.
+pub fn with_stack<T: Send>(f: impl FnOnce() -> T + Send) -> T {
+    thread::scope(|scope| {
+        let thread = thread::Builder::new()
+            .name("interpreter".to_string())
+            .stack_size(STACK_SIZE)
+            .spawn_scoped(scope, f)
+            .expect("failed to spawn the interpreter thread");
+        //a panic in `f` is passed on as it is
+        thread
+            .join()
+            .unwrap_or_else(|panic| panic::resume_unwind(panic))
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Builtin {
+    //print(a, b, ...): writes the values separated by spaces, then a newline
+    Print,
+    //len(string): length in characters
+    Len,
+    //str(value): the value as printed
+    Str,
+}
+
+impl Builtin {
+    const ALL: [Builtin; 3] = [Builtin::Print, Builtin::Len, Builtin::Str];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Builtin::Print => "print",
+            Builtin::Len => "len",
+            Builtin::Str => "str",
+        }
+    }
+}
+
+pub struct Closure {
+    pub function: Rc<Function>,
+    pub env: Rc<RefCell<Env>>,
+}
+
+#[derive(Clone)]
+pub enum Value {
+    Unit,
+    Int(i64),
+    Bool(bool),
+    Str(Rc<str>),
+    Closure(Rc<Closure>),
+    Builtin(Builtin),
+}
+
+impl Value {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Unit => "unit",
+            Value::Int(_) => "int",
+            Value::Bool(_) => "bool",
+            Value::Str(_) => "string",
+            Value::Closure(_) | Value::Builtin(_) => "function",
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Unit => write!(f, "()"),
+            Value::Int(value) => write!(f, "{}", value),
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::Str(value) => write!(f, "{}", value),
+            Value::Closure(closure) => write!(f, "<fn({})>", closure.function.params.join(", ")),
+            Value::Builtin(builtin) => write!(f, "<builtin {}>", builtin.name()),
+        }
+    }
+}
+
+//not derived: a closure's environment may contain the closure itself
+impl fmt::Debug for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Str(value) => write!(f, "{:?}", value),
+            other => write!(f, "{}", other),
+        }
+    }
+}
+
+//functions are equal only to themselves
+impl PartialEq for Value {
+    fn eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Unit, Value::Unit) => true,
+            (Value::Int(a), Value::Int(b)) => a == b,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Str(a), Value::Str(b)) => a == b,
+            (Value::Closure(a), Value::Closure(b)) => Rc::ptr_eq(a, b),
+            (Value::Builtin(a), Value::Builtin(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Env {
+    values: HashMap<String, Value>,
+    parent: Option<Rc<RefCell<Env>>>,
+}
+
+impl Env {
+    pub fn child(parent: &Rc<RefCell<Env>>) -> Rc<RefCell<Env>> {
+        Rc::new(RefCell::new(Env {
+            values: HashMap::new(),
+            parent: Some(Rc::clone(parent)),
+        }))
+    }
+
+    //let: a new variable in this scope, shadowing outer ones
+    pub fn define(&mut self, name: &str, value: Value) {
+        self.values.insert(name.to_string(), value);
+    }
+
+    pub fn get(&self, name: &str) -> Option<Value> {
+        match self.values.get(name) {
+            Some(value) => Some(value.clone()),
+            None => self.parent.as_ref()?.borrow().get(name),
+        }
+    }
+
+    //assignment: changes the nearest existing variable, false if there is none
+    pub fn assign(&mut self, name: &str, value: Value) -> bool {
+        if let Some(slot) = self.values.get_mut(name) {
+            *slot = value;
+            return true;
+        }
+        match &self.parent {
+            Some(parent) => parent.borrow_mut().assign(name, value),
+            None => false,
+        }
+    }
+}
+
+/// Runs programs against one global environment; `print` writes to `W`.
+///
+/// Deep recursion needs a deep stack: run programs you don't control inside `with_stack`.
+///
+/// ```
+/// use rust_by_example::capstone_interpreter::{Interpreter, Value};
+///
+/// let mut interpreter = Interpreter::new(Vec::new());
+/// interpreter.run("let double = fn(x) { x * 2 };").unwrap();
+/// //globals survive between runs, like in a REPL
+/// assert_eq!(interpreter.run("double(21)").unwrap(), Value::Int(42));
+/// interpreter.run("print(\"hi\", double(1))").unwrap();
+/// assert_eq!(interpreter.output(), b"hi 2\n");
+/// ```
+pub struct Interpreter<W> {
+    globals: Rc<RefCell<Env>>,
+    out: W,
+    calls: usize,
+    depth: usize,
+}
+
+impl<W: Write> Interpreter<W> {
+    pub fn new(out: W) -> Interpreter<W> {
+        let mut globals = Env::default();
+        for builtin in Builtin::ALL {
+            globals.define(builtin.name(), Value::Builtin(builtin));
+        }
+        Interpreter {
+            globals: Rc::new(RefCell::new(globals)),
+            out,
+            calls: 0,
+            depth: 0,
+        }
+    }
+
+    pub fn output(&self) -> &W {
+        &self.out
+    }
+
+    pub fn into_output(self) -> W {
+        self.out
+    }
+
+    /// Parses and evaluates `source`; its `let`s go into the global scope.
+    pub fn run(&mut self, source: &str) -> Result<Value> {
+        let program = parse(source)?;
+        self.calls = 0;
+        self.depth = 0;
+        let globals = Rc::clone(&self.globals);
+        self.block(&program, &globals)
+    }
+
+    fn block(&mut self, block: &Block, env: &Rc<RefCell<Env>>) -> Result<Value> {
+        for statement in &block.statements {
+            match statement {
+                Stmt::Let { name, value } => {
+                    //evaluate first: `env.borrow_mut().define(name, self.eval(..)?)` would hold
+                    //the mutable borrow while the value is evaluated, and panic on any lookup
+                    let value = self.eval(value, env)?;
+                    env.borrow_mut().define(name, value);
+                }
+                Stmt::Expr(expr) => {
+                    self.eval(expr, env)?;
+                }
+            }
+        }
+        match &block.result {
+            Some(result) => self.eval(result, env),
+            None => Ok(Value::Unit),
+        }
+    }
+
+    //the stack grows with every nested expression, not only with calls: all of them count.
+    //Only calls can nest this deep, the parser limits nesting within the source
+    fn eval(&mut self, expr: &Expr, env: &Rc<RefCell<Env>>) -> Result<Value> {
+        if self.depth >= MAX_DEPTH {
+            return Err(Error::new(ErrorKind::StackOverflow, expr.span));
+        }
+        self.depth += 1;
+        let value = self.eval_kind(expr, env);
+        self.depth -= 1;
+        value
+    }
+
+    fn eval_kind(&mut self, expr: &Expr, env: &Rc<RefCell<Env>>) -> Result<Value> {
+        match &expr.kind {
+            ExprKind::Int(value) => Ok(Value::Int(*value)),
+            ExprKind::Str(value) => Ok(Value::Str(Rc::clone(value))),
+            ExprKind::Bool(value) => Ok(Value::Bool(*value)),
+            ExprKind::Var(name) => env
+                .borrow()
+                .get(name)
+                .ok_or_else(|| Error::new(ErrorKind::Undefined(name.clone()), expr.span)),
+            ExprKind::Unary { op, operand } => {
+                let value = self.eval(operand, env)?;
+                unary(*op, value, expr.span)
+            }
+            ExprKind::Binary { .. } => self.binary(expr, env),
+            ExprKind::Assign { name, value } => {
+                let value = self.eval(value, env)?;
+                if env.borrow_mut().assign(name, value.clone()) {
+                    Ok(value)
+                } else {
+                    Err(Error::new(ErrorKind::Undefined(name.clone()), expr.span))
+                }
+            }
+            ExprKind::Block(block) => self.block(block, &Env::child(env)),
+            ExprKind::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                if self.condition(condition, env)? {
+                    self.eval(then, env)
+                } else if let Some(otherwise) = otherwise {
+                    self.eval(otherwise, env)
+                } else {
+                    Ok(Value::Unit)
+                }
+            }
+            ExprKind::While { condition, body } => {
+                while self.condition(condition, env)? {
+                    self.eval(body, env)?;
+                }
+                Ok(Value::Unit)
+            }
+            //capturing: the closure keeps a reference to the current environment
+            ExprKind::Function(function) => Ok(Value::Closure(Rc::new(Closure {
+                function: Rc::clone(function),
+                env: Rc::clone(env),
+            }))),
+            ExprKind::Call { callee, args } => {
+                let function = self.eval(callee, env)?;
+                let args = args
+                    .iter()
+                    .map(|arg| self.eval(arg, env))
+                    .collect::<Result<Vec<Value>>>()?;
+                self.call(function, args, callee.span, expr.span)
+            }
+        }
+    }
+
+    fn condition(&mut self, condition: &Expr, env: &Rc<RefCell<Env>>) -> Result<bool> {
+        match self.eval(condition, env)? {
+            Value::Bool(value) => Ok(value),
+            other => Err(Error::new(
+                ErrorKind::TypeMismatch {
+                    operation: "a condition".to_string(),
+                    found: other.type_name().to_string(),
+                },
+                condition.span,
+            )),
+        }
+    }
+
+    //a chain like 1 + 2 + ... + n is the tree ((1 + 2) + ...) + n: its left side is walked in a
+    //loop instead of one recursion per operator, a flat chain of any length takes no stack
+    fn binary(&mut self, expr: &Expr, env: &Rc<RefCell<Env>>) -> Result<Value> {
+        let mut chain = Vec::new();
+        let mut first = expr;
+        while let ExprKind::Binary { op, left, right } = &first.kind {
+            chain.push((*op, right, first.span));
+            first = left;
+        }
+        let mut value = self.eval(first, env)?;
+        for (op, right, span) in chain.into_iter().rev() {
+            value = self.operator(op, value, right, span, env)?;
+        }
+        Ok(value)
+    }
+
+    //`left_value op right`, with right evaluated here: && and || may skip it
+    fn operator(
+        &mut self,
+        op: BinaryOp,
+        left_value: Value,
+        right: &Expr,
+        span: Span,
+        env: &Rc<RefCell<Env>>,
+    ) -> Result<Value> {
+        //&& and ||: the right side only runs when it decides the result
+        if let BinaryOp::And | BinaryOp::Or = op {
+            return match left_value {
+                Value::Bool(value) if value == (op == BinaryOp::Or) => Ok(Value::Bool(value)),
+                Value::Bool(_) => match self.eval(right, env)? {
+                    Value::Bool(value) => Ok(Value::Bool(value)),
+                    other => Err(mismatch(op.symbol(), &Value::Bool(false), &other, span)),
+                },
+                other => Err(mismatch(op.symbol(), &other, &Value::Bool(false), span)),
+            };
+        }
+        let right_value = self.eval(right, env)?;
+
+        use BinaryOp::*;
+        match (op, &left_value, &right_value) {
+            (Equal, a, b) => Ok(Value::Bool(a == b)),
+            (NotEqual, a, b) => Ok(Value::Bool(a != b)),
+            (Add, Value::Str(a), Value::Str(b)) => Ok(Value::Str(format!("{}{}", a, b).into())),
+            (Divide, Value::Int(_), Value::Int(0)) | (Remainder, Value::Int(_), Value::Int(0)) => {
+                Err(Error::new(ErrorKind::DivisionByZero, right.span))
+            }
+            (_, &Value::Int(a), &Value::Int(b)) => {
+                let result = match op {
+                    Add => a.checked_add(b),
+                    Subtract => a.checked_sub(b),
+                    Multiply => a.checked_mul(b),
+                    Divide => a.checked_div(b),
+                    Remainder => a.checked_rem(b),
+                    Less => return Ok(Value::Bool(a < b)),
+                    LessEqual => return Ok(Value::Bool(a <= b)),
+                    Greater => return Ok(Value::Bool(a > b)),
+                    GreaterEqual => return Ok(Value::Bool(a >= b)),
+                    Equal | NotEqual | And | Or => unreachable!("handled above"),
+                };
+                result
+                    .map(Value::Int)
+                    .ok_or_else(|| Error::new(ErrorKind::Overflow, span))
+            }
+            (Less | LessEqual | Greater | GreaterEqual, Value::Str(a), Value::Str(b)) => {
+                let ordering = a.cmp(b);
+                Ok(Value::Bool(match op {
+                    Less => ordering.is_lt(),
+                    LessEqual => ordering.is_le(),
+                    Greater => ordering.is_gt(),
+                    _ => ordering.is_ge(),
+                }))
+            }
+            _ => Err(mismatch(op.symbol(), &left_value, &right_value, span)),
+        }
+    }
+
+    fn call(
+        &mut self,
+        function: Value,
+        args: Vec<Value>,
+        callee: Span,
+        span: Span,
+    ) -> Result<Value> {
+        let closure = match function {
+            Value::Closure(closure) => closure,
+            Value::Builtin(builtin) => return self.builtin(builtin, args, span),
+            other => {
+                let found = other.type_name().to_string();
+                return Err(Error::new(ErrorKind::NotCallable(found), callee));
+            }
+        };
+        let params = &closure.function.params;
+        if params.len() != args.len() {
+            let kind = ErrorKind::Arity {
+                expected: params.len(),
+                found: args.len(),
+            };
+            return Err(Error::new(kind, span));
+        }
+        if self.calls >= MAX_CALLS {
+            return Err(Error::new(ErrorKind::StackOverflow, span));
+        }
+
+        //parameters live in a new scope inside the captured environment, not the caller's
+        let scope = Env::child(&closure.env);
+        for (param, arg) in params.iter().zip(args) {
+            scope.borrow_mut().define(param, arg);
+        }
+        self.calls += 1;
+        let value = self.eval(&closure.function.body, &scope);
+        self.calls -= 1;
+        value
+    }
+
+    fn builtin(&mut self, builtin: Builtin, args: Vec<Value>, span: Span) -> Result<Value> {
+        let arity = |expected: usize| {
+            if args.len() == expected {
+                Ok(())
+            } else {
+                let kind = ErrorKind::Arity {
+                    expected,
+                    found: args.len(),
+                };
+                Err(Error::new(kind, span))
+            }
+        };
+        match builtin {
+            Builtin::Print => {
+                let line: Vec<String> = args.iter().map(Value::to_string).collect();
+                writeln!(self.out, "{}", line.join(" "))
+                    .map_err(|err| Error::new(ErrorKind::Io(err.to_string()), span))?;
+                Ok(Value::Unit)
+            }
+            Builtin::Len => {
+                arity(1)?;
+                match &args[0] {
+                    Value::Str(value) => Ok(Value::Int(value.chars().count() as i64)),
+                    other => Err(Error::new(
+                        ErrorKind::TypeMismatch {
+                            operation: "len".to_string(),
+                            found: other.type_name().to_string(),
+                        },
+                        span,
+                    )),
+                }
+            }
+            Builtin::Str => {
+                arity(1)?;
+                Ok(Value::Str(args[0].to_string().into()))
+            }
+        }
+    }
+}
+
+fn unary(op: UnaryOp, value: Value, span: Span) -> Result<Value> {
+    match (op, value) {
+        (UnaryOp::Negate, Value::Int(value)) => value
+            .checked_neg()
+            .map(Value::Int)
+            .ok_or_else(|| Error::new(ErrorKind::Overflow, span)),
+        (UnaryOp::Not, Value::Bool(value)) => Ok(Value::Bool(!value)),
+        (op, other) => Err(Error::new(
+            ErrorKind::TypeMismatch {
+                operation: format!("`{}`", op.symbol()),
+                found: other.type_name().to_string(),
+            },
+            span,
+        )),
+    }
+}
+
+fn mismatch(symbol: &str, left: &Value, right: &Value, span: Span) -> Error {
+    let kind = ErrorKind::TypeMismatch {
+        operation: format!("`{}`", symbol),
+        found: format!("{} and {}", left.type_name(), right.type_name()),
+    };
+    Error::new(kind, span)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(source: &str) -> Result<Value> {
+        Interpreter::new(Vec::new()).run(source)
+    }
+
+    #[test]
+    fn arithmetic_and_comparison() {
+        assert_eq!(run("1 + 2 * 3 - 4 / 2").unwrap(), Value::Int(5));
+        assert_eq!(run("-7 % 3").unwrap(), Value::Int(-1));
+        assert_eq!(run("1 < 2 && \"a\" < \"b\"").unwrap(), Value::Bool(true));
+        assert_eq!(
+            run("\"ab\" + \"cd\" == \"abcd\"").unwrap(),
+            Value::Bool(true)
+        );
+        //different types are never equal, but comparing them is allowed
+        assert_eq!(run("1 == true").unwrap(), Value::Bool(false));
+    }
+
+    #[test]
+    fn long_flat_chains() {
+        let sum = vec!["1"; 500].join(" + ");
+        assert_eq!(run(&sum).unwrap(), Value::Int(500));
+        //left to right, one operator at a time: 100 - 1 - 1 ... = 0
+        let difference = format!("100{}", " - 1".repeat(100));
+        assert_eq!(run(&difference).unwrap(), Value::Int(0));
+        let mixed = vec!["2 * 3"; 300].join(" + ");
+        assert_eq!(run(&mixed).unwrap(), Value::Int(1800));
+        //short-circuit anywhere in the chain
+        let chain = format!("{} && false && undefined", vec!["true"; 300].join(" && "));
+        assert_eq!(run(&chain).unwrap(), Value::Bool(false));
+        //errors still point at the operator that failed
+        let source = format!("{} + true + 1", vec!["1"; 300].join(" + "));
+        let error = run(&source).unwrap_err();
+        assert_eq!(error.span, Span::new(0, source.len() - 4));
+    }
+
+    #[test]
+    fn short_circuit_skips_the_right_side() {
+        assert_eq!(run("false && undefined").unwrap(), Value::Bool(false));
+        assert_eq!(run("true || 1 / 0").unwrap(), Value::Bool(true));
+        let error = run("true && 1").unwrap_err();
+        assert_eq!(error.kind.to_string(), "cannot apply `&&` to bool and int");
+    }
+
+    #[test]
+    fn blocks_scope_and_shadow() {
+        let source = "let x = 1; let y = { let x = 10; x + 1 }; x = x + y; x";
+        assert_eq!(run(source).unwrap(), Value::Int(12));
+        let error = run("{ let inner = 1; } inner").unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Undefined("inner".into()));
+    }
+
+    #[test]
+    fn closures_share_captured_state() {
+        let source = "
+            let counter = fn() { let n = 0; fn() { n = n + 1; n } };
+            let a = counter();
+            let b = counter();
+            a(); a();
+            a() * 10 + b()
+        ";
+        assert_eq!(run(source).unwrap(), Value::Int(31));
+    }
+
+    #[test]
+    fn runtime_errors_point_at_the_culprit() {
+        let source = "let f = fn(a) { a };\nf(1, 2)";
+        let error = run(source).unwrap_err();
+        assert_eq!(
+            error.kind,
+            ErrorKind::Arity {
+                expected: 1,
+                found: 2
+            }
+        );
+        assert_eq!(&source[error.span.start..error.span.end], "f(1, 2)");
+
+        let error = run("10 / (5 - 5)").unwrap_err();
+        assert_eq!(
+            (error.kind, error.span),
+            (ErrorKind::DivisionByZero, Span::new(5, 12))
+        );
+
+        let error = run("9223372036854775807 + 1").unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Overflow);
+
+        let error = run("let x = 1; x()").unwrap_err();
+        assert_eq!(error.kind, ErrorKind::NotCallable("int".into()));
+    }
+
+    #[test]
+    fn recursion_is_bounded() {
+        //Value holds Rcs and can't leave the thread: compared in there
+        with_stack(|| {
+            let error = run("let f = fn(n) { f(n + 1) }; f(0)").unwrap_err();
+            assert_eq!(error.kind, ErrorKind::StackOverflow);
+            let count = "let f = fn(n) { if n == 0 { 0 } else { 1 + f(n - 1) } };";
+            //f(n) is n + 1 calls deep
+            assert_eq!(
+                run(&format!("{} f({})", count, MAX_CALLS - 1)),
+                Ok(Value::Int(MAX_CALLS as i64 - 1))
+            );
+            let error = run(&format!("{} f({})", count, MAX_CALLS)).unwrap_err();
+            assert_eq!(error.kind, ErrorKind::StackOverflow);
+
+            //deeply nested bodies take more stack per call: the level limit comes first
+            let body = format!("{}f(n + 1){}", "{ -".repeat(28), "}".repeat(28));
+            let source = format!("let f = fn(n) {{ {} }}; f(0)", body);
+            let mut interpreter = Interpreter::new(Vec::new());
+            assert_eq!(
+                interpreter.run(&source).unwrap_err().kind,
+                ErrorKind::StackOverflow
+            );
+            assert!(interpreter.calls == 0 && interpreter.depth == 0);
+            //the limits are per run: the interpreter is still usable afterwards
+            assert_eq!(interpreter.run("1").unwrap(), Value::Int(1));
+        });
+    }
+}
@@ -0,0 +1,315 @@
+/*
+ * Lexer: source text -> tokens, each with its Span
+ * An Iterator over Result<Token>: the parser collects it, stopping at the first error
+ * Whitespace and comments (# to end of line) are skipped
+ */
+
+use super::error::{Error, ErrorKind, Result, Span};
+use std::fmt;
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKind {
+    Int(i64),
+    Str(String),
+    Ident(String),
+    //keywords
+    Let,
+    Fn,
+    If,
+    Else,
+    While,
+    True,
+    False,
+    //punctuation
+    LeftParen,
+    RightParen,
+    LeftBrace,
+    RightBrace,
+    Comma,
+    Semicolon,
+    //operators
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Percent,
+    Bang,
+    Assign,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    And,
+    Or,
+    Eof,
+}
+
+//how tokens appear in "expected .., found .." messages
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
+            TokenKind::Int(value) => return write!(f, "number {}", value),
+            TokenKind::Str(value) => return write!(f, "string {:?}", value),
+            TokenKind::Ident(name) => return write!(f, "`{}`", name),
+            TokenKind::Eof => return write!(f, "end of input"),
+            TokenKind::Let => "let",
+            TokenKind::Fn => "fn",
+            TokenKind::If => "if",
+            TokenKind::Else => "else",
+            TokenKind::While => "while",
+            TokenKind::True => "true",
+            TokenKind::False => "false",
+            TokenKind::LeftParen => "(",
+            TokenKind::RightParen => ")",
+            TokenKind::LeftBrace => "{",
+            TokenKind::RightBrace => "}",
+            TokenKind::Comma => ",",
+            TokenKind::Semicolon => ";",
+            TokenKind::Plus => "+",
+            TokenKind::Minus => "-",
+            TokenKind::Star => "*",
+            TokenKind::Slash => "/",
+            TokenKind::Percent => "%",
+            TokenKind::Bang => "!",
+            TokenKind::Assign => "=",
+            TokenKind::Equal => "==",
+            TokenKind::NotEqual => "!=",
+            TokenKind::Less => "<",
+            TokenKind::LessEqual => "<=",
+            TokenKind::Greater => ">",
+            TokenKind::GreaterEqual => ">=",
+            TokenKind::And => "&&",
+            TokenKind::Or => "||",
+        };
+        write!(f, "`{}`", text)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+pub struct Lexer<'a> {
+    source: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    //Eof is produced once, then the iterator ends
+    done: bool,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(source: &'a str) -> Lexer<'a> {
+        Lexer {
+            source,
+            chars: source.char_indices().peekable(),
+            done: false,
+        }
+    }
+
+    //byte offset of the next character
+    fn offset(&mut self) -> usize {
+        self.chars.peek().map_or(self.source.len(), |&(i, _)| i)
+    }
+
+    //consumes the next character if it's `expected`
+    fn eat(&mut self, expected: char) -> bool {
+        self.chars.next_if(|&(_, c)| c == expected).is_some()
+    }
+
+    fn skip_whitespace_and_comments(&mut self) {
+        while let Some(&(_, c)) = self.chars.peek() {
+            if c == '#' {
+                while self.chars.next_if(|&(_, c)| c != '\n').is_some() {}
+            } else if c.is_whitespace() {
+                self.chars.next();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn number(&mut self, start: usize) -> Result<TokenKind> {
+        while self.chars.next_if(|(_, c)| c.is_ascii_digit()).is_some() {}
+        let end = self.offset();
+        self.source[start..end]
+            .parse()
+            .map(TokenKind::Int)
+            .map_err(|_| Error::new(ErrorKind::IntegerTooLarge, Span::new(start, end)))
+    }
+
+    fn string(&mut self, start: usize) -> Result<TokenKind> {
+        let mut value = String::new();
+        while let Some((_, c)) = self.chars.next() {
+            match c {
+                '"' => return Ok(TokenKind::Str(value)),
+                '\\' => match self.chars.next() {
+                    Some((_, 'n')) => value.push('\n'),
+                    Some((_, 't')) => value.push('\t'),
+                    Some((_, other)) => value.push(other),
+                    None => break,
+                },
+                _ => value.push(c),
+            }
+        }
+        let end = self.offset();
+        Err(Error::new(
+            ErrorKind::UnterminatedString,
+            Span::new(start, end),
+        ))
+    }
+
+    fn identifier(&mut self, start: usize) -> TokenKind {
+        while self
+            .chars
+            .next_if(|(_, c)| c.is_alphanumeric() || *c == '_')
+            .is_some()
+        {}
+        match &self.source[start..self.offset()] {
+            "let" => TokenKind::Let,
+            "fn" => TokenKind::Fn,
+            "if" => TokenKind::If,
+            "else" => TokenKind::Else,
+            "while" => TokenKind::While,
+            "true" => TokenKind::True,
+            "false" => TokenKind::False,
+            name => TokenKind::Ident(name.to_string()),
+        }
+    }
+
+    fn token(&mut self) -> Result<Token> {
+        self.skip_whitespace_and_comments();
+        let (start, c) = match self.chars.next() {
+            Some(next) => next,
+            None => {
+                self.done = true;
+                let end = self.source.len();
+                return Ok(Token {
+                    kind: TokenKind::Eof,
+                    span: Span::new(end, end),
+                });
+            }
+        };
+        let kind = match c {
+            '0'..='9' => self.number(start)?,
+            '"' => self.string(start)?,
+            c if c.is_alphabetic() || c == '_' => self.identifier(start),
+            '(' => TokenKind::LeftParen,
+            ')' => TokenKind::RightParen,
+            '{' => TokenKind::LeftBrace,
+            '}' => TokenKind::RightBrace,
+            ',' => TokenKind::Comma,
+            ';' => TokenKind::Semicolon,
+            '+' => TokenKind::Plus,
+            '-' => TokenKind::Minus,
+            '*' => TokenKind::Star,
+            '/' => TokenKind::Slash,
+            '%' => TokenKind::Percent,
+            //one or two characters: look one ahead
+            '!' if self.eat('=') => TokenKind::NotEqual,
+            '!' => TokenKind::Bang,
+            '=' if self.eat('=') => TokenKind::Equal,
+            '=' => TokenKind::Assign,
+            '<' if self.eat('=') => TokenKind::LessEqual,
+            '<' => TokenKind::Less,
+            '>' if self.eat('=') => TokenKind::GreaterEqual,
+            '>' => TokenKind::Greater,
+            '&' if self.eat('&') => TokenKind::And,
+            '|' if self.eat('|') => TokenKind::Or,
+            other => {
+                let span = Span::new(start, start + other.len_utf8());
+                return Err(Error::new(ErrorKind::UnexpectedChar(other), span));
+            }
+        };
+        Ok(Token {
+            kind,
+            span: Span::new(start, self.offset()),
+        })
+    }
+}
+
+impl Iterator for Lexer<'_> {
+    type Item = Result<Token>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let token = self.token();
+        //no tokens after an error: the rest would be noise
+        if token.is_err() {
+            self.done = true;
+        }
+        Some(token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(source: &str) -> Vec<TokenKind> {
+        Lexer::new(source)
+            .map(|token| token.unwrap().kind)
+            .collect()
+    }
+
+    #[test]
+    fn operators_and_keywords() {
+        use TokenKind::*;
+        assert_eq!(
+            kinds("let f = fn(x) { x <= 10 && !done };"),
+            [
+                Let,
+                Ident("f".into()),
+                Assign,
+                Fn,
+                LeftParen,
+                Ident("x".into()),
+                RightParen,
+                LeftBrace,
+                Ident("x".into()),
+                LessEqual,
+                Int(10),
+                And,
+                Bang,
+                Ident("done".into()),
+                RightBrace,
+                Semicolon,
+                Eof
+            ]
+        );
+    }
+
+    #[test]
+    fn spans_are_byte_ranges() {
+        let tokens: Vec<Token> = Lexer::new("  42 # comment\n\"hi\\n\"")
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(tokens[0].span, Span::new(2, 4));
+        assert_eq!(tokens[1].kind, TokenKind::Str("hi\n".into()));
+        assert_eq!(tokens[1].span, Span::new(15, 21));
+        assert_eq!(tokens[2].span, Span::new(21, 21));
+    }
+
+    #[test]
+    fn lexical_errors() {
+        let error = Lexer::new("1 + $").nth(2).unwrap().unwrap_err();
+        assert_eq!(error.kind, ErrorKind::UnexpectedChar('$'));
+        assert_eq!(error.span, Span::new(4, 5));
+
+        let mut lexer = Lexer::new("\"open");
+        assert_eq!(
+            lexer.next().unwrap().unwrap_err().kind,
+            ErrorKind::UnterminatedString
+        );
+        assert!(lexer.next().is_none());
+
+        let error = Lexer::new("99999999999999999999").next().unwrap();
+        assert_eq!(error.unwrap_err().kind, ErrorKind::IntegerTooLarge);
+    }
+}
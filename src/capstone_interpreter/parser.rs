@@ -0,0 +1,538 @@
+/*
+ * Recursive descent: one method per precedence level, lowest first
+ *
+ * program    := statement* expression?
+ * statement  := "let" IDENT "=" expression ";" | expression ";" | block-like expression
+ * expression := IDENT "=" expression | or
+ * or         := and ("||" and)*
+ * and        := equality ("&&" equality)*
+ * equality   := comparison (("==" | "!=") comparison)*
+ * comparison := term (("<" | "<=" | ">" | ">=") term)*
+ * term       := factor (("+" | "-") factor)*
+ * factor     := unary (("*" | "/" | "%") unary)*
+ * unary      := ("-" | "!") unary | call
+ * call       := primary ("(" (expression ("," expression)*)? ")")*
+ * primary    := INT | STRING | "true" | "false" | IDENT | "(" expression ")"
+ *             | block | if | while | "fn" "(" params ")" block
+ *
+ * Binary levels loop instead of recursing on the right: operators are left-associative
+ *
+ * Nesting is limited to MAX_DEPTH levels, deeper input is an error instead of a stack overflow
+ * in the parser or later in the evaluator. A level is a pair of parentheses or braces, a unary
+ * operator or an assignment. A flat chain like 1 + 2 + 3 is not nesting, even though its tree is
+ * ((1 + 2) + 3): the evaluator walks down the left side in a loop
+ */
+
+use super::ast::{BinaryOp, Block, Expr, ExprKind, Function, Stmt, UnaryOp};
+use super::error::{Error, ErrorKind, Result};
+use super::lexer::{Lexer, Token, TokenKind};
+use std::rc::Rc;
+
+//nesting beyond this is refused instead of overflowing the stack on ((((((...
+const MAX_DEPTH: usize = 64;
+
+/// Parses a whole program.
+///
+/// ```
+/// use rust_by_example::capstone_interpreter::parse;
+///
+/// let program = parse("let x = 1; x + 2").unwrap();
+/// assert_eq!(program.statements.len(), 1);
+/// assert!(program.result.is_some());
+///
+/// let error = parse("let = 1").unwrap_err();
+/// assert_eq!(error.to_string(), "expected variable name, found `=` at 4..5");
+/// ```
+pub fn parse(source: &str) -> Result<Block> {
+    let tokens = Lexer::new(source).collect::<Result<Vec<Token>>>()?;
+    let mut parser = Parser {
+        tokens,
+        position: 0,
+        depth: 0,
+    };
+    let program = parser.block_body(&TokenKind::Eof)?;
+    parser.expect(TokenKind::Eof, "end of input")?;
+    Ok(program)
+}
+
+struct Parser {
+    //always ends with Eof
+    tokens: Vec<Token>,
+    position: usize,
+    depth: usize,
+}
+
+//(token, operator) pairs of one precedence level
+type Operators = [(TokenKind, BinaryOp)];
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.position]
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.position].clone();
+        //stay on Eof
+        if token.kind != TokenKind::Eof {
+            self.position += 1;
+        }
+        token
+    }
+
+    fn check(&self, kind: &TokenKind) -> bool {
+        &self.peek().kind == kind
+    }
+
+    fn error_here(&self, expected: &str) -> Error {
+        let found = self.peek();
+        let kind = ErrorKind::Expected {
+            expected: expected.to_string(),
+            found: found.kind.to_string(),
+        };
+        Error::new(kind, found.span)
+    }
+
+    fn expect(&mut self, kind: TokenKind, expected: &str) -> Result<Token> {
+        if self.check(&kind) {
+            Ok(self.advance())
+        } else {
+            Err(self.error_here(expected))
+        }
+    }
+
+    fn identifier(&mut self, expected: &str) -> Result<String> {
+        match &self.peek().kind {
+            TokenKind::Ident(name) => {
+                let name = name.clone();
+                self.advance();
+                Ok(name)
+            }
+            _ => Err(self.error_here(expected)),
+        }
+    }
+
+    //statements up to (not including) `end`
+    fn block_body(&mut self, end: &TokenKind) -> Result<Block> {
+        let mut statements = Vec::new();
+        while !self.check(end) {
+            if self.check(&TokenKind::Let) {
+                self.advance();
+                let name = self.identifier("variable name")?;
+                self.expect(TokenKind::Assign, "`=`")?;
+                let value = self.expression()?;
+                self.expect(TokenKind::Semicolon, "`;`")?;
+                statements.push(Stmt::Let { name, value });
+                continue;
+            }
+            let expr = self.expression()?;
+            if self.check(&TokenKind::Semicolon) {
+                self.advance();
+            } else if self.check(end) {
+                return Ok(Block {
+                    statements,
+                    result: Some(Box::new(expr)),
+                });
+            } else if self.check(&TokenKind::Eof) {
+                //input ended inside a block: the missing `}` is the better hint
+                return Err(self.error_here(&end.to_string()));
+            } else if !is_block_like(&expr) {
+                //`if`, `while` and blocks end with `}`: no semicolon needed, as in Rust
+                return Err(self.error_here("`;`"));
+            }
+            statements.push(Stmt::Expr(expr));
+        }
+        Ok(Block {
+            statements,
+            result: None,
+        })
+    }
+
+    fn block(&mut self) -> Result<Expr> {
+        let open = self.expect(TokenKind::LeftBrace, "`{`")?;
+        let body = self.block_body(&TokenKind::RightBrace)?;
+        let close = self.expect(TokenKind::RightBrace, "`}`")?;
+        Ok(Expr {
+            kind: ExprKind::Block(body),
+            span: open.span.to(close.span),
+        })
+    }
+
+    //one more level of nesting around `parse`
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<Expr>) -> Result<Expr> {
+        if self.depth == MAX_DEPTH {
+            return Err(Error::new(ErrorKind::TooDeep, self.peek().span));
+        }
+        self.depth += 1;
+        let expr = parse(self);
+        self.depth -= 1;
+        expr
+    }
+
+    fn expression(&mut self) -> Result<Expr> {
+        self.nested(Self::assignment)
+    }
+
+    fn assignment(&mut self) -> Result<Expr> {
+        let target = self.or()?;
+        if !self.check(&TokenKind::Assign) {
+            return Ok(target);
+        }
+        self.advance();
+        //right-associative: a = b = 1
+        let value = self.expression()?;
+        match target.kind {
+            ExprKind::Var(name) => Ok(Expr {
+                span: target.span.to(value.span),
+                kind: ExprKind::Assign {
+                    name,
+                    value: Box::new(value),
+                },
+            }),
+            _ => Err(Error::new(ErrorKind::InvalidAssignment, target.span)),
+        }
+    }
+
+    //one left-associative level: next (op next)*
+    fn binary(
+        &mut self,
+        operators: &Operators,
+        next: fn(&mut Self) -> Result<Expr>,
+    ) -> Result<Expr> {
+        let mut left = next(self)?;
+        while let Some(&(_, op)) = operators.iter().find(|(kind, _)| self.check(kind)) {
+            self.advance();
+            let right = next(self)?;
+            left = Expr {
+                span: left.span.to(right.span),
+                kind: ExprKind::Binary {
+                    op,
+                    left: Box::new(left),
+                    right: Box::new(right),
+                },
+            };
+        }
+        Ok(left)
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        self.binary(&[(TokenKind::Or, BinaryOp::Or)], Self::and)
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        self.binary(&[(TokenKind::And, BinaryOp::And)], Self::equality)
+    }
+
+    fn equality(&mut self) -> Result<Expr> {
+        let operators = [
+            (TokenKind::Equal, BinaryOp::Equal),
+            (TokenKind::NotEqual, BinaryOp::NotEqual),
+        ];
+        self.binary(&operators, Self::comparison)
+    }
+
+    fn comparison(&mut self) -> Result<Expr> {
+        let operators = [
+            (TokenKind::Less, BinaryOp::Less),
+            (TokenKind::LessEqual, BinaryOp::LessEqual),
+            (TokenKind::Greater, BinaryOp::Greater),
+            (TokenKind::GreaterEqual, BinaryOp::GreaterEqual),
+        ];
+        self.binary(&operators, Self::term)
+    }
+
+    fn term(&mut self) -> Result<Expr> {
+        let operators = [
+            (TokenKind::Plus, BinaryOp::Add),
+            (TokenKind::Minus, BinaryOp::Subtract),
+        ];
+        self.binary(&operators, Self::factor)
+    }
+
+    fn factor(&mut self) -> Result<Expr> {
+        let operators = [
+            (TokenKind::Star, BinaryOp::Multiply),
+            (TokenKind::Slash, BinaryOp::Divide),
+            (TokenKind::Percent, BinaryOp::Remainder),
+        ];
+        self.binary(&operators, Self::unary)
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        let op = match self.peek().kind {
+            TokenKind::Minus => UnaryOp::Negate,
+            TokenKind::Bang => UnaryOp::Not,
+            _ => return self.call(),
+        };
+        let start = self.advance().span;
+        let operand = self.nested(Self::unary)?;
+        Ok(Expr {
+            span: start.to(operand.span),
+            kind: ExprKind::Unary {
+                op,
+                operand: Box::new(operand),
+            },
+        })
+    }
+
+    fn call(&mut self) -> Result<Expr> {
+        let mut callee = self.primary()?;
+        while self.check(&TokenKind::LeftParen) {
+            self.advance();
+            let args = self.comma_separated(Self::expression)?;
+            let close = self.expect(TokenKind::RightParen, "`)`")?;
+            callee = Expr {
+                span: callee.span.to(close.span),
+                kind: ExprKind::Call {
+                    callee: Box::new(callee),
+                    args,
+                },
+            };
+        }
+        Ok(callee)
+    }
+
+    //items until `)`, with optional trailing comma
+    fn comma_separated<T>(&mut self, item: fn(&mut Self) -> Result<T>) -> Result<Vec<T>> {
+        let mut items = Vec::new();
+        while !self.check(&TokenKind::RightParen) {
+            items.push(item(self)?);
+            if !self.check(&TokenKind::Comma) {
+                break;
+            }
+            self.advance();
+        }
+        Ok(items)
+    }
+
+    fn primary(&mut self) -> Result<Expr> {
+        let token = self.peek().clone();
+        let kind = match token.kind {
+            TokenKind::Int(value) => ExprKind::Int(value),
+            TokenKind::Str(value) => ExprKind::Str(value.into()),
+            TokenKind::True => ExprKind::Bool(true),
+            TokenKind::False => ExprKind::Bool(false),
+            TokenKind::Ident(name) => ExprKind::Var(name),
+            TokenKind::LeftParen => {
+                self.advance();
+                let inner = self.expression()?;
+                let close = self.expect(TokenKind::RightParen, "`)`")?;
+                //the parentheses become part of the span
+                return Ok(Expr {
+                    kind: inner.kind,
+                    span: token.span.to(close.span),
+                });
+            }
+            TokenKind::LeftBrace => return self.block(),
+            TokenKind::If => return self.if_else(),
+            TokenKind::While => {
+                self.advance();
+                let condition = self.expression()?;
+                let body = self.block()?;
+                return Ok(Expr {
+                    span: token.span.to(body.span),
+                    kind: ExprKind::While {
+                        condition: Box::new(condition),
+                        body: Box::new(body),
+                    },
+                });
+            }
+            TokenKind::Fn => {
+                self.advance();
+                self.expect(TokenKind::LeftParen, "`(`")?;
+                let params = self.comma_separated(|parser| parser.identifier("parameter name"))?;
+                self.expect(TokenKind::RightParen, "`)`")?;
+                let body = self.block()?;
+                return Ok(Expr {
+                    span: token.span.to(body.span),
+                    kind: ExprKind::Function(Rc::new(Function { params, body })),
+                });
+            }
+            _ => return Err(self.error_here("expression")),
+        };
+        self.advance();
+        Ok(Expr {
+            kind,
+            span: token.span,
+        })
+    }
+
+    //"if" was peeked, not consumed
+    fn if_else(&mut self) -> Result<Expr> {
+        let start = self.advance().span;
+        let condition = self.expression()?;
+        let then = self.block()?;
+        let otherwise = if self.check(&TokenKind::Else) {
+            self.advance();
+            //else if: the nested if is the else branch
+            Some(Box::new(if self.check(&TokenKind::If) {
+                self.if_else()?
+            } else {
+                self.block()?
+            }))
+        } else {
+            None
+        };
+        let end = otherwise.as_ref().map_or(then.span, |branch| branch.span);
+        Ok(Expr {
+            span: start.to(end),
+            kind: ExprKind::If {
+                condition: Box::new(condition),
+                then: Box::new(then),
+                otherwise,
+            },
+        })
+    }
+}
+
+fn is_block_like(expr: &Expr) -> bool {
+    matches!(
+        expr.kind,
+        ExprKind::Block(_) | ExprKind::If { .. } | ExprKind::While { .. }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capstone_interpreter::Span;
+
+    fn expression(source: &str) -> Expr {
+        *parse(source).unwrap().result.unwrap()
+    }
+
+    //fully parenthesized form: shows precedence and associativity
+    fn show(expr: &Expr) -> String {
+        match &expr.kind {
+            ExprKind::Int(value) => value.to_string(),
+            ExprKind::Var(name) => name.clone(),
+            ExprKind::Unary { op, operand } => format!("({:?} {})", op, show(operand)),
+            ExprKind::Binary { op, left, right } => {
+                format!("({} {:?} {})", show(left), op, show(right))
+            }
+            ExprKind::Assign { name, value } => format!("({} = {})", name, show(value)),
+            ExprKind::Call { callee, args } => {
+                let args: Vec<String> = args.iter().map(show).collect();
+                format!("{}({})", show(callee), args.join(", "))
+            }
+            other => format!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn precedence_and_associativity() {
+        let cases = [
+            ("1 + 2 * 3", "(1 Add (2 Multiply 3))"),
+            ("1 - 2 - 3", "((1 Subtract 2) Subtract 3)"),
+            ("-a * b", "((Negate a) Multiply b)"),
+            ("a < b == !c", "((a Less b) Equal (Not c))"),
+            ("a || b && c", "(a Or (b And c))"),
+            ("a = b = 1 + 1", "(a = (b = (1 Add 1)))"),
+            ("(1 + 2) * 3", "((1 Add 2) Multiply 3)"),
+            ("f(1, g(2))(3)", "f(1, g(2))(3)"),
+        ];
+        for (source, expected) in cases {
+            assert_eq!(show(&expression(source)), expected, "{}", source);
+        }
+    }
+
+    #[test]
+    fn spans_cover_the_expression() {
+        let source = "  foo(1, 2) + (3)";
+        let expr = expression(source);
+        assert_eq!(expr.span, Span::new(2, 17));
+        match expr.kind {
+            ExprKind::Binary { left, right, .. } => {
+                assert_eq!(&source[left.span.start..left.span.end], "foo(1, 2)");
+                assert_eq!(&source[right.span.start..right.span.end], "(3)");
+            }
+            other => panic!("not a binary expression: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn statements_and_blocks() {
+        let program = parse("let x = 1; if x { 2 } else { 3 } while false {} x").unwrap();
+        assert_eq!(program.statements.len(), 3);
+        assert!(matches!(program.statements[0], Stmt::Let { .. }));
+        assert!(matches!(program.result.unwrap().kind, ExprKind::Var(_)));
+
+        let program = parse("fn(a, b,) { a };").unwrap();
+        assert!(program.result.is_none());
+        match &program.statements[0] {
+            Stmt::Expr(Expr {
+                kind: ExprKind::Function(function),
+                ..
+            }) => assert_eq!(function.params, ["a", "b"]),
+            other => panic!("not a function: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn syntax_errors() {
+        let error = parse("1 + ").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "expected expression, found end of input at 4..4"
+        );
+        let error = parse("1 2").unwrap_err();
+        assert_eq!(error.to_string(), "expected `;`, found number 2 at 2..3");
+        let error = parse("1 = 2").unwrap_err();
+        assert_eq!(
+            (error.kind, error.span),
+            (ErrorKind::InvalidAssignment, Span::new(0, 1))
+        );
+        let error = parse("{ 1").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "expected `}`, found end of input at 3..3"
+        );
+    }
+
+    #[test]
+    fn deep_nesting() {
+        let nested = |open: &str, close: &str, depth: usize| {
+            format!("{}1{}", open.repeat(depth), close.repeat(depth))
+        };
+        //the whole program is one level, each pair of parentheses another
+        assert!(parse(&nested("(", ")", MAX_DEPTH - 1)).is_ok());
+        let error = parse(&nested("(", ")", MAX_DEPTH)).unwrap_err();
+        assert_eq!(
+            (error.kind, error.span),
+            (ErrorKind::TooDeep, Span::new(MAX_DEPTH, MAX_DEPTH + 1))
+        );
+
+        //far beyond the limit: an error, not a stack overflow
+        let shapes = [
+            ("(", ")"),
+            ("{", "}"),
+            ("-", ""),
+            ("!", ""),
+            ("a = ", ""),
+            ("1 + (", ")"),
+        ];
+        for (open, close) in shapes {
+            let error = parse(&nested(open, close, 10_000)).unwrap_err();
+            assert_eq!(error.kind, ErrorKind::TooDeep, "{}", open);
+        }
+        let error = parse(&"if true { ".repeat(10_000)).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::TooDeep);
+    }
+
+    #[test]
+    fn flat_chains_are_not_nesting() {
+        for op in ["+", "*", "==", "&&"] {
+            let chain = vec!["1"; 500].join(&format!(" {} ", op));
+            assert!(parse(&chain).is_ok(), "{}", op);
+        }
+        //still left-associative: ((1 - 2) - 3)
+        let program = parse("1 - 2 - 3").unwrap();
+        let result = program.result.unwrap();
+        match result.kind {
+            ExprKind::Binary { left, right, .. } => {
+                assert!(matches!(left.kind, ExprKind::Binary { .. }));
+                assert!(matches!(right.kind, ExprKind::Int(3)));
+            }
+            other => panic!("expected a binary expression, got {:?}", other),
+        }
+    }
+}
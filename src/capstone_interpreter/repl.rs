@@ -0,0 +1,224 @@
+/*
+ * Read-eval-print loop: `cargo run -- repl`
+ * On a terminal: raw mode line editing with termion (arrows, Home/End, history)
+ * Piped input (echo '1 + 1' | cargo run -- repl): plain lines, no prompt
+ *
+ * Raw mode is only on while a line is edited: the terminal doesn't translate
+ * "\n" into "\r\n" then, so program output must happen in normal mode
+ */
+
+use super::eval::{Interpreter, Value};
+use std::io::{self, BufRead, Write};
+use termion::event::Key;
+use termion::input::TermRead;
+use termion::raw::IntoRawMode;
+
+const PROMPT: &str = "> ";
+
+#[derive(Debug, PartialEq)]
+pub enum Action {
+    Continue,
+    Submit(String),
+    Exit,
+}
+
+/// Line being edited plus history; knows nothing about the terminal.
+#[derive(Default)]
+pub struct LineEditor {
+    line: Vec<char>,
+    cursor: usize,
+    history: Vec<String>,
+    //Some(i): browsing history entry i
+    browsing: Option<usize>,
+}
+
+impl LineEditor {
+    pub fn line(&self) -> String {
+        self.line.iter().collect()
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    fn replace(&mut self, text: &str) {
+        self.line = text.chars().collect();
+        self.cursor = self.line.len();
+    }
+
+    pub fn key(&mut self, key: Key) -> Action {
+        match key {
+            Key::Char('\n') => {
+                let line = self.line();
+                if !line.trim().is_empty() && self.history.last() != Some(&line) {
+                    self.history.push(line.clone());
+                }
+                self.replace("");
+                self.browsing = None;
+                return Action::Submit(line);
+            }
+            Key::Char(c) => {
+                self.line.insert(self.cursor, c);
+                self.cursor += 1;
+            }
+            Key::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.line.remove(self.cursor);
+            }
+            Key::Delete if self.cursor < self.line.len() => {
+                self.line.remove(self.cursor);
+            }
+            Key::Left => self.cursor = self.cursor.saturating_sub(1),
+            Key::Right => self.cursor = (self.cursor + 1).min(self.line.len()),
+            Key::Home | Key::Ctrl('a') => self.cursor = 0,
+            Key::End | Key::Ctrl('e') => self.cursor = self.line.len(),
+            Key::Ctrl('u') | Key::Ctrl('c') => self.replace(""),
+            Key::Ctrl('d') if self.line.is_empty() => return Action::Exit,
+            Key::Up if !self.history.is_empty() => {
+                let entry = self
+                    .browsing
+                    .map_or(self.history.len(), |i| i)
+                    .saturating_sub(1);
+                self.browsing = Some(entry);
+                let text = self.history[entry].clone();
+                self.replace(&text);
+            }
+            Key::Down => match self.browsing {
+                Some(entry) if entry + 1 < self.history.len() => {
+                    self.browsing = Some(entry + 1);
+                    let text = self.history[entry + 1].clone();
+                    self.replace(&text);
+                }
+                //past the newest entry: back to an empty line
+                Some(_) => {
+                    self.browsing = None;
+                    self.replace("");
+                }
+                None => {}
+            },
+            _ => {}
+        }
+        Action::Continue
+    }
+}
+
+//redraws prompt and line, then puts the cursor where it belongs
+fn render<W: Write>(out: &mut W, editor: &LineEditor) -> io::Result<()> {
+    let line = editor.line();
+    write!(out, "\r{}{}{}", termion::clear::CurrentLine, PROMPT, line)?;
+    let back = line.chars().count() - editor.cursor();
+    //cursor::Left(0) still moves one column on most terminals
+    if back > 0 {
+        write!(out, "{}", termion::cursor::Left(back as u16))?;
+    }
+    out.flush()
+}
+
+//None on Ctrl-D or end of input
+fn read_line(editor: &mut LineEditor) -> io::Result<Option<String>> {
+    let mut out = io::stdout().into_raw_mode()?;
+    render(&mut out, editor)?;
+    for key in io::stdin().keys() {
+        match editor.key(key?) {
+            Action::Continue => render(&mut out, editor)?,
+            Action::Submit(line) => {
+                write!(out, "\r\n")?;
+                return Ok(Some(line));
+            }
+            Action::Exit => break,
+        }
+    }
+    write!(out, "\r\n")?;
+    Ok(None)
+}
+
+//result or error for one line of input, None for nothing to show
+fn evaluate<W: Write>(interpreter: &mut Interpreter<W>, line: &str) -> Option<String> {
+    match interpreter.run(line) {
+        Ok(Value::Unit) => None,
+        Ok(value) => Some(format!("{:?}", value)),
+        Err(error) => Some(error.render(line)),
+    }
+}
+
+//the whole session on one thread: globals are Rc and can't move between threads
+pub fn run() -> io::Result<()> {
+    super::with_stack(session)
+}
+
+fn session() -> io::Result<()> {
+    let mut interpreter = Interpreter::new(io::stdout());
+    let stdin = io::stdin();
+
+    if !termion::is_tty(&stdin) {
+        for line in stdin.lock().lines() {
+            if let Some(result) = evaluate(&mut interpreter, &line?) {
+                println!("{}", result);
+            }
+        }
+        return Ok(());
+    }
+
+    println!("expression language, Ctrl-D to exit");
+    let mut editor = LineEditor::default();
+    while let Some(line) = read_line(&mut editor)? {
+        if let Some(result) = evaluate(&mut interpreter, &line) {
+            println!("{}", result);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn type_text(editor: &mut LineEditor, text: &str) {
+        for c in text.chars() {
+            assert_eq!(editor.key(Key::Char(c)), Action::Continue);
+        }
+    }
+
+    #[test]
+    fn editing_in_the_middle() {
+        let mut editor = LineEditor::default();
+        type_text(&mut editor, "1 + 3");
+        editor.key(Key::Left);
+        editor.key(Key::Backspace);
+        editor.key(Key::Backspace);
+        type_text(&mut editor, "* ");
+        assert_eq!((editor.line(), editor.cursor()), ("1 * 3".to_string(), 4));
+        editor.key(Key::Home);
+        editor.key(Key::Delete);
+        type_text(&mut editor, "2");
+        assert_eq!(editor.key(Key::Char('\n')), Action::Submit("2 * 3".into()));
+        assert_eq!(editor.line(), "");
+    }
+
+    #[test]
+    fn history_navigation() {
+        let mut editor = LineEditor::default();
+        for line in ["let a = 1;", "a + 1"] {
+            type_text(&mut editor, line);
+            editor.key(Key::Char('\n'));
+        }
+        editor.key(Key::Up);
+        assert_eq!(editor.line(), "a + 1");
+        editor.key(Key::Up);
+        editor.key(Key::Up);
+        assert_eq!(editor.line(), "let a = 1;");
+        editor.key(Key::Down);
+        assert_eq!(editor.line(), "a + 1");
+        editor.key(Key::Down);
+        assert_eq!(editor.line(), "");
+    }
+
+    #[test]
+    fn ctrl_d_exits_only_on_an_empty_line() {
+        let mut editor = LineEditor::default();
+        type_text(&mut editor, "x");
+        assert_eq!(editor.key(Key::Ctrl('d')), Action::Continue);
+        editor.key(Key::Ctrl('u'));
+        assert_eq!(editor.key(Key::Ctrl('d')), Action::Exit);
+    }
+}
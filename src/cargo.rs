@@ -1,4 +1,4 @@
-/**
+/*
  * New project
  * cargo new foo
  * cargo new --lib foo
@@ -14,10 +14,11 @@
  *       |--my_other_bin.rs
  * for main.rs: cargo
  * for my_other_bin: cargo --bin my_other_bin
+ * (this package: src/bin/minigrep.rs, cargo run --bin minigrep; see minigrep.rs)
  *
  *
  *
- * Each file in ./test directory is a separate integration test
+ * Each file in ./tests directory is a separate integration test (see testing.rs)
  * cargo test
  * cargo test test_name
  *
@@ -0,0 +1,330 @@
+/*
+ * Continuation of std_library_types (Vec, HashMap, HashSet)
+ * std::collections, n = number of elements:
+ * - Vec: push/pop at the end O(1) amortized, insert/remove in the middle O(n)
+ * - VecDeque: push/pop at both ends O(1) amortized, ring buffer over one allocation
+ * - LinkedList: push/pop at both ends O(1), append O(1), anything by index O(n)
+ * - HashMap/HashSet: get/insert/remove O(1) expected, no order
+ * - BTreeMap/BTreeSet: get/insert/remove O(log n), sorted, range queries
+ * - BinaryHeap: push O(log n) (O(1) expected), pop O(log n), peek O(1), max first
+ * Vec and VecDeque are the default choice: contiguous memory beats asymptotics for small n
+ */
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, LinkedList, VecDeque};
+
+/*
+ * Ordered collections: iteration is sorted by key, range(..) finds the start in O(log n)
+ * and then walks k elements: O(log n + k)
+ */
+fn btree_ranges() {
+    use std::ops::Bound::{Excluded, Included, Unbounded};
+
+    //temperature readings by minute of the day
+    let readings: BTreeMap<u32, f64> = vec![
+        (0, 14.5),
+        (60, 14.0),
+        (360, 12.5),
+        (720, 21.0),
+        (780, 22.5),
+        (1080, 18.0),
+        (1380, 15.0),
+    ]
+    .into_iter()
+    .collect();
+
+    //afternoon: 12:00 to 18:00 exclusive
+    let afternoon: Vec<f64> = readings.range(720..1080).map(|(_, &t)| t).collect();
+    assert_eq!(afternoon, [21.0, 22.5]);
+
+    //last reading at or before 08:00, first one after it
+    assert_eq!(readings.range(..=480).next_back(), Some((&360, &12.5)));
+    assert_eq!(
+        readings.range((Excluded(480), Unbounded)).next(),
+        Some((&720, &21.0))
+    );
+    assert_eq!(readings.first_key_value(), Some((&0, &14.5)));
+    assert_eq!(readings.last_key_value(), Some((&1380, &15.0)));
+
+    //sorted set: prefix search over strings is a range too
+    let words: BTreeSet<&str> = ["car", "card", "care", "cart", "cat", "dog"]
+        .iter()
+        .copied()
+        .collect();
+    let car: Vec<&str> = words
+        .range::<&str, _>((Included("car"), Excluded("cas")))
+        .copied()
+        .collect();
+    assert_eq!(car, ["car", "card", "care", "cart"]);
+
+    //split_off: everything >= key moves into a new set, O(log n)
+    let mut numbers: BTreeSet<i32> = (1..=10).collect();
+    let upper = numbers.split_off(&6);
+    assert_eq!(numbers.iter().max(), Some(&5));
+    assert_eq!(upper.len(), 5);
+}
+
+/*
+ * Entry API: one lookup for "insert if missing, then update",
+ * instead of contains_key + insert + get_mut
+ */
+fn entry_api() {
+    let text = "the quick brown fox jumps over the lazy dog the end";
+
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for word in text.split_whitespace() {
+        *counts.entry(word).or_insert(0) += 1;
+    }
+    assert_eq!(counts["the"], 3);
+    assert_eq!(counts["fox"], 1);
+    assert_eq!(counts.get("cat"), None);
+
+    //most frequent words: HashMap has no order, sort explicitly (ties by word)
+    let mut frequent: Vec<(&str, usize)> = counts.into_iter().collect();
+    frequent.sort_by(|(a, x), (b, y)| y.cmp(x).then(a.cmp(b)));
+    assert_eq!(&frequent[..2], &[("the", 3), ("brown", 1)]);
+
+    //grouping: or_default creates the Vec on first use
+    let mut by_length: BTreeMap<usize, Vec<&str>> = BTreeMap::new();
+    for word in text.split_whitespace() {
+        by_length.entry(word.len()).or_default().push(word);
+    }
+    assert_eq!(by_length[&5], ["quick", "brown", "jumps"]);
+
+    //and_modify + or_insert_with: different code for existing and new keys
+    let mut first_seen: HashMap<char, (usize, usize)> = HashMap::new();
+    for (position, c) in "abracadabra".chars().enumerate() {
+        first_seen
+            .entry(c)
+            .and_modify(|(_, count)| *count += 1)
+            .or_insert_with(|| (position, 1));
+    }
+    assert_eq!(first_seen[&'a'], (0, 5));
+    assert_eq!(first_seen[&'c'], (4, 1));
+
+    //matching on the entry itself
+    use std::collections::hash_map::Entry;
+    let mut cache: HashMap<u64, u64> = HashMap::new();
+    let mut computed = 0;
+    for n in [10, 20, 10] {
+        match cache.entry(n) {
+            Entry::Occupied(entry) => assert_eq!(*entry.get(), n * n),
+            Entry::Vacant(entry) => {
+                computed += 1;
+                entry.insert(n * n);
+            }
+        }
+    }
+    assert_eq!(computed, 2);
+}
+
+/*
+ * VecDeque as a fixed-size ring buffer: keep the last N items,
+ * pop_front + push_back are O(1), no shifting like Vec::remove(0)
+ */
+fn ring_buffer() {
+    struct History<T> {
+        items: VecDeque<T>,
+        capacity: usize,
+    }
+
+    impl<T> History<T> {
+        fn new(capacity: usize) -> History<T> {
+            History {
+                items: VecDeque::with_capacity(capacity),
+                capacity,
+            }
+        }
+
+        //returns the evicted item
+        fn push(&mut self, item: T) -> Option<T> {
+            let evicted = if self.items.len() == self.capacity {
+                self.items.pop_front()
+            } else {
+                None
+            };
+            self.items.push_back(item);
+            evicted
+        }
+    }
+
+    let mut history = History::new(3);
+    let evicted: Vec<Option<i32>> = (1..=5).map(|i| history.push(i)).collect();
+    assert_eq!(evicted, [None, None, None, Some(1), Some(2)]);
+    assert_eq!(history.items, [3, 4, 5]);
+    assert_eq!(history.items.front(), Some(&3));
+    assert_eq!(history.items[2], 5);
+
+    //the ring may wrap around the end of the allocation: two slices
+    let (front, back) = history.items.as_slices();
+    assert_eq!(front.len() + back.len(), 3);
+    //one contiguous slice, moving elements if needed: O(n)
+    assert_eq!(history.items.make_contiguous(), &[3, 4, 5]);
+
+    //rotation: O(min(k, n - k))
+    let mut queue: VecDeque<char> = "abcde".chars().collect();
+    queue.rotate_left(2);
+    assert_eq!(queue.iter().collect::<String>(), "cdeab");
+}
+
+/*
+ * BinaryHeap is a max-heap; Reverse flips the ordering to get a min-heap
+ * Dijkstra: O((V + E) log V) with a heap and lazy deletion of stale entries
+ */
+fn dijkstra() {
+    //adjacency list: node -> [(neighbour, weight)]
+    fn shortest_paths(graph: &[Vec<(usize, u32)>], start: usize) -> Vec<Option<u32>> {
+        let mut distances: Vec<Option<u32>> = vec![None; graph.len()];
+        let mut heap = BinaryHeap::new();
+        distances[start] = Some(0);
+        heap.push(Reverse((0, start)));
+
+        while let Some(Reverse((distance, node))) = heap.pop() {
+            //a shorter path to this node was already processed
+            if distances[node].is_some_and(|best| distance > best) {
+                continue;
+            }
+            for &(next, weight) in &graph[node] {
+                let candidate = distance + weight;
+                if distances[next].is_none_or(|best| candidate < best) {
+                    distances[next] = Some(candidate);
+                    heap.push(Reverse((candidate, next)));
+                }
+            }
+        }
+        distances
+    }
+
+    //0 -> 1 (4), 0 -> 2 (1), 2 -> 1 (2), 1 -> 3 (1), 2 -> 3 (5), 4 unreachable
+    let graph = vec![
+        vec![(1, 4), (2, 1)],
+        vec![(3, 1)],
+        vec![(1, 2), (3, 5)],
+        vec![],
+        vec![(0, 1)],
+    ];
+    assert_eq!(
+        shortest_paths(&graph, 0),
+        [Some(0), Some(3), Some(1), Some(4), None]
+    );
+
+    //plain max-heap: peek is the largest, into_sorted_vec is ascending
+    let mut heap: BinaryHeap<i32> = vec![3, 1, 4, 1, 5].into_iter().collect();
+    assert_eq!(heap.peek(), Some(&5));
+    heap.push(9);
+    assert_eq!(heap.pop(), Some(9));
+    assert_eq!(heap.into_sorted_vec(), [1, 1, 3, 4, 5]);
+}
+
+/*
+ * LinkedList: rarely the right choice (pointer chasing, an allocation per node),
+ * useful when lists are split and joined often: append is O(1)
+ * Cursors (cursor_front_mut, insert_after, remove_current, splice_after) are unstable
+ * (#![feature(linked_list_cursors)]); on stable the same edits are done with
+ * split_off + append, which walk to the position: O(min(i, n - i))
+ */
+fn linked_list() {
+    fn text(list: &LinkedList<&str>) -> String {
+        list.iter().copied().collect::<Vec<_>>().join(" ")
+    }
+
+    let mut list: LinkedList<&str> = ["b", "c", "e"].iter().copied().collect();
+    list.push_front("a");
+    list.push_back("f");
+    assert_eq!(text(&list), "a b c e f");
+
+    //insert "d" after index 2: what cursor.insert_after would do
+    let mut tail = list.split_off(3);
+    list.push_back("d");
+    list.append(&mut tail);
+    assert_eq!(text(&list), "a b c d e f");
+
+    //remove the element at index 1: cursor.remove_current
+    let mut tail = list.split_off(1);
+    assert_eq!(tail.pop_front(), Some("b"));
+    list.append(&mut tail);
+    assert_eq!(text(&list), "a c d e f");
+
+    //splice a whole list in the middle: cursor.splice_after
+    let mut inserted: LinkedList<&str> = ["x", "y"].iter().copied().collect();
+    let mut tail = list.split_off(2);
+    list.append(&mut inserted);
+    list.append(&mut tail);
+    assert_eq!(text(&list), "a c x y d e f");
+    assert!(inserted.is_empty());
+
+    //in-place edits through iter_mut, both ends without walking
+    for item in list
+        .iter_mut()
+        .filter(|item| item.len() == 1 && **item > "w")
+    {
+        *item = "_";
+    }
+    assert_eq!(text(&list), "a c _ _ d e f");
+    assert_eq!((list.front(), list.back()), (Some(&"a"), Some(&"f")));
+}
+
+/*
+ * Slices: sort is stable O(n log n) (merge-based), sort_unstable is O(n log n) in place
+ * dedup removes consecutive duplicates only, O(n): sort first to remove all
+ * binary_search needs sorted input, O(log n); Err(i) is where the value would be inserted
+ */
+fn slices() {
+    #[derive(Debug, Clone, PartialEq)]
+    struct Player {
+        name: &'static str,
+        score: u32,
+    }
+    let player = |name, score| Player { name, score };
+
+    let mut players = [
+        player("ann", 30),
+        player("bob", 10),
+        player("cid", 30),
+        player("dan", 20),
+    ];
+    //stable: ann stays before cid
+    players.sort_by_key(|p| Reverse(p.score));
+    let names: Vec<&str> = players.iter().map(|p| p.name).collect();
+    assert_eq!(names, ["ann", "cid", "dan", "bob"]);
+
+    let mut numbers = vec![5, 3, 8, 3, 1, 8, 8, 2];
+    numbers.dedup();
+    assert_eq!(numbers, [5, 3, 8, 3, 1, 8, 2]);
+    numbers.sort_unstable();
+    numbers.dedup();
+    assert_eq!(numbers, [1, 2, 3, 5, 8]);
+
+    assert_eq!(numbers.binary_search(&5), Ok(3));
+    assert_eq!(numbers.binary_search(&4), Err(3));
+    //keeping a Vec sorted: insert at the Err position
+    if let Err(position) = numbers.binary_search(&4) {
+        numbers.insert(position, 4);
+    }
+    assert_eq!(numbers, [1, 2, 3, 4, 5, 8]);
+
+    //partition_point: first index where the predicate turns false
+    let scores = [10, 20, 20, 30, 40];
+    assert_eq!(scores.partition_point(|&s| s < 20), 1);
+    assert_eq!(scores.partition_point(|&s| s <= 20), 3);
+
+    //k smallest without a full sort: select_nth_unstable is O(n) on average
+    let mut data = [9, 4, 7, 1, 8, 2];
+    let (smallest, third, _) = data.select_nth_unstable(2);
+    smallest.sort_unstable();
+    assert_eq!((&smallest[..], *third), (&[1, 2][..], 4));
+
+    //dedup by key, on already grouped data
+    let mut events = vec![("login", 1), ("login", 2), ("click", 3), ("login", 4)];
+    events.dedup_by_key(|(kind, _)| *kind);
+    assert_eq!(events, [("login", 1), ("click", 3), ("login", 4)]);
+}
+
+pub fn main() {
+    btree_ranges();
+    entry_api();
+    ring_buffer();
+    dijkstra();
+    linked_list();
+    slices();
+}
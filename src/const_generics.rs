@@ -0,0 +1,613 @@
+/*
+ * Continuation of generics.rs
+ * Const generics: values (integers, bool, char) as generic parameters, <const N: usize>
+ * - [T; N] for any N, instead of impls for [T; 0] ... [T; 32]
+ * - the value is part of the type: Matrix<2, 3> and Matrix<3, 2> are different types
+ * - const arguments are written as literals, consts or {expressions} in braces
+ * - arithmetic on parameters in types (Matrix<R, {C + 1}>) is unstable (generic_const_exprs)
+ *
+ * Type-level programming with PhantomData (see generics::phantom_type_paramters):
+ * zero-sized marker types encode state or units, and impls exist only for valid combinations,
+ * so misuse is a compile error instead of a runtime check
+ *
+ * The compile errors are checked by compile_fail doc tests: cargo test --doc
+ */
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
+use std::ops::{Add, Deref, DerefMut, Div, Index, IndexMut, Mul, Sub};
+
+/// `R x C` matrix; multiplication only compiles when the inner dimensions agree.
+///
+/// ```
+/// use rust_by_example::const_generics::Matrix;
+///
+/// let a = Matrix::new([[1, 2, 3], [4, 5, 6]]);
+/// let b = Matrix::new([[1], [0], [-1]]);
+/// let c: Matrix<2, 1> = a * b;
+/// assert_eq!(c, Matrix::new([[-2], [-2]]));
+/// ```
+///
+/// (2 x 3) * (2 x 3) is rejected by the type checker:
+///
+/// ```compile_fail,E0308
+/// use rust_by_example::const_generics::Matrix;
+///
+/// let a = Matrix::new([[1, 2, 3], [4, 5, 6]]);
+/// let _ = a * a;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Matrix<const R: usize, const C: usize> {
+    rows: [[i64; C]; R],
+}
+
+impl<const R: usize, const C: usize> Matrix<R, C> {
+    pub fn new(rows: [[i64; C]; R]) -> Self {
+        Matrix { rows }
+    }
+
+    pub fn zero() -> Self {
+        Matrix { rows: [[0; C]; R] }
+    }
+
+    pub const ROWS: usize = R;
+    pub const COLUMNS: usize = C;
+
+    //dimensions swap in the return type
+    pub fn transpose(&self) -> Matrix<C, R> {
+        let mut result = Matrix::<C, R>::zero();
+        for i in 0..R {
+            for j in 0..C {
+                result[(j, i)] = self[(i, j)];
+            }
+        }
+        result
+    }
+}
+
+//only square matrices have an identity
+impl<const N: usize> Matrix<N, N> {
+    pub fn identity() -> Self {
+        let mut result = Self::zero();
+        for i in 0..N {
+            result[(i, i)] = 1;
+        }
+        result
+    }
+}
+
+impl<const R: usize, const C: usize> Index<(usize, usize)> for Matrix<R, C> {
+    type Output = i64;
+
+    fn index(&self, (row, column): (usize, usize)) -> &i64 {
+        &self.rows[row][column]
+    }
+}
+
+impl<const R: usize, const C: usize> IndexMut<(usize, usize)> for Matrix<R, C> {
+    fn index_mut(&mut self, (row, column): (usize, usize)) -> &mut i64 {
+        &mut self.rows[row][column]
+    }
+}
+
+//(R x C) * (C x K) = (R x K): the shared C is the compile-time check
+impl<const R: usize, const C: usize, const K: usize> Mul<Matrix<C, K>> for Matrix<R, C> {
+    type Output = Matrix<R, K>;
+
+    fn mul(self, other: Matrix<C, K>) -> Matrix<R, K> {
+        let mut result = Matrix::<R, K>::zero();
+        for i in 0..R {
+            for j in 0..K {
+                result[(i, j)] = (0..C).map(|k| self[(i, k)] * other[(k, j)]).sum();
+            }
+        }
+        result
+    }
+}
+
+/// Returned by [`ArrayVec::push`] when the vector is full; gives the element back.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CapacityError<T>(pub T);
+
+impl<T> fmt::Display for CapacityError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "insufficient capacity")
+    }
+}
+
+/// Vector with a fixed capacity `N`, stored inline (no heap allocation).
+///
+/// ```
+/// use rust_by_example::const_generics::{ArrayVec, CapacityError};
+///
+/// let mut stack: ArrayVec<String, 2> = ArrayVec::new();
+/// stack.push("a".to_string()).unwrap();
+/// stack.push("b".to_string()).unwrap();
+/// assert_eq!(stack.push("c".to_string()), Err(CapacityError("c".to_string())));
+/// assert_eq!(stack.join(""), "ab");
+/// ```
+pub struct ArrayVec<T, const N: usize> {
+    //items[..len] are initialized, the rest are not
+    items: [MaybeUninit<T>; N],
+    len: usize,
+}
+
+impl<T, const N: usize> ArrayVec<T, N> {
+    pub const CAPACITY: usize = N;
+
+    pub fn new() -> Self {
+        ArrayVec {
+            items: std::array::from_fn(|_| MaybeUninit::uninit()),
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, item: T) -> Result<(), CapacityError<T>> {
+        if self.len == N {
+            return Err(CapacityError(item));
+        }
+        self.items[self.len].write(item);
+        self.len += 1;
+        Ok(())
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        //the slot is now outside items[..len], so it won't be read or dropped again
+        Some(unsafe { self.items[self.len].assume_init_read() })
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+}
+
+impl<T, const N: usize> Default for ArrayVec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//len, is_empty, iter, indexing, ... come from the slice
+impl<T, const N: usize> Deref for ArrayVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        unsafe { std::slice::from_raw_parts(self.items.as_ptr() as *const T, self.len) }
+    }
+}
+
+impl<T, const N: usize> DerefMut for ArrayVec<T, N> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        unsafe { std::slice::from_raw_parts_mut(self.items.as_mut_ptr() as *mut T, self.len) }
+    }
+}
+
+impl<T, const N: usize> Drop for ArrayVec<T, N> {
+    fn drop(&mut self) {
+        //MaybeUninit never drops its content
+        unsafe { std::ptr::drop_in_place(&mut **self as *mut [T]) }
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for ArrayVec<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/*
+ * Typestate builder: which fields were set is tracked in the type parameters
+ * build() exists only on ServerBuilder<HasHost, HasPort>
+ */
+
+//marker types: never instantiated
+pub struct Missing;
+pub struct HasHost;
+pub struct HasPort;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Server {
+    pub host: String,
+    pub port: u16,
+    pub workers: usize,
+}
+
+/// Builder for [`Server`]; `build` is only available once host and port are set.
+///
+/// ```
+/// use rust_by_example::const_generics::ServerBuilder;
+///
+/// let server = ServerBuilder::new().port(8080).host("localhost").build();
+/// assert_eq!(server.port, 8080);
+/// ```
+///
+/// ```compile_fail,E0599
+/// use rust_by_example::const_generics::ServerBuilder;
+///
+/// let server = ServerBuilder::new().host("localhost").build();
+/// ```
+pub struct ServerBuilder<H, P> {
+    host: String,
+    port: u16,
+    workers: usize,
+    state: PhantomData<(H, P)>,
+}
+
+impl ServerBuilder<Missing, Missing> {
+    pub fn new() -> Self {
+        ServerBuilder {
+            host: String::new(),
+            port: 0,
+            workers: 1,
+            state: PhantomData,
+        }
+    }
+}
+
+impl Default for ServerBuilder<Missing, Missing> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//optional settings: any state, state is kept
+impl<H, P> ServerBuilder<H, P> {
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.workers = workers;
+        self
+    }
+}
+
+//required settings: consume the builder, return it in a new state
+//(setting the host twice doesn't compile either: host() needs H = Missing)
+impl<P> ServerBuilder<Missing, P> {
+    pub fn host(self, host: &str) -> ServerBuilder<HasHost, P> {
+        ServerBuilder {
+            host: host.to_string(),
+            port: self.port,
+            workers: self.workers,
+            state: PhantomData,
+        }
+    }
+}
+
+impl<H> ServerBuilder<H, Missing> {
+    pub fn port(self, port: u16) -> ServerBuilder<H, HasPort> {
+        ServerBuilder {
+            host: self.host,
+            port,
+            workers: self.workers,
+            state: PhantomData,
+        }
+    }
+}
+
+impl ServerBuilder<HasHost, HasPort> {
+    pub fn build(self) -> Server {
+        Server {
+            host: self.host,
+            port: self.port,
+            workers: self.workers,
+        }
+    }
+}
+
+/*
+ * Units of measure: f64 tagged with a unit marker
+ * Length<Metres> + Length<Feet> has no impl, conversions are explicit
+ */
+
+pub trait Unit {
+    const SYMBOL: &'static str;
+    //size of one unit in metres
+    const IN_METRES: f64;
+}
+
+//empty enums: can't be instantiated even by accident
+#[derive(Debug)]
+pub enum Metres {}
+#[derive(Debug)]
+pub enum Feet {}
+
+impl Unit for Metres {
+    const SYMBOL: &'static str = "m";
+    const IN_METRES: f64 = 1.0;
+}
+
+impl Unit for Feet {
+    const SYMBOL: &'static str = "ft";
+    const IN_METRES: f64 = 0.3048;
+}
+
+/// Length in unit `U`; only lengths with the same unit can be added.
+///
+/// ```
+/// use rust_by_example::const_generics::{Feet, Length, Metres};
+///
+/// let total = Length::<Metres>::new(1.0) + Length::<Feet>::new(10.0).convert();
+/// assert_eq!(total.to_string(), "4.048m");
+/// ```
+///
+/// ```compile_fail,E0308
+/// use rust_by_example::const_generics::{Feet, Length, Metres};
+///
+/// let _ = Length::<Metres>::new(1.0) + Length::<Feet>::new(10.0);
+/// ```
+#[derive(Debug)]
+pub struct Length<U> {
+    pub value: f64,
+    unit: PhantomData<U>,
+}
+
+//derives would require U: Clone, U: PartialEq, ...: markers don't implement them
+impl<U> Clone for Length<U> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<U> Copy for Length<U> {}
+
+impl<U> PartialEq for Length<U> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<U: Unit> Length<U> {
+    pub fn new(value: f64) -> Self {
+        Length {
+            value,
+            unit: PhantomData,
+        }
+    }
+
+    pub fn convert<V: Unit>(self) -> Length<V> {
+        Length::new(self.value * U::IN_METRES / V::IN_METRES)
+    }
+}
+
+impl<U: Unit> fmt::Display for Length<U> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.value, U::SYMBOL)
+    }
+}
+
+impl<U> Add for Length<U> {
+    type Output = Length<U>;
+
+    fn add(self, other: Length<U>) -> Length<U> {
+        Length {
+            value: self.value + other.value,
+            unit: PhantomData,
+        }
+    }
+}
+
+impl<U> Sub for Length<U> {
+    type Output = Length<U>;
+
+    fn sub(self, other: Length<U>) -> Length<U> {
+        Length {
+            value: self.value - other.value,
+            unit: PhantomData,
+        }
+    }
+}
+
+//scaling keeps the unit
+impl<U> Mul<f64> for Length<U> {
+    type Output = Length<U>;
+
+    fn mul(self, k: f64) -> Length<U> {
+        Length {
+            value: self.value * k,
+            unit: PhantomData,
+        }
+    }
+}
+
+//same unit divided by same unit: a plain number
+impl<U> Div for Length<U> {
+    type Output = f64;
+
+    fn div(self, other: Length<U>) -> f64 {
+        self.value / other.value
+    }
+}
+
+fn const_parameters() {
+    //one function for arrays of every length
+    fn sum<const N: usize>(values: [i32; N]) -> i32 {
+        values.iter().sum()
+    }
+
+    assert_eq!(sum([]), 0);
+    assert_eq!(sum([1, 2, 3]), 6);
+    assert_eq!(sum([1; 100]), 100);
+
+    //N is inferred from the return type's use
+    fn repeat<const N: usize>(value: char) -> [char; N] {
+        [value; N]
+    }
+    let dashes: [char; 3] = repeat('-');
+    assert_eq!(dashes.iter().collect::<String>(), "---");
+
+    //explicit argument: literal, const item or a block
+    const SIZE: usize = 2;
+    assert_eq!(repeat::<SIZE>('a'), ['a', 'a']);
+    assert_eq!(repeat::<{ SIZE * 2 }>('b').len(), 4);
+
+    //defaults, and non-usize parameters
+    struct Buffer<const LOUD: bool = false>;
+    impl<const LOUD: bool> Buffer<LOUD> {
+        fn shout(&self, text: &str) -> String {
+            if LOUD {
+                text.to_uppercase()
+            } else {
+                text.to_string()
+            }
+        }
+    }
+    let quiet: Buffer = Buffer;
+    assert_eq!(quiet.shout("hi"), "hi");
+    assert_eq!(Buffer::<true>.shout("hi"), "HI");
+
+    //Not allowed: parameter in an expression inside a type
+    // fn push<const N: usize>(values: [i32; N]) -> [i32; N + 1] { ... }
+    //error: generic parameters may not be used in const operations
+}
+
+fn matrices() {
+    let a = Matrix::new([[1, 2, 3], [4, 5, 6]]);
+    let b = a.transpose();
+    assert_eq!(b, Matrix::new([[1, 4], [2, 5], [3, 6]]));
+    assert_eq!((Matrix::<2, 3>::ROWS, Matrix::<2, 3>::COLUMNS), (2, 3));
+
+    //(2x3)*(3x2) = 2x2, (3x2)*(2x3) = 3x3
+    let square: Matrix<2, 2> = a * b;
+    assert_eq!(square, Matrix::new([[14, 32], [32, 77]]));
+    let bigger: Matrix<3, 3> = b * a;
+    assert_eq!(bigger[(2, 2)], 45);
+
+    assert_eq!(Matrix::identity() * a, a);
+    assert_eq!(a * Matrix::identity(), a);
+
+    //Not allowed: inner dimensions differ
+    // let _ = a * a;
+    //error[E0308]: mismatched types: expected `Matrix<3, _>`, found `Matrix<2, 3>`
+}
+
+fn array_vec() {
+    let mut numbers: ArrayVec<u32, 4> = ArrayVec::new();
+    assert_eq!(ArrayVec::<u32, 4>::CAPACITY, 4);
+    assert!(numbers.is_empty());
+
+    for i in 1..=5 {
+        if let Err(CapacityError(rejected)) = numbers.push(i * 10) {
+            assert_eq!(rejected, 50);
+        }
+    }
+    assert!(numbers.is_full());
+    assert_eq!(&numbers[..], &[10, 20, 30, 40]);
+    assert_eq!(format!("{:?}", numbers), "[10, 20, 30, 40]");
+
+    //slice methods through DerefMut
+    numbers.reverse();
+    assert_eq!(numbers.pop(), Some(10));
+    assert_eq!(numbers.iter().sum::<u32>(), 90);
+
+    //inline storage: size depends on N, no pointer to the heap
+    use std::mem::size_of;
+    assert_eq!(
+        size_of::<ArrayVec<u32, 4>>(),
+        4 * size_of::<u32>() + size_of::<usize>()
+    );
+
+    //non-Copy elements are dropped exactly once
+    use std::rc::Rc;
+    let shared = Rc::new(());
+    {
+        let mut clones: ArrayVec<Rc<()>, 3> = ArrayVec::default();
+        for _ in 0..3 {
+            clones.push(Rc::clone(&shared)).unwrap();
+        }
+        drop(clones.pop());
+        assert_eq!(Rc::strong_count(&shared), 3);
+    }
+    assert_eq!(Rc::strong_count(&shared), 1);
+}
+
+fn typestate_builder() {
+    let server = ServerBuilder::new()
+        .workers(4)
+        .host("127.0.0.1")
+        .port(80)
+        .build();
+    assert_eq!(
+        server,
+        Server {
+            host: "127.0.0.1".to_string(),
+            port: 80,
+            workers: 4,
+        }
+    );
+
+    //markers are zero-sized: same size as the fields alone
+    use std::mem::size_of;
+    assert_eq!(
+        size_of::<ServerBuilder<Missing, Missing>>(),
+        size_of::<ServerBuilder<HasHost, HasPort>>()
+    );
+
+    //Not allowed: port is missing
+    // ServerBuilder::new().host("localhost").build();
+    //error[E0599]: no method named `build` found for struct `ServerBuilder<HasHost, Missing>`
+}
+
+fn units_of_measure() {
+    let track = Length::<Metres>::new(400.0);
+    let ceiling = Length::<Feet>::new(10.0);
+
+    assert_eq!((track * 2.0).to_string(), "800m");
+    assert_eq!(track / Length::new(100.0), 4.0);
+
+    let ceiling_metres: Length<Metres> = ceiling.convert();
+    assert!((ceiling_metres.value - 3.048).abs() < 1e-9);
+    assert!((ceiling_metres.convert::<Feet>() - ceiling).value.abs() < 1e-9);
+
+    //Not allowed: different units
+    // let _ = track + ceiling;
+    //error[E0308]: mismatched types: expected `Length<Metres>`, found `Length<Feet>`
+}
+
+pub fn main() {
+    const_parameters();
+    matrices();
+    array_vec();
+    typestate_builder();
+    units_of_measure();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matrix_multiplication_is_associative() {
+        let a = Matrix::new([[1, 2], [3, 4], [5, 6]]);
+        let b = Matrix::new([[1, 0, 2], [0, 1, -1]]);
+        let c = Matrix::new([[2], [1], [0]]);
+        assert_eq!((a * b) * c, a * (b * c));
+    }
+
+    #[test]
+    fn transpose_of_product() {
+        let a = Matrix::new([[1, 2, 3]]);
+        let b = Matrix::new([[4], [5], [6]]);
+        assert_eq!((a * b).transpose(), b.transpose() * a.transpose());
+    }
+
+    #[test]
+    fn array_vec_of_zero_capacity() {
+        let mut empty: ArrayVec<String, 0> = ArrayVec::new();
+        assert!(empty.is_full());
+        assert_eq!(
+            empty.push("x".to_string()),
+            Err(CapacityError("x".to_string()))
+        );
+        assert_eq!(empty.pop(), None);
+    }
+
+    #[test]
+    fn conversion_round_trip() {
+        let length = Length::<Feet>::new(3.0);
+        let back: Length<Feet> = length.convert::<Metres>().convert();
+        assert!((back.value - 3.0).abs() < 1e-9);
+    }
+}
@@ -1,4 +1,4 @@
-/**
+/*
  * Structures: no args, unnamed args, named args
  * Constructing all three
  * Descrutcuring all three
@@ -32,7 +32,8 @@ fn structures() {
     }
 
     //Trivial structure
-    assert_eq!(Unit, Unit);
+    let unit = Unit;
+    assert_eq!(unit, Unit);
 
     let pair = Pair(1, 2.0);
     assert_eq!(pair.1, 2.0);
@@ -1,8 +1,10 @@
 fn panic() {
     //use env var RUST_BACKTRACE=0|1|full
     //panic!("bla");
+    //hooks, catch_unwind, unwinding and poisoning: see panics.rs
 }
 
+#[allow(clippy::unnecessary_literal_unwrap, clippy::manual_map)]
 fn option() {
     //Option: Some(T) | None
     let x: Option<i32> = Some(42);
@@ -30,9 +32,12 @@ fn option() {
 
     //combining with map and map_then
     let x2 = x.map(|v| v + 1);
-    let x3 = x.and_then(|v| Some(v));
+    //and_then: the closure itself may return None
+    let x3 = x.and_then(|v| v.checked_sub(42));
+    let x4 = x.and_then(|v| v.checked_mul(i32::MAX));
     assert_eq!(x2, Some(43));
-    assert_eq!(x3, Some(42));
+    assert_eq!(x3, Some(0));
+    assert_eq!(x4, None);
 }
 
 fn result() {
@@ -48,7 +53,7 @@ fn result() {
         }
     }
 
-    impl error::Error for Error {};
+    impl error::Error for Error {}
 
     //Result: Ok(T) | Err(E)
     let x: Result<u32, Error> = Ok(42);
@@ -79,8 +84,8 @@ fn result() {
     let y2 = y.map(|v| v + 1);
     assert_eq!(x2, Ok(43));
     assert_eq!(y2, Err(Error));
-    let x3 = x.and_then(|v| Ok(v + 1));
-    let y3 = y.and_then(|v| Ok(v + 1));
+    let x3 = x.and_then(|v| v.checked_add(1).ok_or(Error));
+    let y3 = y.and_then(|v| v.checked_add(1).ok_or(Error));
     assert_eq!(x3, Ok(43));
     assert_eq!(y3, Err(Error));
 
@@ -108,7 +113,7 @@ fn multiple_error_types() {
         }
     }
 
-    impl error::Error for Error {};
+    impl error::Error for Error {}
 
     //1. Converting Option to Result
     //option.ok_or
@@ -125,15 +130,15 @@ fn multiple_error_types() {
     type Result<T> = std::result::Result<T, Box<dyn error::Error>>;
     let y2: Result<i32> = "cake".parse::<i32>().map_err(|e| e.into());
     let y3: Result<i32> = Some(42).ok_or_else(|| Error.into());
-    assert_eq!(y2.is_err(), true);
-    assert_eq!(y3.is_ok(), true);
+    assert!(y2.is_err());
+    assert!(y3.is_ok());
 
     //3.2. Boxing errors + ?
     fn auto_box() -> Result<i32> {
         let x = "cake".parse::<i32>()?;
         Ok(x + 1)
     }
-    assert_eq!(auto_box().is_err(), true);
+    assert!(auto_box().is_err());
 
     //4. Wrapping errors + ?
     use std::num::ParseIntError;
@@ -152,7 +157,13 @@ fn multiple_error_types() {
         Ok(2 * parsed)
     }
     let r: WrappingResult<i32> = convert();
-    assert_eq!(r.is_err(), true);
+    match r {
+        Err(WrappingError::Parse(err)) => {
+            assert_eq!(err.to_string(), "invalid digit found in string")
+        }
+        Ok(_) => unreachable!(),
+    }
+    //source() chains, context and exit codes: see error_hierarchies.rs
 }
 
 fn iterating_over_results() {
@@ -0,0 +1,469 @@
+/*
+ * Continuation of error_handling::multiple_error_types
+ * - one error type per layer, each wrapping the error of the layer below
+ * - Error::source(): the wrapped error, so the whole chain can be walked from the top
+ * - Display shows only the current layer: the chain is printed by whoever reports the error
+ * - Backtrace: captured when an error is created, printed only if RUST_BACKTRACE is set
+ * - context: a message added on the way up, without a dedicated variant for every call site
+ * - Termination: what main's return value turns into (exit code + output)
+ */
+
+use std::error::Error;
+use std::fmt;
+
+//config.ini-like format: `key = value` lines, # comments
+mod config {
+    use std::error::Error;
+    use std::fmt;
+    use std::fs;
+    use std::io;
+    use std::num::ParseIntError;
+    use std::path::{Path, PathBuf};
+
+    #[derive(Debug, PartialEq)]
+    pub struct Config {
+        pub name: String,
+        pub port: u16,
+        pub workers: u32,
+    }
+
+    //lowest layer: one line of text
+    #[derive(Debug)]
+    pub enum ParseError {
+        MissingEquals,
+        UnknownKey(String),
+        InvalidNumber { key: String, source: ParseIntError },
+    }
+
+    impl fmt::Display for ParseError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                ParseError::MissingEquals => write!(f, "expected `key = value`"),
+                ParseError::UnknownKey(key) => write!(f, "unknown key `{}`", key),
+                ParseError::InvalidNumber { key, .. } => write!(f, "`{}` is not a number", key),
+            }
+        }
+    }
+
+    impl Error for ParseError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                ParseError::InvalidNumber { source, .. } => Some(source),
+                _ => None,
+            }
+        }
+    }
+
+    //values that parse, but make no sense together
+    #[derive(Debug)]
+    pub struct ValidationError {
+        pub field: &'static str,
+        pub reason: String,
+    }
+
+    impl fmt::Display for ValidationError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "invalid `{}`: {}", self.field, self.reason)
+        }
+    }
+
+    impl Error for ValidationError {}
+
+    //top layer: what load() returns
+    #[derive(Debug)]
+    pub enum ConfigError {
+        Io { path: PathBuf, source: io::Error },
+        Parse { line: usize, source: ParseError },
+        Validation(ValidationError),
+    }
+
+    //Display doesn't repeat the source: the report prints it on its own line
+    impl fmt::Display for ConfigError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                ConfigError::Io { path, .. } => write!(f, "can't read {}", path.display()),
+                ConfigError::Parse { line, .. } => write!(f, "syntax error on line {}", line),
+                ConfigError::Validation(_) => write!(f, "configuration is invalid"),
+            }
+        }
+    }
+
+    impl Error for ConfigError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                ConfigError::Io { source, .. } => Some(source),
+                ConfigError::Parse { source, .. } => Some(source),
+                ConfigError::Validation(source) => Some(source),
+            }
+        }
+    }
+
+    impl From<ValidationError> for ConfigError {
+        fn from(err: ValidationError) -> ConfigError {
+            ConfigError::Validation(err)
+        }
+    }
+
+    fn parse_number<T: std::str::FromStr<Err = ParseIntError>>(
+        key: &str,
+        value: &str,
+    ) -> Result<T, ParseError> {
+        value.parse().map_err(|source| ParseError::InvalidNumber {
+            key: key.to_string(),
+            source,
+        })
+    }
+
+    pub fn parse(text: &str) -> Result<Config, ConfigError> {
+        let mut config = Config {
+            name: String::from("unnamed"),
+            port: 0,
+            workers: 1,
+        };
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            //every ParseError gets its line number on the way up
+            let set = |config: &mut Config| -> Result<(), ParseError> {
+                let (key, value) = line.split_once('=').ok_or(ParseError::MissingEquals)?;
+                let (key, value) = (key.trim(), value.trim());
+                match key {
+                    "name" => config.name = value.to_string(),
+                    "port" => config.port = parse_number(key, value)?,
+                    "workers" => config.workers = parse_number(key, value)?,
+                    _ => return Err(ParseError::UnknownKey(key.to_string())),
+                }
+                Ok(())
+            };
+            set(&mut config).map_err(|source| ConfigError::Parse {
+                line: index + 1,
+                source,
+            })?;
+        }
+        validate(&config)?;
+        Ok(config)
+    }
+
+    fn validate(config: &Config) -> Result<(), ValidationError> {
+        if config.port < 1024 {
+            return Err(ValidationError {
+                field: "port",
+                reason: format!("{} is a privileged port", config.port),
+            });
+        }
+        if !(1..=64).contains(&config.workers) {
+            return Err(ValidationError {
+                field: "workers",
+                reason: format!("{} is not in 1..=64", config.workers),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Config, ConfigError> {
+        let text = fs::read_to_string(path).map_err(|source| ConfigError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        parse(&text)
+    }
+}
+
+//Walks source() down to the root cause, one line per layer
+fn report(error: &(dyn Error + 'static)) -> String {
+    let mut lines = vec![format!("error: {}", error)];
+    let causes = std::iter::successors(error.source(), |&cause| cause.source());
+    lines.extend(causes.map(|cause| format!("caused by: {}", cause)));
+    lines.join("\n")
+}
+
+fn root_cause<'a>(error: &'a (dyn Error + 'static)) -> &'a (dyn Error + 'static) {
+    match error.source() {
+        Some(source) => root_cause(source),
+        None => error,
+    }
+}
+
+fn source_chains() {
+    use config::{parse, ConfigError, ParseError};
+
+    let ok = parse("# server\nname = api\nport = 8080\nworkers = 4\n").unwrap();
+    assert_eq!((ok.name.as_str(), ok.port, ok.workers), ("api", 8080, 4));
+
+    let error = parse("name = api\nport = 80\n").unwrap_err();
+    assert_eq!(
+        report(&error),
+        "error: configuration is invalid\n\
+         caused by: invalid `port`: 80 is a privileged port"
+    );
+
+    //three layers: ConfigError -> ParseError -> ParseIntError
+    let error = parse("name = api\n\nport = 80a80\n").unwrap_err();
+    assert_eq!(
+        report(&error),
+        "error: syntax error on line 3\n\
+         caused by: `port` is not a number\n\
+         caused by: invalid digit found in string"
+    );
+
+    //causes are still typed: downcast to inspect a specific layer
+    let root = root_cause(&error);
+    assert!(root.is::<std::num::ParseIntError>());
+    let parse_error = error
+        .source()
+        .and_then(|source| source.downcast_ref::<ParseError>());
+    assert!(matches!(
+        parse_error,
+        Some(ParseError::InvalidNumber { key, .. }) if key == "port"
+    ));
+
+    //or match on the top layer directly
+    match parse("workers = 100\nport = 2000").unwrap_err() {
+        ConfigError::Validation(err) => assert_eq!(err.field, "workers"),
+        other => panic!("unexpected {:?}", other),
+    }
+    match parse("port 2000").unwrap_err() {
+        ConfigError::Parse {
+            line: 1,
+            source: ParseError::MissingEquals,
+        } => {}
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+/*
+ * Backtrace::capture() only records a trace if RUST_BACKTRACE or RUST_LIB_BACKTRACE
+ * is set (it's slow); force_capture() always does.
+ * Errors usually capture in their constructor or From impl, where the error is born
+ */
+fn backtraces() {
+    use std::backtrace::{Backtrace, BacktraceStatus};
+
+    #[derive(Debug)]
+    struct Traced<E> {
+        error: E,
+        backtrace: Backtrace,
+    }
+
+    //capturing in From: every `?` that converts into Traced records where it happened
+    impl<E> From<E> for Traced<E> {
+        fn from(error: E) -> Self {
+            Traced {
+                error,
+                backtrace: Backtrace::force_capture(),
+            }
+        }
+    }
+
+    fn failing() -> Result<(), Traced<std::num::ParseIntError>> {
+        "x".parse::<i32>()?;
+        Ok(())
+    }
+
+    let traced = failing().unwrap_err();
+    assert_eq!(traced.error.to_string(), "invalid digit found in string");
+    //Captured, unless the platform doesn't support backtraces
+    assert_ne!(traced.backtrace.status(), BacktraceStatus::Disabled);
+    //frames are only symbolized when the backtrace is printed: {} or {:?}
+    let _printed = traced.backtrace.to_string();
+
+    //capture() follows the environment variables
+    let lazy = Backtrace::capture();
+    let enabled = std::env::var("RUST_LIB_BACKTRACE")
+        .or_else(|_| std::env::var("RUST_BACKTRACE"))
+        .is_ok_and(|value| value != "0");
+    assert_eq!(lazy.status() == BacktraceStatus::Disabled, !enabled);
+}
+
+/*
+ * Context: like anyhow::Context, from scratch.
+ * ContextError keeps the original error as its source,
+ * so the report shows both what was being done and why it failed
+ */
+#[derive(Debug)]
+struct ContextError {
+    context: String,
+    source: Option<Box<dyn Error + Send + Sync>>,
+}
+
+impl fmt::Display for ContextError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.context)
+    }
+}
+
+impl Error for ContextError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_ref()
+            .map(|source| source.as_ref() as &(dyn Error + 'static))
+    }
+}
+
+trait Context<T> {
+    fn context<C: fmt::Display>(self, context: C) -> Result<T, ContextError>;
+
+    //closure: the message is only built on the error path
+    fn with_context<C: fmt::Display, F: FnOnce() -> C>(self, f: F) -> Result<T, ContextError>;
+}
+
+impl<T, E: Error + Send + Sync + 'static> Context<T> for Result<T, E> {
+    fn context<C: fmt::Display>(self, context: C) -> Result<T, ContextError> {
+        self.with_context(|| context)
+    }
+
+    fn with_context<C: fmt::Display, F: FnOnce() -> C>(self, f: F) -> Result<T, ContextError> {
+        self.map_err(|error| ContextError {
+            context: f().to_string(),
+            source: Some(Box::new(error)),
+        })
+    }
+}
+
+//None has no error to keep: the context is the whole error
+impl<T> Context<T> for Option<T> {
+    fn context<C: fmt::Display>(self, context: C) -> Result<T, ContextError> {
+        self.with_context(|| context)
+    }
+
+    fn with_context<C: fmt::Display, F: FnOnce() -> C>(self, f: F) -> Result<T, ContextError> {
+        self.ok_or_else(|| ContextError {
+            context: f().to_string(),
+            source: None,
+        })
+    }
+}
+
+fn context() {
+    use std::collections::HashMap;
+    use std::path::Path;
+
+    fn port_of(path: &Path) -> Result<u16, ContextError> {
+        let config = config::load(path)
+            .with_context(|| format!("loading settings for {}", path.display()))?;
+        Ok(config.port)
+    }
+
+    let missing = Path::new("/nonexistent/server.conf");
+    let error = port_of(missing).unwrap_err();
+    let report = report(&error);
+    let lines: Vec<&str> = report.lines().collect();
+    assert_eq!(
+        lines[0],
+        "error: loading settings for /nonexistent/server.conf"
+    );
+    assert_eq!(lines[1], "caused by: can't read /nonexistent/server.conf");
+    //the io::Error message is platform specific
+    assert_eq!(lines.len(), 3);
+
+    let users: HashMap<&str, u32> = vec![("root", 0)].into_iter().collect();
+    let uid = users.get("admin").context("no such user: admin");
+    assert_eq!(uid.unwrap_err().to_string(), "no such user: admin");
+}
+
+/*
+ * main can return any T: Termination
+ * (), Result<(), E: Debug> (Err prints {:?} and exits with 1), ExitCode, ...
+ * A custom Termination type chooses both the output and the exit code
+ */
+mod exit {
+    use super::config::ConfigError;
+    use std::error::Error;
+    use std::process::{ExitCode, Termination};
+
+    //sysexits.h codes
+    pub const EX_DATAERR: u8 = 65;
+    pub const EX_IOERR: u8 = 74;
+    pub const EX_CONFIG: u8 = 78;
+
+    pub struct Exit(pub Result<(), Box<dyn Error>>);
+
+    impl Exit {
+        //the first ConfigError in the chain decides, anything else is a plain failure
+        pub fn code(&self) -> u8 {
+            let error = match &self.0 {
+                Ok(()) => return 0,
+                Err(error) => error.as_ref(),
+            };
+            let config_error = std::iter::successors(Some(error), |&e| e.source())
+                .find_map(|e| e.downcast_ref::<ConfigError>());
+            match config_error {
+                Some(ConfigError::Io { .. }) => EX_IOERR,
+                Some(ConfigError::Parse { .. }) => EX_DATAERR,
+                Some(ConfigError::Validation(_)) => EX_CONFIG,
+                None => 1,
+            }
+        }
+    }
+
+    impl Termination for Exit {
+        fn report(self) -> ExitCode {
+            let code = self.code();
+            if let Err(error) = &self.0 {
+                eprintln!("{}", super::report(error.as_ref()));
+            }
+            ExitCode::from(code)
+        }
+    }
+
+    //`?` in main converts into Exit through Box<dyn Error>
+    impl<E: Into<Box<dyn Error>>> From<Result<(), E>> for Exit {
+        fn from(result: Result<(), E>) -> Exit {
+            Exit(result.map_err(Into::into))
+        }
+    }
+}
+
+fn termination() {
+    use exit::{Exit, EX_CONFIG, EX_DATAERR, EX_IOERR};
+    use std::fs;
+    use std::process::{ExitCode, Termination};
+
+    //what a real `fn main() -> Exit` would look like, with the path as an argument
+    fn run(path: &std::path::Path) -> Result<(), Box<dyn Error>> {
+        let config = config::load(path)?;
+        if config.name.is_empty() {
+            return Err("name can't be empty".into());
+        }
+        Ok(())
+    }
+
+    let directory = std::env::temp_dir().join(format!("rbe-errors-{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    let write = |name: &str, text: &str| {
+        let path = directory.join(name);
+        fs::write(&path, text).unwrap();
+        path
+    };
+
+    let good = write("good.conf", "port = 8080\n");
+    let syntax = write("syntax.conf", "port: 8080\n");
+    let invalid = write("invalid.conf", "port = 22\n");
+    let unnamed = write("unnamed.conf", "name =\nport = 8080\n");
+    let missing = directory.join("missing.conf");
+
+    assert_eq!(Exit(run(&good)).code(), 0);
+    assert_eq!(Exit(run(&syntax)).code(), EX_DATAERR);
+    assert_eq!(Exit(run(&invalid)).code(), EX_CONFIG);
+    assert_eq!(Exit(run(&missing)).code(), EX_IOERR);
+    assert_eq!(Exit(run(&unnamed)).code(), 1);
+
+    //found through the chain, under a context layer too
+    let wrapped: Result<(), ContextError> =
+        config::load(&missing).map(|_| ()).context("starting up");
+    assert_eq!(Exit::from(wrapped).code(), EX_IOERR);
+
+    //report() is what the runtime calls with main's return value
+    assert_eq!(Exit(run(&good)).report(), ExitCode::SUCCESS);
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+pub fn main() {
+    source_chains();
+    backtraces();
+    context();
+    termination();
+}
@@ -27,6 +27,7 @@ fn loop_loop() {
     assert_eq!(p, 42);
 }
 
+#[allow(clippy::let_unit_value)]
 fn while_loop() {
     let mut k = 1;
 
@@ -62,6 +63,7 @@ fn if_else() {
     assert_eq!(big_n, 8);
 }
 
+#[allow(clippy::let_unit_value, clippy::no_effect)]
 fn for_range_loop() {
     //for range/iterator loop
     //continue/break with labels available
@@ -127,12 +129,12 @@ fn for_iterator_loop() {
             names2.push(name)
         }
         //Can do: values were immutably borrowed
-        assert_eq!(names, names);
+        assert_eq!(names, ["Bob", "Frank", "Ferris"]);
         assert_eq!(names2, vec![&"Bob", &"Frank", &"Ferris"]);
     }
 
     {
-        let mut names = vec!["Bob", "Frank", "Ferris"];
+        let mut names = ["Bob", "Frank", "Ferris"];
         let mut names2 = vec![];
         for name in names.iter_mut() {
             names2.push(name)
@@ -143,6 +145,8 @@ fn for_iterator_loop() {
     }
 }
 
+//`|` between patterns is the point of the example, even where a range would do
+#[allow(clippy::manual_range_patterns)]
 fn match_control() {
     let number = 42;
 
@@ -180,16 +184,16 @@ fn match_and_destructuring() {
         x: (u32, u32),
         y: u32,
     }
-    let foo = Foo { x: (1, 2), y: 3 };
+    let value = Foo { x: (1, 2), y: 3 };
     //can use field name to refer
     //can rename field name to refer
     //can destrcuture field
     //can ignore field(s) with ..
-    let x = match foo {
+    let x = match value {
         Foo { x: (1, b), y } => format!("{} {}", b, y).to_owned(),
         Foo { x: i, y: 2 } => format!("{:?}", i).to_owned(),
-        Foo { x: (2, _), .. } => format!("ignored").to_owned(),
-        _ => format!("anything else"),
+        Foo { x: (2, _), .. } => "ignored".to_owned(),
+        _ => "anything else".to_owned(),
     };
     assert_eq!(x, "2 3");
 
@@ -198,13 +202,13 @@ fn match_and_destructuring() {
     enum Color {
         Red,
         Blue,
-        RGB(u32, u32, u32),
+        Rgb(u32, u32, u32),
     }
-    let color = Color::RGB(1, 2, 3);
+    let color = Color::Rgb(1, 2, 3);
     let c = match color {
         Color::Red => "red".to_owned(),
         Color::Blue => "blue".to_owned(),
-        Color::RGB(r, g, b) => format!("r{} g{} b{}", r, g, b),
+        Color::Rgb(r, g, b) => format!("r{} g{} b{}", r, g, b),
     };
 
     assert_eq!(c, "r1 g2 b3");
@@ -212,13 +216,13 @@ fn match_and_destructuring() {
 
 fn if_let() {
     let x = Some(42);
-    let y = !true;
+    let y = x.is_none();
     let result = if let Some(y) = x {
         format!("it's {}", y)
     } else if y {
-        format!("can't happen")
+        "can't happen".to_owned()
     } else {
-        format!("can't happen either")
+        "can't happen either".to_owned()
     };
     assert_eq!(result, "it's 42");
 
@@ -232,7 +236,7 @@ fn if_let() {
     let result = if let f @ Foo::Bar = bar {
         format!("matched to {:?}", f)
     } else {
-        format!("matched to smth else")
+        "matched to smth else".to_owned()
     };
     assert_eq!(result, "matched to Bar");
 }
@@ -252,6 +256,7 @@ fn while_let() {
 /**
  * Following * and &, mut and ref mut can be used anywhere in matching constructions listed in this file
  */
+#[allow(clippy::match_single_binding)]
 fn match_and_refrerences() {
     //destructure with * on value or with & on pattern
     {
@@ -171,7 +171,7 @@ fn closure_traits() {
     {
         let string = String::from("asdasd");
         let mut closure = || {
-            format!("{}", string);
+            let _ = string.len();
         };
         apply_fn(closure); //<-- moved here, since signature is "apply_fn<F>(f: F) where F: Fn()"
         apply_fnmut(&mut closure);
@@ -200,7 +200,7 @@ fn functions_as_closures() {
 fn returning_closures() {
     fn create_fn() -> impl Fn() -> String {
         let x = "cake1";
-        move || format!("{}", x)
+        move || x.to_string()
     }
     let f1 = create_fn();
     let result1 = f1();
@@ -210,7 +210,7 @@ fn returning_closures() {
         let mut x = String::from("cake");
         move || {
             x.push('2');
-            format!("{}", x)
+            x.clone()
         }
     }
     let mut f2 = create_fnmut();
@@ -221,8 +221,8 @@ fn returning_closures() {
         let mut x = String::from("cake");
         move || {
             x.push('3');
-            let y = x;
-            format!("{}", y)
+            //x itself is moved out: the closure can only be called once
+            x
         }
     }
     let f3 = create_fnonce();
@@ -232,21 +232,21 @@ fn returning_closures() {
 
 fn std_examples() {
     let mut vec = vec![1, 2, 3, 4];
-    let found1 = vec.iter().any(|&x: &i32| x == 2);
-    assert_eq!(found1, true);
+    let found1 = vec.iter().any(|&x: &i32| x % 2 == 0);
+    assert!(found1);
 
     let found2 = vec.iter_mut().any(|x: &mut i32| {
         *x += 1;
         *x == 2
     });
-    assert_eq!(found2, true);
+    assert!(found2);
     assert_eq!(vec, vec![2, 2, 3, 4]);
 
     let found3 = vec.into_iter().any(|x: i32| {
         let p = x;
         p == 2
     });
-    assert_eq!(found3, true);
+    assert!(found3);
     //vec was borrowed after move
     // assert_eq!(vec, vec![2, 2, 3, 4]);
 
@@ -254,7 +254,7 @@ fn std_examples() {
         .map(|n: i32| n * n)
         .take_while(|&n| n < 10)
         .filter(|&x| x > 4)
-        .fold(0, |acc, n| acc + n);
+        .sum::<i32>();
     assert_eq!(x, 9);
 }
 
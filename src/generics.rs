@@ -59,6 +59,10 @@ fn traits() {
     empty.double_drop(null);
 }
 
+pub(crate) trait HasArea {
+    fn area(&self) -> f64;
+}
+
 /**
  * Bounds allow to:
  * 1. Restrict generic type to types that satisfy bounds
@@ -80,9 +84,6 @@ fn bounds() {
 
     //Applciation 2: access methods of traits specified in bounds
     use std::fmt::Debug;
-    trait HasArea {
-        fn area(&self) -> f64;
-    }
     impl HasArea for Rectangle {
         fn area(&self) -> f64 {
             self.length * self.height
@@ -173,20 +174,27 @@ fn multiple_bounds() {
  */
 fn where_clause() {
     //Defining generic types in where
+    //(the traits only ever appear in bounds, nothing implements them)
     struct _YourType;
-    trait TraitB {};
-    trait TraitC {};
-    trait TraitE {};
-    trait TraitF {};
-    trait MyTrait<X, Y> {};
+    #[allow(dead_code)]
+    trait TraitB {}
+    #[allow(dead_code)]
+    trait TraitC {}
+    #[allow(dead_code)]
+    trait TraitE {}
+    #[allow(dead_code)]
+    trait TraitF {}
+    #[allow(dead_code)]
+    trait MyTrait<X, Y> {}
     impl<A, D> MyTrait<A, D> for _YourType
     where
         A: TraitB + TraitC,
         D: TraitE + TraitF,
     {
-    };
+    }
 
     //Defining bounds for Option<T> in where
+    #[allow(dead_code)]
     trait PrintInOption {
         fn print_in_option(self);
     }
@@ -202,6 +210,7 @@ fn where_clause() {
 
 fn new_type_idiom() {
     struct Years(i32);
+    #[allow(dead_code)]
     struct Days(i32);
 
     fn old_enough(age: &Years) -> bool {
@@ -210,7 +219,7 @@ fn new_type_idiom() {
 
     let _age_days = Days(4444);
     let age_years = Years(43);
-    assert_eq!(true, old_enough(&age_years));
+    assert!(old_enough(&age_years));
     //This won't work: types don't match
     // assert_eq!(true, old_enough(&_age_days));
 }
@@ -250,6 +259,7 @@ fn associated_types() {
     }
 
     let container = Container(1, 3);
+    assert!(container.contains(&1, &3));
     assert_eq!(2, difference(&container));
 }
 
@@ -265,7 +275,7 @@ fn phantom_type_paramters() {
     struct PhantomStruct<A, B> {
         _first: A,
         phantom: PhantomData<B>,
-    };
+    }
 
     let _t1: PhantomTuple<char, i32> = PhantomTuple('Q', PhantomData);
     let _t2: PhantomTuple<char, f64> = PhantomTuple('T', PhantomData);
@@ -278,6 +288,7 @@ fn phantom_type_paramters() {
         _first: 'Q',
         phantom: PhantomData,
     };
+    //marker types for typestate builders and units of measure: see const_generics.rs
 }
 
 pub fn main() {
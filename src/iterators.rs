@@ -0,0 +1,423 @@
+/*
+ * Iterator: next() -> Option<Item>, everything else is provided
+ * IntoIterator: what `for x in collection` calls, usually implemented three times
+ * - for T: yields T, consumes the collection
+ * - for &T: yields &T
+ * - for &mut T: yields &mut T
+ * FromIterator: what collect() calls
+ * Extend: adds items from an iterator to an existing collection
+ * DoubleEndedIterator: next_back(), enables rev()
+ * ExactSizeIterator: len(), must agree with size_hint()
+ */
+
+mod stack {
+    //part of the prelude only since edition 2021
+    use std::iter::FromIterator;
+
+    //Collection: items are pushed and popped at the top,
+    //iteration goes from the top to the bottom
+    #[derive(Debug, PartialEq)]
+    pub struct Stack<T> {
+        items: Vec<T>,
+    }
+
+    impl<T> Stack<T> {
+        pub fn new() -> Self {
+            Stack { items: Vec::new() }
+        }
+
+        pub fn push(&mut self, item: T) {
+            self.items.push(item);
+        }
+
+        pub fn pop(&mut self) -> Option<T> {
+            self.items.pop()
+        }
+
+        pub fn iter(&self) -> Iter<'_, T> {
+            Iter { items: &self.items }
+        }
+
+        pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+            IterMut {
+                items: &mut self.items,
+            }
+        }
+    }
+
+    //1. Owned iterator: wraps the Vec one, but reverses the direction
+    pub struct IntoIter<T>(std::iter::Rev<std::vec::IntoIter<T>>);
+
+    impl<T> Iterator for IntoIter<T> {
+        type Item = T;
+
+        fn next(&mut self) -> Option<T> {
+            self.0.next()
+        }
+
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            self.0.size_hint()
+        }
+    }
+
+    impl<T> DoubleEndedIterator for IntoIter<T> {
+        fn next_back(&mut self) -> Option<T> {
+            self.0.next_back()
+        }
+    }
+
+    impl<T> ExactSizeIterator for IntoIter<T> {}
+
+    impl<T> IntoIterator for Stack<T> {
+        type Item = T;
+        type IntoIter = IntoIter<T>;
+
+        fn into_iter(self) -> IntoIter<T> {
+            IntoIter(self.items.into_iter().rev())
+        }
+    }
+
+    //2. Shared iterator: written by hand on top of a slice
+    pub struct Iter<'a, T> {
+        items: &'a [T],
+    }
+
+    impl<'a, T> Iterator for Iter<'a, T> {
+        type Item = &'a T;
+
+        fn next(&mut self) -> Option<&'a T> {
+            let (last, rest) = self.items.split_last()?;
+            self.items = rest;
+            Some(last)
+        }
+
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            (self.items.len(), Some(self.items.len()))
+        }
+    }
+
+    impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+        fn next_back(&mut self) -> Option<&'a T> {
+            let (first, rest) = self.items.split_first()?;
+            self.items = rest;
+            Some(first)
+        }
+    }
+
+    impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+    impl<'a, T> IntoIterator for &'a Stack<T> {
+        type Item = &'a T;
+        type IntoIter = Iter<'a, T>;
+
+        fn into_iter(self) -> Iter<'a, T> {
+            self.iter()
+        }
+    }
+
+    //3. Mutable iterator: the slice has to be taken out of self,
+    //otherwise the returned reference would borrow from &mut self
+    pub struct IterMut<'a, T> {
+        items: &'a mut [T],
+    }
+
+    impl<'a, T> Iterator for IterMut<'a, T> {
+        type Item = &'a mut T;
+
+        fn next(&mut self) -> Option<&'a mut T> {
+            let items = std::mem::take(&mut self.items);
+            let (last, rest) = items.split_last_mut()?;
+            self.items = rest;
+            Some(last)
+        }
+
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            (self.items.len(), Some(self.items.len()))
+        }
+    }
+
+    impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+        fn next_back(&mut self) -> Option<&'a mut T> {
+            let items = std::mem::take(&mut self.items);
+            let (first, rest) = items.split_first_mut()?;
+            self.items = rest;
+            Some(first)
+        }
+    }
+
+    impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+    impl<'a, T> IntoIterator for &'a mut Stack<T> {
+        type Item = &'a mut T;
+        type IntoIter = IterMut<'a, T>;
+
+        fn into_iter(self) -> IterMut<'a, T> {
+            self.iter_mut()
+        }
+    }
+
+    //collect() into a Stack: items are pushed in iteration order
+    impl<T> FromIterator<T> for Stack<T> {
+        fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+            let mut stack = Stack::new();
+            stack.extend(iter);
+            stack
+        }
+    }
+
+    impl<T> Extend<T> for Stack<T> {
+        fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+            for item in iter {
+                self.push(item);
+            }
+        }
+    }
+}
+
+fn custom_collection() {
+    use stack::Stack;
+
+    let mut stack: Stack<i32> = (1..=3).collect();
+    stack.extend(vec![4, 5]);
+    assert_eq!(stack.pop(), Some(5));
+
+    //&T: stack is still usable afterwards
+    let mut seen = Vec::new();
+    for x in &stack {
+        seen.push(*x);
+    }
+    assert_eq!(seen, [4, 3, 2, 1]);
+
+    //&mut T
+    for x in &mut stack {
+        *x *= 10;
+    }
+    assert_eq!(stack.iter().copied().collect::<Vec<_>>(), [40, 30, 20, 10]);
+
+    //DoubleEndedIterator and ExactSizeIterator
+    let mut iter = stack.iter();
+    assert_eq!(iter.len(), 4);
+    assert_eq!(iter.next(), Some(&40));
+    assert_eq!(iter.next_back(), Some(&10));
+    assert_eq!(iter.len(), 2);
+    assert_eq!(stack.iter().rev().collect::<Vec<_>>(), [&10, &20, &30, &40]);
+    assert_eq!(stack.iter_mut().rev().len(), 4);
+
+    //T: stack is moved into the loop
+    let mut owned = Vec::new();
+    for x in stack {
+        owned.push(x);
+    }
+    assert_eq!(owned, [40, 30, 20, 10]);
+    //Not allowed: stack was moved
+    // stack.push(1);
+
+    //generic code only needs IntoIterator
+    fn sum<'a, I: IntoIterator<Item = &'a i32>>(items: I) -> i32 {
+        items.into_iter().sum()
+    }
+    let stack: Stack<i32> = vec![1, 2, 3].into_iter().collect();
+    assert_eq!(sum(&stack), 6);
+    assert_eq!(sum(&vec![1, 2, 3]), 6);
+}
+
+/*
+ * Custom adaptor:
+ * 1. Struct wrapping the inner iterator
+ * 2. Iterator impl for the struct
+ * 3. Extension trait with a blanket impl, so the adaptor is callable as a method
+ */
+mod adaptors {
+    use std::iter::Peekable;
+
+    pub struct ChunksByKey<I: Iterator, F> {
+        iter: Peekable<I>,
+        key: F,
+    }
+
+    impl<I, F, K> Iterator for ChunksByKey<I, F>
+    where
+        I: Iterator,
+        F: FnMut(&I::Item) -> K,
+        K: PartialEq,
+    {
+        type Item = (K, Vec<I::Item>);
+
+        fn next(&mut self) -> Option<Self::Item> {
+            let first = self.iter.next()?;
+            let key = (self.key)(&first);
+            let mut chunk = vec![first];
+            //closures capture the whole `self` before edition 2021, so borrow the field separately
+            let key_of = &mut self.key;
+            //next_if consumes the item only when the predicate holds
+            while let Some(item) = self.iter.next_if(|item| key_of(item) == key) {
+                chunk.push(item);
+            }
+            Some((key, chunk))
+        }
+    }
+
+    pub trait IteratorExt: Iterator + Sized {
+        //Groups consecutive items that have the same key
+        fn chunks_by_key<K, F>(self, key: F) -> ChunksByKey<Self, F>
+        where
+            F: FnMut(&Self::Item) -> K,
+            K: PartialEq,
+        {
+            ChunksByKey {
+                iter: self.peekable(),
+                key,
+            }
+        }
+    }
+
+    impl<I: Iterator> IteratorExt for I {}
+}
+
+fn custom_adaptor() {
+    use adaptors::IteratorExt;
+
+    let words = [
+        "apple",
+        "avocado",
+        "banana",
+        "blueberry",
+        "cherry",
+        "apricot",
+    ];
+    let chunks: Vec<(char, Vec<&str>)> = words
+        .iter()
+        .copied()
+        .chunks_by_key(|word| word.chars().next().unwrap())
+        .collect();
+    assert_eq!(
+        chunks,
+        [
+            ('a', vec!["apple", "avocado"]),
+            ('b', vec!["banana", "blueberry"]),
+            ('c', vec!["cherry"]),
+            //only consecutive items are grouped
+            ('a', vec!["apricot"]),
+        ]
+    );
+
+    //composes with std adaptors
+    let run_lengths: Vec<(bool, usize)> = [1, 3, 5, 2, 4, 7]
+        .iter()
+        .chunks_by_key(|x| *x % 2 == 0)
+        .map(|(even, chunk)| (even, chunk.len()))
+        .collect();
+    assert_eq!(run_lengths, [(false, 3), (true, 2), (false, 1)]);
+}
+
+fn peekable_parsing() {
+    use std::iter::Peekable;
+    use std::str::Chars;
+
+    #[derive(Debug, PartialEq)]
+    enum Token {
+        Number(u64),
+        Ident(String),
+        Op(char),
+    }
+
+    //peek() looks at the next item without consuming it
+    fn number(chars: &mut Peekable<Chars>) -> u64 {
+        let mut value = 0;
+        while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+            value = value * 10 + digit as u64;
+            chars.next();
+        }
+        value
+    }
+
+    fn tokenize(input: &str) -> Result<Vec<Token>, char> {
+        let mut chars = input.chars().peekable();
+        let mut tokens = Vec::new();
+        while let Some(&c) = chars.peek() {
+            match c {
+                ' ' => {
+                    chars.next();
+                }
+                '0'..='9' => tokens.push(Token::Number(number(&mut chars))),
+                'a'..='z' => {
+                    let mut ident = String::new();
+                    //next_if: peek + conditional next in one call
+                    while let Some(c) = chars.next_if(char::is_ascii_alphanumeric) {
+                        ident.push(c);
+                    }
+                    tokens.push(Token::Ident(ident));
+                }
+                '+' | '-' | '*' | '/' => {
+                    tokens.push(Token::Op(c));
+                    chars.next();
+                }
+                other => return Err(other),
+            }
+        }
+        Ok(tokens)
+    }
+
+    assert_eq!(
+        tokenize("12 + x2*345"),
+        Ok(vec![
+            Token::Number(12),
+            Token::Op('+'),
+            Token::Ident("x2".to_owned()),
+            Token::Op('*'),
+            Token::Number(345),
+        ])
+    );
+    assert_eq!(tokenize("1 % 2"), Err('%'));
+}
+
+fn checked_fibonacci() {
+    //traits::iterators version computes curr + next with plain `+`:
+    //panics in debug, wraps in release after u32::MAX is exceeded
+    struct Fibonacci {
+        curr: Option<u32>,
+        next: Option<u32>,
+    }
+
+    impl Iterator for Fibonacci {
+        type Item = u32;
+
+        fn next(&mut self) -> Option<u32> {
+            let curr = self.curr?;
+            self.curr = self.next;
+            //None once the sum doesn't fit: sequence ends after the last representable item
+            self.next = self.next.and_then(|next| curr.checked_add(next));
+            Some(curr)
+        }
+    }
+
+    //once None is returned, None is returned forever
+    impl std::iter::FusedIterator for Fibonacci {}
+
+    fn fibonacci() -> Fibonacci {
+        Fibonacci {
+            curr: Some(0),
+            next: Some(1),
+        }
+    }
+
+    assert_eq!(
+        fibonacci().take(8).collect::<Vec<_>>(),
+        [0, 1, 1, 2, 3, 5, 8, 13]
+    );
+    //F(0)..=F(47) fit into u32, F(48) doesn't
+    assert_eq!(fibonacci().count(), 48);
+    assert_eq!(fibonacci().last(), Some(2_971_215_073));
+
+    let mut sequence = fibonacci().skip(47);
+    assert_eq!(sequence.next(), Some(2_971_215_073));
+    assert_eq!(sequence.next(), None);
+    assert_eq!(sequence.next(), None);
+}
+
+pub fn main() {
+    custom_collection();
+    custom_adaptor();
+    peekable_parsing();
+    checked_fibonacci();
+}
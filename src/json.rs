@@ -0,0 +1,106 @@
+/*
+ * JSON without external crates
+ * - value: `enum Value`, one variant per JSON type; Display prints it, {:#} pretty
+ * - parser: text -> Value, errors with line and column
+ * - convert: ToJson / FromJson between Value and Rust types, user structs included
+ * Pieces from earlier chapters: custom_types::enums, traits, macro_rules (impls for every
+ * integer type), error_handling (own error types), collections (BTreeMap, HashMap)
+ *
+ * Conformance cases (escapes, surrogate pairs, nesting, malformed input) are in tests/json.rs
+ */
+
+mod convert;
+mod parser;
+mod value;
+
+pub use convert::{field, FromJson, FromJsonError, ToJson};
+pub use parser::{parse, Error, ErrorKind, MAX_DEPTH};
+pub use value::Value;
+
+use std::collections::{BTreeMap, HashMap};
+
+//a user type: both directions written by hand, one line per field
+#[derive(Debug, PartialEq)]
+struct Package {
+    name: String,
+    version: String,
+    downloads: u64,
+    keywords: Vec<String>,
+    homepage: Option<String>,
+}
+
+impl ToJson for Package {
+    fn to_json(&self) -> Value {
+        let mut members = BTreeMap::new();
+        members.insert("name".to_string(), self.name.to_json());
+        members.insert("version".to_string(), self.version.to_json());
+        members.insert("downloads".to_string(), self.downloads.to_json());
+        members.insert("keywords".to_string(), self.keywords.to_json());
+        members.insert("homepage".to_string(), self.homepage.to_json());
+        Value::Object(members)
+    }
+}
+
+impl FromJson for Package {
+    fn from_json(value: &Value) -> Result<Package, FromJsonError> {
+        Ok(Package {
+            name: field(value, "name")?,
+            version: field(value, "version")?,
+            downloads: field(value, "downloads")?,
+            keywords: field(value, "keywords")?,
+            homepage: field(value, "homepage")?,
+        })
+    }
+}
+
+pub fn main() {
+    let text = r#"
+        {
+            "name": "termion",
+            "version": "4.0.6",
+            "downloads": 11000000,
+            "keywords": ["tty", "terminal", "color"]
+        }
+    "#;
+    let value = parse(text).unwrap();
+    //navigating the tree: a missing key is null, not a panic
+    assert_eq!(value["keywords"][1].as_str(), Some("terminal"));
+    assert!(value["homepage"].is_null());
+
+    let package = Package::from_json(&value).unwrap();
+    assert_eq!(package.downloads, 11_000_000);
+    assert_eq!(package.homepage, None);
+
+    //{} is compact, {:#} pretty; keys come out sorted
+    assert_eq!(
+        package.to_json().to_string(),
+        r#"{"downloads":11000000,"homepage":null,"keywords":["tty","terminal","color"],"name":"termion","version":"4.0.6"}"#
+    );
+    println!("{:#}", package.to_json());
+
+    //back and forth: the text parses to the same package
+    let again = Package::from_json(&parse(&format!("{:#}", package.to_json())).unwrap()).unwrap();
+    assert_eq!(again, package);
+
+    //standard collections work out of the box
+    let mut scores: HashMap<String, Vec<u8>> = HashMap::new();
+    scores.insert("ferris".to_string(), vec![10, 9]);
+    let json = scores.to_json().to_string();
+    assert_eq!(json, r#"{"ferris":[10,9]}"#);
+    assert_eq!(
+        HashMap::<String, Vec<u8>>::from_json(&parse(&json).unwrap()).unwrap(),
+        scores
+    );
+
+    //the two kinds of errors: malformed text, and well-formed text of the wrong shape
+    let err = parse("{\n  \"name\": 'termion'\n}").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "unexpected character '\\'' at line 2, column 11"
+    );
+    let err = Package::from_json(
+        &parse(r#"{"name": "x", "version": "1", "downloads": -1, "keywords": []}"#).unwrap(),
+    )
+    .unwrap_err();
+    assert_eq!(err.to_string(), "$.downloads: -1 out of range for u64");
+}
@@ -0,0 +1,317 @@
+/*
+ * Typed conversions: ToJson builds a Value, FromJson reads one back
+ * - primitives, Option, Vec, HashMap<String, _> here; user structs implement the traits by hand
+ *   (field() does the lookup and records where an error happened)
+ * - errors carry a path into the document: $.users[1].age
+ */
+
+use super::value::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+
+pub trait ToJson {
+    fn to_json(&self) -> Value;
+}
+
+pub trait FromJson: Sized {
+    fn from_json(value: &Value) -> Result<Self, FromJsonError>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FromJsonError {
+    //segments from the root down, built up while the error travels outwards
+    path: Vec<String>,
+    pub message: String,
+}
+
+impl FromJsonError {
+    pub fn new(message: String) -> FromJsonError {
+        FromJsonError {
+            path: Vec::new(),
+            message,
+        }
+    }
+
+    pub fn mismatch(expected: &str, found: &Value) -> FromJsonError {
+        FromJsonError::new(format!(
+            "expected {}, found {}",
+            expected,
+            found.type_name()
+        ))
+    }
+
+    fn inside(mut self, segment: String) -> FromJsonError {
+        self.path.insert(0, segment);
+        self
+    }
+
+    /// Where in the document: `$` is the root.
+    pub fn path(&self) -> String {
+        let mut path = String::from("$");
+        path.extend(self.path.iter().map(String::as_str));
+        path
+    }
+}
+
+impl fmt::Display for FromJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path(), self.message)
+    }
+}
+
+impl error::Error for FromJsonError {}
+
+/// Reads member `name` of an object. A missing member reads as `null`,
+/// so an `Option` field may be left out and anything else reports it missing.
+pub fn field<T: FromJson>(object: &Value, name: &str) -> Result<T, FromJsonError> {
+    if object.as_object().is_none() {
+        return Err(FromJsonError::mismatch("object", object));
+    }
+    match object.get(name) {
+        Some(value) => T::from_json(value),
+        None => T::from_json(&Value::Null)
+            .map_err(|_| FromJsonError::new(format!("missing field `{}`", name))),
+    }
+    .map_err(|err| err.inside(format!(".{}", name)))
+}
+
+impl ToJson for Value {
+    fn to_json(&self) -> Value {
+        self.clone()
+    }
+}
+
+impl FromJson for Value {
+    fn from_json(value: &Value) -> Result<Value, FromJsonError> {
+        Ok(value.clone())
+    }
+}
+
+impl ToJson for bool {
+    fn to_json(&self) -> Value {
+        Value::Bool(*self)
+    }
+}
+
+impl FromJson for bool {
+    fn from_json(value: &Value) -> Result<bool, FromJsonError> {
+        value
+            .as_bool()
+            .ok_or_else(|| FromJsonError::mismatch("boolean", value))
+    }
+}
+
+impl ToJson for str {
+    fn to_json(&self) -> Value {
+        Value::String(self.to_string())
+    }
+}
+
+impl ToJson for String {
+    fn to_json(&self) -> Value {
+        Value::String(self.clone())
+    }
+}
+
+impl FromJson for String {
+    fn from_json(value: &Value) -> Result<String, FromJsonError> {
+        value
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| FromJsonError::mismatch("string", value))
+    }
+}
+
+impl ToJson for char {
+    fn to_json(&self) -> Value {
+        Value::String(self.to_string())
+    }
+}
+
+impl FromJson for char {
+    fn from_json(value: &Value) -> Result<char, FromJsonError> {
+        let mut chars = value.as_str().map(str::chars);
+        match chars.as_mut().map(|chars| (chars.next(), chars.next())) {
+            Some((Some(c), None)) => Ok(c),
+            _ => Err(FromJsonError::mismatch("single-character string", value)),
+        }
+    }
+}
+
+//the same two impls for every float type
+macro_rules! json_float {
+    ($($t:ty),*) => {$(
+        impl ToJson for $t {
+            fn to_json(&self) -> Value {
+                Value::Number(*self as f64)
+            }
+        }
+
+        impl FromJson for $t {
+            fn from_json(value: &Value) -> Result<$t, FromJsonError> {
+                value
+                    .as_f64()
+                    .map(|n| n as $t)
+                    .ok_or_else(|| FromJsonError::mismatch("number", value))
+            }
+        }
+    )*};
+}
+
+json_float!(f32, f64);
+
+//integers: only whole numbers that fit the type; i64/u64 beyond 2^53 lose precision in f64
+macro_rules! json_integer {
+    ($($t:ty),*) => {$(
+        impl ToJson for $t {
+            fn to_json(&self) -> Value {
+                Value::Number(*self as f64)
+            }
+        }
+
+        impl FromJson for $t {
+            fn from_json(value: &Value) -> Result<$t, FromJsonError> {
+                let n = value
+                    .as_f64()
+                    .ok_or_else(|| FromJsonError::mismatch("integer", value))?;
+                if n.fract() != 0.0 {
+                    return Err(FromJsonError::new(format!("expected integer, found {}", n)));
+                }
+                //`as i128` saturates, try_from does the range check
+                <$t>::try_from(n as i128).map_err(|_| {
+                    FromJsonError::new(format!("{} out of range for {}", n, stringify!($t)))
+                })
+            }
+        }
+    )*};
+}
+
+json_integer!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl<T: ToJson> ToJson for Option<T> {
+    fn to_json(&self) -> Value {
+        match self {
+            Some(value) => value.to_json(),
+            None => Value::Null,
+        }
+    }
+}
+
+impl<T: FromJson> FromJson for Option<T> {
+    fn from_json(value: &Value) -> Result<Option<T>, FromJsonError> {
+        match value {
+            Value::Null => Ok(None),
+            value => T::from_json(value).map(Some),
+        }
+    }
+}
+
+impl<T: ToJson> ToJson for [T] {
+    fn to_json(&self) -> Value {
+        Value::Array(self.iter().map(ToJson::to_json).collect())
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn to_json(&self) -> Value {
+        self.as_slice().to_json()
+    }
+}
+
+impl<T: FromJson> FromJson for Vec<T> {
+    fn from_json(value: &Value) -> Result<Vec<T>, FromJsonError> {
+        let items = value
+            .as_array()
+            .ok_or_else(|| FromJsonError::mismatch("array", value))?;
+        items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| T::from_json(item).map_err(|err| err.inside(format!("[{}]", i))))
+            .collect()
+    }
+}
+
+impl<T: ToJson> ToJson for HashMap<String, T> {
+    fn to_json(&self) -> Value {
+        Value::Object(
+            self.iter()
+                .map(|(key, value)| (key.clone(), value.to_json()))
+                .collect(),
+        )
+    }
+}
+
+impl<T: FromJson> FromJson for HashMap<String, T> {
+    fn from_json(value: &Value) -> Result<HashMap<String, T>, FromJsonError> {
+        let members: &BTreeMap<String, Value> = value
+            .as_object()
+            .ok_or_else(|| FromJsonError::mismatch("object", value))?;
+        members
+            .iter()
+            .map(|(key, member)| {
+                T::from_json(member)
+                    .map(|value| (key.clone(), value))
+                    .map_err(|err| err.inside(format!(".{}", key)))
+            })
+            .collect()
+    }
+}
+
+//references serialize like what they point to: to_json(&&item) in generic code just works
+impl<T: ToJson + ?Sized> ToJson for &T {
+    fn to_json(&self) -> Value {
+        (**self).to_json()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn primitives() {
+        assert_eq!(true.to_json(), Value::Bool(true));
+        assert_eq!("hi".to_json(), Value::String("hi".into()));
+        assert_eq!(u8::from_json(&Value::Number(255.0)), Ok(255));
+        assert_eq!(
+            u8::from_json(&Value::Number(256.0)).unwrap_err().message,
+            "256 out of range for u8"
+        );
+        assert_eq!(
+            i32::from_json(&Value::Number(1.5)).unwrap_err().message,
+            "expected integer, found 1.5"
+        );
+        assert_eq!(char::from_json(&'é'.to_json()), Ok('é'));
+        assert!(char::from_json(&"ab".to_json()).is_err());
+    }
+
+    #[test]
+    fn paths_point_at_the_culprit() {
+        let list = Value::Array(vec![Value::Number(1.0), Value::String("two".into())]);
+        let err = Vec::<i32>::from_json(&list).unwrap_err();
+        assert_eq!(err.to_string(), "$[1]: expected integer, found string");
+
+        let mut map = HashMap::new();
+        map.insert("xs".to_string(), list);
+        let err = HashMap::<String, Vec<i32>>::from_json(&map.to_json()).unwrap_err();
+        assert_eq!(err.path(), "$.xs[1]");
+    }
+
+    #[test]
+    fn optional_fields_may_be_missing() {
+        let object = Value::Object(BTreeMap::new());
+        assert_eq!(field::<Option<String>>(&object, "nick"), Ok(None));
+        assert_eq!(
+            field::<String>(&object, "name").unwrap_err().to_string(),
+            "$.name: missing field `name`"
+        );
+        assert_eq!(
+            field::<String>(&Value::Null, "name")
+                .unwrap_err()
+                .to_string(),
+            "$: expected object, found null"
+        );
+    }
+}
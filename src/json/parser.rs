@@ -0,0 +1,422 @@
+/*
+ * Recursive descent over the bytes of the input, strictly by RFC 8259:
+ * no trailing commas, no comments, no leading zeros or "+", no NaN, no single quotes
+ * Errors carry the line and column (in characters, both 1-based) where the problem starts
+ */
+
+use super::value::Value;
+use std::collections::BTreeMap;
+use std::error;
+use std::fmt;
+
+//nesting beyond this is refused instead of overflowing the stack on [[[[[[...
+pub const MAX_DEPTH: usize = 128;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorKind {
+    UnexpectedEnd,
+    UnexpectedChar(char),
+    Expected(&'static str),
+    InvalidNumber,
+    NumberOutOfRange,
+    InvalidEscape,
+    //a \u escape for half a surrogate pair without the other half
+    LoneSurrogate(u16),
+    ControlCharacter,
+    TrailingCharacters,
+    TooDeep,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+    pub kind: ErrorKind,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ErrorKind::UnexpectedEnd => write!(f, "unexpected end of input"),
+            ErrorKind::UnexpectedChar(c) => write!(f, "unexpected character {:?}", c),
+            ErrorKind::Expected(what) => write!(f, "expected {}", what),
+            ErrorKind::InvalidNumber => write!(f, "invalid number"),
+            ErrorKind::NumberOutOfRange => write!(f, "number out of range"),
+            ErrorKind::InvalidEscape => write!(f, "invalid escape sequence"),
+            ErrorKind::LoneSurrogate(unit) => write!(f, "lone surrogate \\u{:04x}", unit),
+            ErrorKind::ControlCharacter => write!(f, "control character in string"),
+            ErrorKind::TrailingCharacters => write!(f, "trailing characters after the value"),
+            ErrorKind::TooDeep => write!(f, "nested more than {} levels deep", MAX_DEPTH),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} at line {}, column {}",
+            self.kind, self.line, self.column
+        )
+    }
+}
+
+impl error::Error for Error {}
+
+/// Parses a whole document: one value, surrounded by nothing but whitespace.
+///
+/// ```
+/// use rust_by_example::json::{parse, Value};
+///
+/// let value = parse(r#"{"name": "Ferris", "legs": 10}"#).unwrap();
+/// assert_eq!(value["legs"], Value::Number(10.0));
+///
+/// let err = parse("[1,\n 2,]").unwrap_err();
+/// assert_eq!(err.to_string(), "unexpected character ']' at line 2, column 4");
+/// ```
+pub fn parse(source: &str) -> Result<Value, Error> {
+    let mut parser = Parser {
+        source,
+        bytes: source.as_bytes(),
+        pos: 0,
+        depth: 0,
+    };
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    if parser.pos < parser.bytes.len() {
+        return Err(parser.error(ErrorKind::TrailingCharacters));
+    }
+    Ok(value)
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error_at(&self, kind: ErrorKind, pos: usize) -> Error {
+        //counted only when an error happens: parsing itself tracks nothing but a byte offset
+        let before = &self.source[..pos];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let column = before[line_start..].chars().count() + 1;
+        Error { kind, line, column }
+    }
+
+    fn error(&self, kind: ErrorKind) -> Error {
+        self.error_at(kind, self.pos)
+    }
+
+    //the character at pos, or the end of input
+    fn unexpected(&self) -> Error {
+        match self.source[self.pos..].chars().next() {
+            Some(c) => self.error(ErrorKind::UnexpectedChar(c)),
+            None => self.error(ErrorKind::UnexpectedEnd),
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8, what: &'static str) -> Result<(), Error> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b) if b == byte => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(_) => Err(self.error(ErrorKind::Expected(what))),
+            None => Err(self.error(ErrorKind::UnexpectedEnd)),
+        }
+    }
+
+    fn literal(&mut self, word: &str, value: Value) -> Result<Value, Error> {
+        if self.bytes[self.pos..].starts_with(word.as_bytes()) {
+            self.pos += word.len();
+            Ok(value)
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, Error> {
+        self.skip_whitespace();
+        match self.peek() {
+            None => Err(self.error(ErrorKind::UnexpectedEnd)),
+            Some(b'n') => self.literal("null", Value::Null),
+            Some(b't') => self.literal("true", Value::Bool(true)),
+            Some(b'f') => self.literal("false", Value::Bool(false)),
+            Some(b'"') => self.string().map(Value::String),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(b'[') => self.nested(Parser::array),
+            Some(b'{') => self.nested(Parser::object),
+            Some(_) => Err(self.unexpected()),
+        }
+    }
+
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<Value, Error>) -> Result<Value, Error> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error(ErrorKind::TooDeep));
+        }
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    //after `[` or `{`: true if the closing byte follows right away
+    fn empty(&mut self, close: u8) -> bool {
+        self.pos += 1;
+        self.skip_whitespace();
+        let empty = self.peek() == Some(close);
+        if empty {
+            self.pos += 1;
+        }
+        empty
+    }
+
+    //after an item: true for `,`, false for the closing byte
+    fn more(&mut self, close: u8) -> Result<bool, Error> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b',') => {
+                self.pos += 1;
+                Ok(true)
+            }
+            Some(b) if b == close => {
+                self.pos += 1;
+                Ok(false)
+            }
+            Some(_) => Err(self.error(ErrorKind::Expected(if close == b']' {
+                "`,` or `]`"
+            } else {
+                "`,` or `}`"
+            }))),
+            None => Err(self.error(ErrorKind::UnexpectedEnd)),
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, Error> {
+        let mut items = Vec::new();
+        if !self.empty(b']') {
+            loop {
+                items.push(self.value()?);
+                if !self.more(b']')? {
+                    break;
+                }
+            }
+        }
+        Ok(Value::Array(items))
+    }
+
+    fn object(&mut self) -> Result<Value, Error> {
+        let mut members = BTreeMap::new();
+        if !self.empty(b'}') {
+            loop {
+                self.skip_whitespace();
+                if self.peek() != Some(b'"') {
+                    return Err(self.unexpected());
+                }
+                let key = self.string()?;
+                self.expect(b':', "`:`")?;
+                //a repeated key: the last one wins, as in most parsers
+                members.insert(key, self.value()?);
+                if !self.more(b'}')? {
+                    break;
+                }
+            }
+        }
+        Ok(Value::Object(members))
+    }
+
+    fn digits(&mut self) -> usize {
+        let start = self.pos;
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.pos += 1;
+        }
+        self.pos - start
+    }
+
+    // -?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][+-]?[0-9]+)?
+    fn number(&mut self) -> Result<Value, Error> {
+        let start = self.pos;
+        let invalid = |parser: &Self| Err(parser.error_at(ErrorKind::InvalidNumber, start));
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+        }
+        match self.peek() {
+            Some(b'0') => {
+                self.pos += 1;
+                if let Some(b'0'..=b'9') = self.peek() {
+                    return invalid(self);
+                }
+            }
+            Some(b'1'..=b'9') => {
+                self.digits();
+            }
+            _ => return invalid(self),
+        }
+        if self.peek() == Some(b'.') {
+            self.pos += 1;
+            if self.digits() == 0 {
+                return invalid(self);
+            }
+        }
+        if let Some(b'e' | b'E') = self.peek() {
+            self.pos += 1;
+            if let Some(b'+' | b'-') = self.peek() {
+                self.pos += 1;
+            }
+            if self.digits() == 0 {
+                return invalid(self);
+            }
+        }
+        //the grammar above is a subset of what f64's FromStr accepts
+        let n: f64 = self.source[start..self.pos].parse().unwrap();
+        if n.is_infinite() {
+            return Err(self.error_at(ErrorKind::NumberOutOfRange, start));
+        }
+        Ok(Value::Number(n))
+    }
+
+    fn hex4(&mut self) -> Result<u16, Error> {
+        let digits = self
+            .source
+            .get(self.pos..self.pos + 4)
+            .filter(|digits| digits.bytes().all(|b| b.is_ascii_hexdigit()));
+        match digits {
+            Some(digits) => {
+                self.pos += 4;
+                Ok(u16::from_str_radix(digits, 16).unwrap())
+            }
+            None => Err(self.error(ErrorKind::InvalidEscape)),
+        }
+    }
+
+    //at the `u` of \uXXXX; a high surrogate needs a \uXXXX low surrogate right after it
+    fn unicode_escape(&mut self, escape_start: usize) -> Result<char, Error> {
+        self.pos += 1;
+        let unit = self.hex4()?;
+        let lone = |parser: &Self| parser.error_at(ErrorKind::LoneSurrogate(unit), escape_start);
+        match unit {
+            0xD800..=0xDBFF => {
+                if !self.bytes[self.pos..].starts_with(b"\\u") {
+                    return Err(lone(self));
+                }
+                let after_high = self.pos;
+                self.pos += 2;
+                let low = self.hex4()?;
+                if !(0xDC00..=0xDFFF).contains(&low) {
+                    self.pos = after_high;
+                    return Err(lone(self));
+                }
+                let code = 0x10000 + ((unit as u32 - 0xD800) << 10) + (low as u32 - 0xDC00);
+                Ok(char::from_u32(code).unwrap())
+            }
+            0xDC00..=0xDFFF => Err(lone(self)),
+            unit => Ok(char::from_u32(unit as u32).unwrap()),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        //opening quote
+        self.pos += 1;
+        let mut s = String::new();
+        loop {
+            let run_start = self.pos;
+            //plain bytes are copied in runs: the input is a &str, so they are valid UTF-8
+            while let Some(b) = self.peek() {
+                if b == b'"' || b == b'\\' || b < 0x20 {
+                    break;
+                }
+                self.pos += 1;
+            }
+            s.push_str(&self.source[run_start..self.pos]);
+
+            match self.peek() {
+                None => return Err(self.error(ErrorKind::UnexpectedEnd)),
+                Some(b'"') => {
+                    self.pos += 1;
+                    return Ok(s);
+                }
+                Some(b'\\') => {
+                    let escape_start = self.pos;
+                    self.pos += 1;
+                    let c = match self.peek() {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => {
+                            s.push(self.unicode_escape(escape_start)?);
+                            continue;
+                        }
+                        None => return Err(self.error(ErrorKind::UnexpectedEnd)),
+                        Some(_) => {
+                            return Err(self.error_at(ErrorKind::InvalidEscape, escape_start))
+                        }
+                    };
+                    self.pos += 1;
+                    s.push(c);
+                }
+                //below 0x20: must be escaped
+                Some(_) => return Err(self.error(ErrorKind::ControlCharacter)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(source: &str) -> (ErrorKind, usize, usize) {
+        let err = parse(source).unwrap_err();
+        (err.kind, err.line, err.column)
+    }
+
+    #[test]
+    fn scalars() {
+        assert_eq!(parse(" null ").unwrap(), Value::Null);
+        assert_eq!(parse("true").unwrap(), Value::Bool(true));
+        assert_eq!(parse("-12.5e1").unwrap(), Value::Number(-125.0));
+        assert_eq!(parse(r#""a\tb""#).unwrap(), Value::String("a\tb".into()));
+    }
+
+    #[test]
+    fn positions_count_characters_and_lines() {
+        assert_eq!(error("[1, 2"), (ErrorKind::UnexpectedEnd, 1, 6));
+        assert_eq!(
+            error("{\n  \"ключ\": tru\n}"),
+            (ErrorKind::UnexpectedChar('t'), 2, 11)
+        );
+        assert_eq!(
+            error("[\"été\" 1]"),
+            (ErrorKind::Expected("`,` or `]`"), 1, 8)
+        );
+    }
+
+    #[test]
+    fn depth_is_limited() {
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        assert!(parse(&nested(MAX_DEPTH)).is_ok());
+        assert_eq!(
+            error(&nested(MAX_DEPTH + 1)),
+            (ErrorKind::TooDeep, 1, MAX_DEPTH + 1)
+        );
+    }
+}
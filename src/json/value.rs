@@ -0,0 +1,263 @@
+/*
+ * The document tree: one enum variant per JSON type, like custom_types::enums' WebEvent
+ * Display writes JSON text: {} compact, {:#} pretty-printed with two-space indentation
+ */
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
+use std::ops::Index;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    //JSON doesn't tell integers from floats: one f64 for both, like JavaScript
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    //sorted keys: the same document always prints the same way
+    Object(BTreeMap<String, Value>),
+}
+
+static NULL: Value = Value::Null;
+
+impl Value {
+    /// Name of the JSON type, for error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Null => "null",
+            Value::Bool(_) => "boolean",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Array(_) => "array",
+            Value::Object(_) => "object",
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        *self == Value::Null
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<Value>> {
+        match self {
+            Value::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&BTreeMap<String, Value>> {
+        match self {
+            Value::Object(members) => Some(members),
+            _ => None,
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.as_object().and_then(|members| members.get(key))
+    }
+}
+
+/// `value["key"]`: `Null` for a missing key or a value that isn't an object.
+impl Index<&str> for Value {
+    type Output = Value;
+
+    fn index(&self, key: &str) -> &Value {
+        self.get(key).unwrap_or(&NULL)
+    }
+}
+
+/// `value[0]`: `Null` past the end or for a value that isn't an array.
+impl Index<usize> for Value {
+    type Output = Value;
+
+    fn index(&self, index: usize) -> &Value {
+        self.as_array()
+            .and_then(|items| items.get(index))
+            .unwrap_or(&NULL)
+    }
+}
+
+fn write_string<W: Write>(out: &mut W, s: &str) -> fmt::Result {
+    out.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => out.write_str("\\\"")?,
+            '\\' => out.write_str("\\\\")?,
+            '\n' => out.write_str("\\n")?,
+            '\r' => out.write_str("\\r")?,
+            '\t' => out.write_str("\\t")?,
+            '\u{8}' => out.write_str("\\b")?,
+            '\u{c}' => out.write_str("\\f")?,
+            //other control characters have no short escape
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32)?,
+            c => out.write_char(c)?,
+        }
+    }
+    out.write_char('"')
+}
+
+fn write_number<W: Write>(out: &mut W, n: f64) -> fmt::Result {
+    if !n.is_finite() {
+        //NaN and infinity aren't JSON: JavaScript's JSON.stringify writes null too
+        out.write_str("null")
+    } else if n != 0.0 && (n.abs() >= 1e21 || n.abs() < 1e-6) {
+        //f64's Display never switches to exponent notation: 1e300 would be 301 digits
+        write!(out, "{:e}", n)
+    } else {
+        //Display for f64 writes 1.0 as "1": integers come out as integers
+        write!(out, "{}", n)
+    }
+}
+
+//indent: None for compact output, Some(level) when pretty-printing
+fn write_value(f: &mut fmt::Formatter, value: &Value, indent: Option<usize>) -> fmt::Result {
+    let newline = |f: &mut fmt::Formatter, level: usize| match indent {
+        Some(_) => write!(f, "\n{:width$}", "", width = 2 * level),
+        None => Ok(()),
+    };
+    let level = indent.unwrap_or(0);
+    let inner = indent.map(|level| level + 1);
+
+    match value {
+        Value::Null => f.write_str("null"),
+        Value::Bool(b) => write!(f, "{}", b),
+        Value::Number(n) => write_number(f, *n),
+        Value::String(s) => write_string(f, s),
+        Value::Array(items) if items.is_empty() => f.write_str("[]"),
+        Value::Array(items) => {
+            f.write_str("[")?;
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(",")?;
+                }
+                newline(f, level + 1)?;
+                write_value(f, item, inner)?;
+            }
+            newline(f, level)?;
+            f.write_str("]")
+        }
+        Value::Object(members) if members.is_empty() => f.write_str("{}"),
+        Value::Object(members) => {
+            f.write_str("{")?;
+            for (i, (key, member)) in members.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(",")?;
+                }
+                newline(f, level + 1)?;
+                write_string(f, key)?;
+                f.write_str(if indent.is_some() { ": " } else { ":" })?;
+                write_value(f, member, inner)?;
+            }
+            newline(f, level)?;
+            f.write_str("}")
+        }
+    }
+}
+
+/// `{}` compact, `{:#}` pretty-printed.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let indent = if f.alternate() { Some(0) } else { None };
+        write_value(f, self, indent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(members: Vec<(&str, Value)>) -> Value {
+        Value::Object(
+            members
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn compact_and_pretty() {
+        let value = object(vec![
+            ("name", Value::String("Ferris".into())),
+            (
+                "tags",
+                Value::Array(vec![Value::Number(1.0), Value::Bool(true), Value::Null]),
+            ),
+            ("empty", Value::Array(vec![])),
+            ("nested", object(vec![("x", Value::Number(-0.5))])),
+        ]);
+        assert_eq!(
+            value.to_string(),
+            r#"{"empty":[],"name":"Ferris","nested":{"x":-0.5},"tags":[1,true,null]}"#
+        );
+        assert_eq!(
+            format!("{:#}", value),
+            r#"{
+  "empty": [],
+  "name": "Ferris",
+  "nested": {
+    "x": -0.5
+  },
+  "tags": [
+    1,
+    true,
+    null
+  ]
+}"#
+        );
+    }
+
+    #[test]
+    fn numbers() {
+        let text = |n: f64| Value::Number(n).to_string();
+        assert_eq!(text(42.0), "42");
+        assert_eq!(text(0.1), "0.1");
+        assert_eq!(text(-0.0), "-0");
+        assert_eq!(text(1e21), "1e21");
+        assert_eq!(text(1.5e-7), "1.5e-7");
+        assert_eq!(text(123456789012.0), "123456789012");
+        assert_eq!(text(f64::NAN), "null");
+    }
+
+    #[test]
+    fn strings_are_escaped() {
+        let s = Value::String("quote \" backslash \\ newline \n bell \u{7} crab 🦀".into());
+        assert_eq!(
+            s.to_string(),
+            r#""quote \" backslash \\ newline \n bell \u0007 crab 🦀""#
+        );
+    }
+
+    #[test]
+    fn indexing_never_panics() {
+        let value = object(vec![(
+            "list",
+            Value::Array(vec![Value::String("a".into())]),
+        )]);
+        assert_eq!(value["list"][0].as_str(), Some("a"));
+        assert!(value["list"][1].is_null());
+        assert!(value["missing"]["deeper"].is_null());
+        assert!(Value::Number(1.0)[0].is_null());
+    }
+}
@@ -0,0 +1,74 @@
+/*
+ * Project: log-structured key-value store (the Bitcask design), std only
+ * - record: the on-disk format, length-prefixed and CRC-32 checksummed records
+ * - store: append-only log plus an in-memory HashMap index from key to record position,
+ *   rebuilt by scanning the log on open; compaction rewrites the live records only
+ * - error: one Error for the whole store (error_hierarchies), Io with a source
+ * CLI: cargo run --bin kvs -- [--file PATH] get KEY | set KEY VALUE | rm KEY | list | compact
+ * Pieces from earlier chapters: std_io (File, BufWriter, Seek), collections (HashMap),
+ * error_handling (?, From for conversions)
+ *
+ * Crash recovery: writes only append, so a crash can leave at most one torn record at the end;
+ * open cuts it off. A bad record anywhere else is reported as Error::Corrupt instead:
+ * the records after it are intact, cutting them off would lose them
+ */
+
+mod error;
+mod record;
+mod store;
+
+pub use error::{Error, Result};
+pub use record::{crc32, Record, MAX_PAYLOAD};
+pub use store::{KvStore, Recovery, Stats};
+
+use std::fs;
+
+pub fn main() {
+    let path = std::env::temp_dir().join(format!("rbe-kv-store-{}.log", std::process::id()));
+    let _ = fs::remove_file(&path);
+
+    let mut store = KvStore::open(&path).unwrap();
+    store.set("language", "rust").unwrap();
+    store.set("mascot", "ferris").unwrap();
+    store.set("language", "Rust").unwrap();
+    assert_eq!(store.get("language").unwrap().as_deref(), Some("Rust"));
+    assert_eq!(store.get("missing").unwrap(), None);
+    match store.remove("missing") {
+        Err(Error::KeyNotFound(key)) => assert_eq!(key, "missing"),
+        other => panic!("{:?}", other.map(|_| ())),
+    }
+
+    //every set is a new record: the first "language" is dead weight now
+    let stats = store.stats();
+    assert_eq!(stats.keys, 2);
+    assert!(stats.stale_bytes > 0);
+    store.compact().unwrap();
+    assert_eq!(store.stats().stale_bytes, 0);
+    drop(store);
+
+    //reopening rebuilds the index from the log
+    let mut store = KvStore::open(&path).unwrap();
+    assert_eq!(store.keys(), ["language", "mascot"]);
+
+    //a crash in the middle of a write: simulated by cutting the last record short
+    store.set("unlucky", "half written").unwrap();
+    drop(store);
+    let len = fs::metadata(&path).unwrap().len();
+    fs::OpenOptions::new()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_len(len - 5)
+        .unwrap();
+    let store = KvStore::open(&path).unwrap();
+    let recovery = store.recovery().unwrap();
+    println!(
+        "recovered: kept {} bytes, dropped {} ({})",
+        recovery.valid_len, recovery.dropped_bytes, recovery.reason
+    );
+    assert_eq!(store.get("unlucky").unwrap(), None);
+    assert_eq!(store.get("mascot").unwrap().as_deref(), Some("ferris"));
+
+    drop(store);
+    fs::remove_file(&path).unwrap();
+}
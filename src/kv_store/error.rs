@@ -0,0 +1,48 @@
+use super::record::MAX_PAYLOAD;
+use std::error;
+use std::fmt;
+use std::io;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    //a record that can't be trusted: wrong checksum, cut short, impossible length
+    Corrupt { offset: u64, reason: &'static str },
+    //refused before writing: read back, it would look like a corrupt record
+    TooLarge { payload_len: usize },
+    KeyNotFound(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(_) => write!(f, "log file error"),
+            Error::Corrupt { offset, reason } => {
+                write!(f, "corrupt record at offset {}: {}", offset, reason)
+            }
+            Error::TooLarge { payload_len } => write!(
+                f,
+                "record of {} bytes is over the limit of {} bytes",
+                payload_len, MAX_PAYLOAD
+            ),
+            Error::KeyNotFound(key) => write!(f, "key not found: {}", key),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
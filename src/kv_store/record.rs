@@ -0,0 +1,260 @@
+/*
+ * On-disk record format, all integers little-endian
+ *
+ *   +------------+-----------+------------------------------------------+
+ *   | length u32 | crc32 u32 | payload (length bytes)                   |
+ *   +------------+-----------+------------------------------------------+
+ *   payload: kind u8 (0 = set, 1 = remove) | key length u32 | key | value (rest)
+ *
+ * The length tells where the next record starts, the checksum catches torn or
+ * flipped bytes: a crash in the middle of a write leaves a record that fails one of both
+ */
+
+use super::error::{Error, Result};
+use std::io::{self, Read, Seek, SeekFrom};
+
+pub const HEADER_LEN: u64 = 8;
+//a length beyond this is garbage, not a record: don't try to allocate it
+pub const MAX_PAYLOAD: u32 = 16 * 1024 * 1024;
+
+const SET: u8 = 0;
+const REMOVE: u8 = 1;
+
+//CRC-32 (IEEE 802.3, as in zip and png), table computed at compile time
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// ```
+/// use rust_by_example::kv_store::crc32;
+///
+/// //the standard check value
+/// assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+/// ```
+pub fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, &byte| {
+        CRC_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Record {
+    Set { key: String, value: String },
+    Remove { key: String },
+}
+
+impl Record {
+    pub fn key(&self) -> &str {
+        match self {
+            Record::Set { key, .. } | Record::Remove { key } => key,
+        }
+    }
+
+    fn parts(&self) -> (u8, &str, &str) {
+        match self {
+            Record::Set { key, value } => (SET, key, value),
+            Record::Remove { key } => (REMOVE, key, ""),
+        }
+    }
+
+    /// Length of the payload `encode` writes: kind, key length, key and value.
+    pub fn payload_len(&self) -> usize {
+        let (_, key, value) = self.parts();
+        5 + key.len() + value.len()
+    }
+
+    /// Header and payload, ready to append. Fails for a payload over `MAX_PAYLOAD`,
+    /// which `read_record` would take for garbage.
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        let payload_len = self.payload_len();
+        if payload_len > MAX_PAYLOAD as usize {
+            return Err(Error::TooLarge { payload_len });
+        }
+        let (kind, key, value) = self.parts();
+        let mut payload = Vec::with_capacity(payload_len);
+        payload.push(kind);
+        payload.extend_from_slice(&(key.len() as u32).to_le_bytes());
+        payload.extend_from_slice(key.as_bytes());
+        payload.extend_from_slice(value.as_bytes());
+
+        let mut bytes = Vec::with_capacity(HEADER_LEN as usize + payload.len());
+        bytes.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&crc32(&payload).to_le_bytes());
+        bytes.extend_from_slice(&payload);
+        Ok(bytes)
+    }
+
+    fn decode(payload: &[u8], offset: u64) -> Result<Record> {
+        let corrupt = |reason| Error::Corrupt { offset, reason };
+        if payload.len() < 5 {
+            return Err(corrupt("payload too short"));
+        }
+        let key_len = u32::from_le_bytes([payload[1], payload[2], payload[3], payload[4]]) as usize;
+        let rest = &payload[5..];
+        if key_len > rest.len() {
+            return Err(corrupt("key length past the end of the record"));
+        }
+        let (key, value) = rest.split_at(key_len);
+        let text =
+            |bytes: &[u8]| String::from_utf8(bytes.to_vec()).map_err(|_| corrupt("invalid UTF-8"));
+        match payload[0] {
+            SET => Ok(Record::Set {
+                key: text(key)?,
+                value: text(value)?,
+            }),
+            REMOVE if value.is_empty() => Ok(Record::Remove { key: text(key)? }),
+            _ => Err(corrupt("unknown record kind")),
+        }
+    }
+}
+
+//like read_exact, but reports how much was read instead of failing at end of input
+fn read_full<R: Read>(reader: &mut R, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(filled)
+}
+
+/// Reads the record starting at `offset`: `Ok(None)` at a clean end of the log,
+/// `Error::Corrupt` for a partial or damaged record. Returns the record and its total length.
+pub fn read_record<R: Read>(reader: &mut R, offset: u64) -> Result<Option<(Record, u64)>> {
+    let corrupt = |reason| Error::Corrupt { offset, reason };
+
+    let mut header = [0u8; HEADER_LEN as usize];
+    match read_full(reader, &mut header)? {
+        0 => return Ok(None),
+        n if n < header.len() => return Err(corrupt("truncated header")),
+        _ => {}
+    }
+    let length = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+    let checksum = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+    if length > MAX_PAYLOAD {
+        return Err(corrupt("length out of range"));
+    }
+
+    let mut payload = vec![0u8; length as usize];
+    if read_full(reader, &mut payload)? < payload.len() {
+        return Err(corrupt("truncated payload"));
+    }
+    if crc32(&payload) != checksum {
+        return Err(corrupt("checksum mismatch"));
+    }
+    let record = Record::decode(&payload, offset)?;
+    Ok(Some((record, HEADER_LEN + length as u64)))
+}
+
+/// Whether the record at `offset`, which failed to read, runs into the end of a log of
+/// `log_len` bytes: cut short by it or ending right at it. A crash in the middle of an append
+/// leaves such a record, damage anywhere before the last record doesn't.
+pub fn reaches_end<R: Read + Seek>(mut reader: R, offset: u64, log_len: u64) -> Result<bool> {
+    if log_len - offset < HEADER_LEN {
+        return Ok(true);
+    }
+    let mut header = [0u8; HEADER_LEN as usize];
+    reader.seek(SeekFrom::Start(offset))?;
+    reader.read_exact(&mut header)?;
+    let length = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+    //appends never write an impossible length: that one is damage
+    Ok(length <= MAX_PAYLOAD && offset + HEADER_LEN + length as u64 >= log_len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(key: &str, value: &str) -> Record {
+        Record::Set {
+            key: key.into(),
+            value: value.into(),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let records = [
+            set("name", "ferris"),
+            set("", ""),
+            set("ключ", "значение"),
+            Record::Remove { key: "name".into() },
+        ];
+        let mut log = Vec::new();
+        for record in &records {
+            log.extend(record.encode().unwrap());
+        }
+
+        let mut reader = &log[..];
+        let mut offset = 0;
+        let mut decoded = Vec::new();
+        while let Some((record, length)) = read_record(&mut reader, offset).unwrap() {
+            offset += length;
+            decoded.push(record);
+        }
+        assert_eq!(decoded, records);
+        assert_eq!(offset, log.len() as u64);
+    }
+
+    #[test]
+    fn layout_is_stable() {
+        let bytes = set("k", "v").encode().unwrap();
+        //length 7 = kind + key length + "k" + "v"
+        assert_eq!(&bytes[..4], [7, 0, 0, 0]);
+        assert_eq!(&bytes[8..], [0, 1, 0, 0, 0, b'k', b'v']);
+        assert_eq!(bytes[4..8], crc32(&bytes[8..]).to_le_bytes());
+    }
+
+    #[test]
+    fn damage_is_detected() {
+        let bytes = set("key", "value").encode().unwrap();
+        let reason = |bytes: &[u8]| match read_record(&mut &bytes[..], 100) {
+            Err(Error::Corrupt {
+                offset: 100,
+                reason,
+            }) => reason,
+            other => panic!("expected corruption, got {:?}", other),
+        };
+        assert_eq!(reason(&bytes[..5]), "truncated header");
+        assert_eq!(reason(&bytes[..bytes.len() - 1]), "truncated payload");
+
+        let mut flipped = bytes.clone();
+        *flipped.last_mut().unwrap() ^= 0x20;
+        assert_eq!(reason(&flipped), "checksum mismatch");
+
+        let mut huge = bytes;
+        huge[3] = 0xFF;
+        assert_eq!(reason(&huge), "length out of range");
+    }
+
+    #[test]
+    fn oversized_records_are_refused() {
+        let limit = MAX_PAYLOAD as usize;
+        //kind and key length take 5 bytes, the key 1
+        let fits = set("k", &"x".repeat(limit - 6));
+        assert_eq!(fits.payload_len(), limit);
+        assert_eq!(fits.encode().unwrap().len(), HEADER_LEN as usize + limit);
+        let over = set("k", &"x".repeat(limit - 5)).encode();
+        assert!(matches!(over, Err(Error::TooLarge { payload_len }) if payload_len == limit + 1));
+    }
+}
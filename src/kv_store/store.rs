@@ -0,0 +1,357 @@
+use super::error::{Error, Result};
+use super::record::{self, Record};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+//compact once this many bytes are dead and they make up more than half of the log
+const COMPACTION_THRESHOLD: u64 = 1024 * 1024;
+
+//where the live value of a key sits in the log: the whole record, header included
+#[derive(Debug, Clone, Copy)]
+struct Position {
+    offset: u64,
+    len: u64,
+}
+
+/// What `open` had to cut off the end of the log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recovery {
+    pub valid_len: u64,
+    pub dropped_bytes: u64,
+    pub reason: &'static str,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub keys: usize,
+    pub log_bytes: u64,
+    //overwritten and removed records, reclaimed by compact()
+    pub stale_bytes: u64,
+}
+
+pub struct KvStore {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    reader: File,
+    index: HashMap<String, Position>,
+    end: u64,
+    stale: u64,
+    recovery: Option<Recovery>,
+}
+
+fn compact_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".compact");
+    PathBuf::from(name)
+}
+
+impl KvStore {
+    /// Opens the log at `path`, creating it if needed, and rebuilds the index from it.
+    ///
+    /// Writes only ever append, so a crash can only damage the last record: a record that
+    /// fails to read and runs into the end of the file is cut off. A bad record before that
+    /// is `Error::Corrupt`, the log is left as it is.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<KvStore> {
+        let path = path.as_ref().to_path_buf();
+        //left over from a compaction that crashed before its rename: the log is still intact
+        match fs::remove_file(compact_path(&path)) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err.into()),
+            _ => {}
+        }
+
+        let file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(&path)?;
+        let file_len = file.metadata()?.len();
+
+        let mut index = HashMap::new();
+        let mut stale = 0;
+        let mut offset = 0;
+        let mut recovery = None;
+        let mut reader = BufReader::new(&file);
+        loop {
+            match record::read_record(&mut reader, offset) {
+                Ok(Some((record, len))) => {
+                    let old = match record {
+                        Record::Set { key, .. } => index.insert(key, Position { offset, len }),
+                        Record::Remove { key } => {
+                            //the tombstone itself is dead weight as well
+                            stale += len;
+                            index.remove(&key)
+                        }
+                    };
+                    stale += old.map_or(0, |position| position.len);
+                    offset += len;
+                }
+                Ok(None) => break,
+                Err(Error::Corrupt { offset, reason })
+                    if !record::reaches_end(&file, offset, file_len)? =>
+                {
+                    return Err(Error::Corrupt { offset, reason });
+                }
+                Err(Error::Corrupt { reason, .. }) => {
+                    recovery = Some(Recovery {
+                        valid_len: offset,
+                        dropped_bytes: file_len - offset,
+                        reason,
+                    });
+                    break;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        if recovery.is_some() {
+            file.set_len(offset)?;
+            file.sync_all()?;
+        }
+
+        Ok(KvStore {
+            reader: File::open(&path)?,
+            writer: BufWriter::new(file),
+            path,
+            index,
+            end: offset,
+            stale,
+            recovery,
+        })
+    }
+
+    pub fn recovery(&self) -> Option<&Recovery> {
+        self.recovery.as_ref()
+    }
+
+    pub fn stats(&self) -> Stats {
+        Stats {
+            keys: self.index.len(),
+            log_bytes: self.end,
+            stale_bytes: self.stale,
+        }
+    }
+
+    /// Keys in sorted order.
+    pub fn keys(&self) -> Vec<&str> {
+        let mut keys: Vec<&str> = self.index.keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    pub fn get(&self, key: &str) -> Result<Option<String>> {
+        let position = match self.index.get(key) {
+            Some(position) => *position,
+            None => return Ok(None),
+        };
+        match self.read_at(position)? {
+            Record::Set { value, .. } => Ok(Some(value)),
+            Record::Remove { .. } => Err(Error::Corrupt {
+                offset: position.offset,
+                reason: "index points at a removal",
+            }),
+        }
+    }
+
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        let record = Record::Set {
+            key: key.to_string(),
+            value: value.to_string(),
+        };
+        let position = self.append(&record)?;
+        if let Some(old) = self.index.insert(key.to_string(), position) {
+            self.stale += old.len;
+        }
+        self.maybe_compact()
+    }
+
+    /// Fails with `Error::KeyNotFound` if there is nothing to remove.
+    pub fn remove(&mut self, key: &str) -> Result<()> {
+        if !self.index.contains_key(key) {
+            return Err(Error::KeyNotFound(key.to_string()));
+        }
+        let position = self.append(&Record::Remove {
+            key: key.to_string(),
+        })?;
+        let old = self.index.remove(key).unwrap();
+        self.stale += old.len + position.len;
+        self.maybe_compact()
+    }
+
+    /// Rewrites the log with only the live records, then swaps it in with a rename:
+    /// a crash before the rename leaves the old log untouched, after it the new one is complete.
+    pub fn compact(&mut self) -> Result<()> {
+        let temp = compact_path(&self.path);
+        let mut keys: Vec<String> = self.index.keys().cloned().collect();
+        keys.sort_unstable();
+
+        let mut index = HashMap::with_capacity(keys.len());
+        let mut offset = 0;
+        {
+            let mut out = BufWriter::new(File::create(&temp)?);
+            for key in keys {
+                let position = self.index[&key];
+                //copied as is, checksum included
+                let bytes = self.read_raw(position)?;
+                out.write_all(&bytes)?;
+                index.insert(
+                    key,
+                    Position {
+                        offset,
+                        len: position.len,
+                    },
+                );
+                offset += position.len;
+            }
+            out.flush()?;
+            out.get_ref().sync_all()?;
+        }
+        fs::rename(&temp, &self.path)?;
+
+        //both handles still point at the old, now unlinked file
+        self.writer = BufWriter::new(OpenOptions::new().append(true).open(&self.path)?);
+        self.reader = File::open(&self.path)?;
+        self.index = index;
+        self.end = offset;
+        self.stale = 0;
+        Ok(())
+    }
+
+    /// Waits until the log is on disk, not only handed to the OS.
+    pub fn sync(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        self.writer.get_ref().sync_data()?;
+        Ok(())
+    }
+
+    fn append(&mut self, record: &Record) -> Result<Position> {
+        let bytes = record.encode()?;
+        self.writer.write_all(&bytes)?;
+        //flushed per record: get() reads through another handle
+        self.writer.flush()?;
+        let position = Position {
+            offset: self.end,
+            len: bytes.len() as u64,
+        };
+        self.end += position.len;
+        Ok(position)
+    }
+
+    fn maybe_compact(&mut self) -> Result<()> {
+        if self.stale > COMPACTION_THRESHOLD && self.stale * 2 > self.end {
+            self.compact()?;
+        }
+        Ok(())
+    }
+
+    fn read_raw(&self, position: Position) -> Result<Vec<u8>> {
+        //Read and Seek are implemented for &File too: no &mut self needed for a lookup
+        let mut reader = &self.reader;
+        reader.seek(SeekFrom::Start(position.offset))?;
+        let mut bytes = vec![0; position.len as usize];
+        reader.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    //checksum verified again: the file may have changed under us
+    fn read_at(&self, position: Position) -> Result<Record> {
+        let bytes = self.read_raw(position)?;
+        match record::read_record(&mut &bytes[..], position.offset)? {
+            Some((record, _)) => Ok(record),
+            None => Err(Error::Corrupt {
+                offset: position.offset,
+                reason: "empty record",
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TempLog(PathBuf);
+
+    impl TempLog {
+        fn new(name: &str) -> TempLog {
+            let path = std::env::temp_dir().join(format!(
+                "rbe-kv-store-unit-{}-{}.log",
+                name,
+                std::process::id()
+            ));
+            let _ = fs::remove_file(&path);
+            TempLog(path)
+        }
+    }
+
+    impl Drop for TempLog {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn set_get_remove() {
+        let log = TempLog::new("basic");
+        let mut store = KvStore::open(&log.0).unwrap();
+        assert_eq!(store.get("a").unwrap(), None);
+        store.set("a", "1").unwrap();
+        store.set("b", "2").unwrap();
+        store.set("a", "3").unwrap();
+        assert_eq!(store.get("a").unwrap().as_deref(), Some("3"));
+        assert_eq!(store.keys(), ["a", "b"]);
+
+        store.remove("b").unwrap();
+        assert_eq!(store.get("b").unwrap(), None);
+        assert!(matches!(store.remove("b"), Err(Error::KeyNotFound(key)) if key == "b"));
+        assert_eq!(store.recovery(), None);
+    }
+
+    #[test]
+    fn stale_bytes_are_counted_and_compacted_away() {
+        let log = TempLog::new("stale");
+        let mut store = KvStore::open(&log.0).unwrap();
+        let one = Record::Set {
+            key: "k".into(),
+            value: "v".into(),
+        }
+        .encode()
+        .unwrap()
+        .len() as u64;
+
+        for _ in 0..10 {
+            store.set("k", "v").unwrap();
+        }
+        let before = store.stats();
+        assert_eq!(before.log_bytes, 10 * one);
+        assert_eq!(before.stale_bytes, 9 * one);
+
+        store.compact().unwrap();
+        let after = store.stats();
+        assert_eq!(
+            (after.keys, after.log_bytes, after.stale_bytes),
+            (1, one, 0)
+        );
+        assert_eq!(fs::metadata(&log.0).unwrap().len(), one);
+        assert_eq!(store.get("k").unwrap().as_deref(), Some("v"));
+        //and appending goes on in the new file
+        store.set("k2", "v2").unwrap();
+        drop(store);
+        let store = KvStore::open(&log.0).unwrap();
+        assert_eq!(store.keys(), ["k", "k2"]);
+        assert_eq!(store.stats().stale_bytes, 0);
+    }
+
+    #[test]
+    fn compacts_by_itself() {
+        let log = TempLog::new("auto");
+        let mut store = KvStore::open(&log.0).unwrap();
+        let value = "x".repeat(64 * 1024);
+        for _ in 0..40 {
+            store.set("big", &value).unwrap();
+        }
+        //without compaction the log would be over 2.5 MiB
+        assert!(store.stats().log_bytes < 2 * COMPACTION_THRESHOLD);
+        assert_eq!(store.get("big").unwrap(), Some(value));
+    }
+}
@@ -0,0 +1,16 @@
+/*
+ * Library crate of the package: chapters whose public items are exercised from outside,
+ * by doc tests and by integration tests in ./tests
+ * The binary crates (main.rs and src/bin) use it as an external crate: rust_by_example::...
+ */
+
+pub mod capstone_chat;
+pub mod capstone_http;
+pub mod capstone_interpreter;
+pub mod const_generics;
+pub mod json;
+pub mod kv_store;
+pub mod macro_rules_advanced;
+pub mod minigrep;
+pub mod testing;
+pub mod variance;
@@ -0,0 +1,406 @@
+/*
+ * Continuation of scoping_rules::lifetimes
+ * - elision: rules that fill in lifetimes left out of fn signatures
+ * - higher-ranked trait bounds: for<'a> F: Fn(&'a T), "for every lifetime 'a"
+ * - variance: see variance.rs, in the library so its compile errors are doc-tested
+ * - self-referential structs can't be expressed with plain references
+ * - borrowed tokens: a parser returns slices of its input instead of allocating
+ */
+
+use crate::scoping_rules::{print_ref, print_ref2, Borrowed, Either, NamedBorrowed, Owner};
+
+fn annotated_items() {
+    let x = 7;
+    let y = 9;
+    print_ref(&x);
+    print_ref2(&"any Debug type");
+
+    let mut owner = Owner(18);
+    owner.add_one();
+    assert_eq!(owner.0, 19);
+
+    //references inside must outlive the struct: x and y are declared before it
+    let single = Borrowed(&x);
+    let double = NamedBorrowed { x: &x, y: &y };
+    assert_eq!(*single.0 + *double.x + *double.y, 23);
+
+    //default borrows a promoted constant: &10 is &'static i32
+    let default = Borrowed::default();
+    assert_eq!(*default.0, 10);
+
+    fn value(either: &Either) -> i32 {
+        match either {
+            Either::Num(n) => *n,
+            Either::Ref(r) => **r,
+        }
+    }
+    assert_eq!(value(&Either::Num(1)) + value(&Either::Ref(&y)), 10);
+
+    //Not allowed: the struct outlives the borrowed value
+    // let dangling;
+    // {
+    //     let short = 1;
+    //     dangling = Borrowed(&short);
+    // }
+    // print_ref(dangling.0);
+    //error[E0597]: `short` does not live long enough
+}
+
+/*
+ * Elision rules:
+ * 1. every elided lifetime in the inputs becomes a distinct lifetime parameter
+ * 2. if there is exactly one input lifetime, it is assigned to all elided output lifetimes
+ * 3. if there is &self or &mut self, its lifetime is assigned to all elided output lifetimes
+ * Anything else has to be written explicitly
+ */
+fn elision() {
+    //rule 1: fn print<'a, 'b>(s: &'a str, n: &'b i32) -> String
+    fn print(s: &str, n: &i32) -> String {
+        format!("{}{}", s, n)
+    }
+
+    //rule 2: fn first_word<'a>(s: &'a str) -> &'a str
+    fn first_word(s: &str) -> &str {
+        s.split(' ').next().unwrap_or("")
+    }
+
+    //rule 2 counts lifetimes, not references: Borrowed<'_> is an input lifetime too
+    //fn inner<'a>(borrowed: Borrowed<'a>) -> &'a i32
+    fn inner(borrowed: Borrowed<'_>) -> &i32 {
+        borrowed.0
+    }
+
+    struct Dictionary {
+        words: Vec<String>,
+    }
+
+    impl Dictionary {
+        //rule 3: fn longest<'s>(&'s self) -> &'s str
+        fn longest(&self) -> &str {
+            self.words
+                .iter()
+                .max_by_key(|word| word.len())
+                .map_or("", String::as_str)
+        }
+
+        //rule 3 picks self, even when the result comes from the argument:
+        //the signature has to say so
+        fn or_default<'d>(&self, index: usize, default: &'d str) -> &'d str {
+            if index < self.words.len() {
+                "known"
+            } else {
+                default
+            }
+        }
+    }
+
+    assert_eq!(print("n=", &1), "n=1");
+    assert_eq!(first_word("hello world"), "hello");
+    assert_eq!(*inner(Borrowed(&5)), 5);
+
+    let dictionary = Dictionary {
+        words: vec!["a".to_string(), "abc".to_string(), "ab".to_string()],
+    };
+    assert_eq!(dictionary.longest(), "abc");
+    //the result only borrows `default`, dictionary can go away before it
+    let answer = {
+        let temporary = Dictionary { words: Vec::new() };
+        temporary.or_default(0, "unknown")
+    };
+    assert_eq!(answer, "unknown");
+
+    //Not allowed: two input lifetimes, no self: output is ambiguous
+    // fn longest(x: &str, y: &str) -> &str { x }
+    //error[E0106]: missing lifetime specifier
+}
+
+/*
+ * Higher-ranked trait bounds: for<'a> makes the bound hold for all lifetimes,
+ * chosen by each call instead of once by the caller.
+ * Fn(&T) -> &T in a bound is already sugar for for<'a> Fn(&'a T) -> &'a T
+ */
+fn higher_ranked_bounds() {
+    //f is called with a reference to a local: no caller-chosen 'a can name it
+    fn apply_to_local<F>(f: F) -> String
+    where
+        F: for<'a> Fn(&'a str) -> &'a str,
+    {
+        let local = String::from("  padded  ");
+        f(&local).to_string()
+    }
+    assert_eq!(apply_to_local(str::trim), "padded");
+    assert_eq!(apply_to_local(|s| &s[..4]), "  pa");
+
+    //Not allowed: 'a is fixed by the caller, a local can't live that long
+    // fn apply_to_local<'a, F: Fn(&'a str) -> &'a str>(f: F) -> usize {
+    //     let local = String::from("x");
+    //     f(&local).len()
+    // }
+    //error[E0597]: `local` does not live long enough
+
+    //trait objects: predicates over borrowed data of any lifetime
+    type Predicate<T> = dyn for<'a> Fn(&'a T) -> bool;
+
+    struct Filter<T> {
+        predicates: Vec<Box<Predicate<T>>>,
+    }
+
+    impl<T> Filter<T> {
+        fn accepts(&self, value: &T) -> bool {
+            self.predicates.iter().all(|predicate| predicate(value))
+        }
+    }
+
+    let filter: Filter<String> = Filter {
+        predicates: vec![
+            Box::new(|s| !s.is_empty()),
+            Box::new(|s| s.chars().all(char::is_alphabetic)),
+        ],
+    };
+    let words: Vec<String> = vec!["cake".into(), "".into(), "l13".into()];
+    let accepted: Vec<&String> = words.iter().filter(|word| filter.accepts(word)).collect();
+    assert_eq!(accepted, ["cake"]);
+
+    //for<'a> on a trait bound of a reference type:
+    //"a shared reference to C, of any lifetime, can be iterated"
+    fn total<C>(collection: &C) -> i32
+    where
+        for<'a> &'a C: IntoIterator<Item = &'a i32>,
+    {
+        collection.into_iter().sum()
+    }
+    assert_eq!(total(&vec![1, 2, 3]), 6);
+    assert_eq!(total(&[4, 5]), 9);
+    let set: std::collections::BTreeSet<i32> = (1..=4).collect();
+    assert_eq!(total(&set), 10);
+}
+
+/*
+ * Self-referential structs: a field borrowing from another field of the same struct
+ * - there is no lifetime to write for the borrow ('self doesn't exist)
+ * - moving the struct would move the owner and leave the reference dangling
+ * Alternatives: store indices, split owner and borrower into two values,
+ * or Pin + unsafe (what async fn state machines do)
+ */
+fn self_referential() {
+    //Not allowed: no name for the lifetime of `text`
+    // struct Document {
+    //     text: String,
+    //     first_word: &str,
+    // }
+    //error[E0106]: missing lifetime specifier
+
+    //Not allowed: moving the owner while it's borrowed
+    // let text = String::from("a b");
+    // let first = text.split(' ').next().unwrap();
+    // let moved = text;
+    // println!("{} {}", first, moved);
+    //error[E0505]: cannot move out of `text` because it is borrowed
+
+    //1. indices into the owned data: the struct can move freely
+    struct Document {
+        text: String,
+        words: Vec<(usize, usize)>,
+    }
+
+    impl Document {
+        fn new(text: String) -> Document {
+            let mut words = Vec::new();
+            let mut start = None;
+            for (i, c) in text.char_indices().chain(Some((text.len(), ' '))) {
+                match (c.is_whitespace(), start) {
+                    (false, None) => start = Some(i),
+                    (true, Some(s)) => {
+                        words.push((s, i));
+                        start = None;
+                    }
+                    _ => {}
+                }
+            }
+            Document { text, words }
+        }
+
+        fn word(&self, index: usize) -> Option<&str> {
+            self.words
+                .get(index)
+                .map(|&(start, end)| &self.text[start..end])
+        }
+    }
+
+    let document = Document::new(String::from("borrow the  text"));
+    let moved = document;
+    assert_eq!(moved.word(2), Some("text"));
+    assert_eq!(moved.word(3), None);
+
+    //2. owner and borrower as separate values: the borrow checker sees both
+    struct Words<'a> {
+        words: Vec<&'a str>,
+    }
+
+    let text = String::from("split owner and view");
+    let view = Words {
+        words: text.split_whitespace().collect(),
+    };
+    assert_eq!(view.words.len(), 4);
+}
+
+/*
+ * Streaming parser: tokens are &'a str slices of the input, no allocation.
+ * Token<'a> borrows the input, not the tokenizer: tokens outlive the tokenizer
+ */
+mod tokenizer {
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    pub enum Token<'a> {
+        Ident(&'a str),
+        Number(&'a str),
+        //without the quotes
+        Quoted(&'a str),
+        Symbol(char),
+    }
+
+    pub struct Tokenizer<'a> {
+        input: &'a str,
+        position: usize,
+    }
+
+    impl<'a> Tokenizer<'a> {
+        pub fn new(input: &'a str) -> Tokenizer<'a> {
+            Tokenizer { input, position: 0 }
+        }
+
+        fn rest(&self) -> &'a str {
+            &self.input[self.position..]
+        }
+
+        //takes the longest prefix of the rest matching `accept`, returns a slice of the input
+        fn take_while<F: Fn(char) -> bool>(&mut self, accept: F) -> &'a str {
+            let rest = self.rest();
+            let end = rest.find(|c| !accept(c)).unwrap_or(rest.len());
+            self.position += end;
+            &rest[..end]
+        }
+    }
+
+    impl<'a> Iterator for Tokenizer<'a> {
+        //Item can borrow the input because it doesn't borrow the iterator
+        type Item = Result<Token<'a>, usize>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            self.take_while(char::is_whitespace);
+            let first = self.rest().chars().next()?;
+            let token = if first.is_alphabetic() || first == '_' {
+                Token::Ident(self.take_while(|c| c.is_alphanumeric() || c == '_'))
+            } else if first.is_ascii_digit() {
+                Token::Number(self.take_while(|c| c.is_ascii_digit() || c == '.'))
+            } else if first == '"' {
+                let start = self.position;
+                self.position += 1;
+                let quoted = self.take_while(|c| c != '"');
+                if self.rest().is_empty() {
+                    //unterminated string: report where it started, stop
+                    self.position = self.input.len();
+                    return Some(Err(start));
+                }
+                self.position += 1;
+                Token::Quoted(quoted)
+            } else {
+                self.position += first.len_utf8();
+                Token::Symbol(first)
+            };
+            Some(Ok(token))
+        }
+    }
+}
+
+fn streaming_parser() {
+    use tokenizer::{Token, Tokenizer};
+
+    let input = String::from(r#"let name = "Ferris"; age = 7.5"#);
+
+    //tokens are collected while the tokenizer is a temporary
+    let tokens: Vec<Token> = Tokenizer::new(&input).map(Result::unwrap).collect();
+    assert_eq!(
+        tokens,
+        [
+            Token::Ident("let"),
+            Token::Ident("name"),
+            Token::Symbol('='),
+            Token::Quoted("Ferris"),
+            Token::Symbol(';'),
+            Token::Ident("age"),
+            Token::Symbol('='),
+            Token::Number("7.5"),
+        ]
+    );
+
+    //no copies: token slices point into the input buffer
+    if let Token::Quoted(name) = tokens[3] {
+        let offset = name.as_ptr() as usize - input.as_ptr() as usize;
+        assert_eq!(&input[offset..offset + name.len()], "Ferris");
+    }
+
+    //key = value pairs on top of the tokens, still borrowing the input
+    fn assignments(input: &str) -> Vec<(&str, &str)> {
+        let tokens: Vec<Token> = Tokenizer::new(input).filter_map(Result::ok).collect();
+        tokens
+            .windows(3)
+            .filter_map(|window| match *window {
+                [Token::Ident(key), Token::Symbol('='), Token::Quoted(value)]
+                | [Token::Ident(key), Token::Symbol('='), Token::Number(value)] => {
+                    Some((key, value))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+    assert_eq!(assignments(&input), [("name", "Ferris"), ("age", "7.5")]);
+
+    let errors: Vec<_> = Tokenizer::new(r#"ok "unterminated"#).collect();
+    assert_eq!(errors, [Ok(Token::Ident("ok")), Err(3)]);
+
+    //Not allowed: tokens would outlive the input buffer
+    // let tokens: Vec<Token> = {
+    //     let buffer = String::from("short lived");
+    //     Tokenizer::new(&buffer).map(Result::unwrap).collect()
+    // };
+    //error[E0597]: `buffer` does not live long enough
+
+    //Contrast: a reader reusing its own buffer must return items borrowing itself
+    //(&mut self -> &str), so it can't implement Iterator: one line at a time
+    struct LineReader<'a> {
+        lines: std::str::Lines<'a>,
+        buffer: String,
+    }
+
+    impl<'a> LineReader<'a> {
+        fn next_line(&mut self) -> Option<&str> {
+            let line = self.lines.next()?;
+            self.buffer.clear();
+            self.buffer.push_str(line.trim());
+            self.buffer.make_ascii_uppercase();
+            Some(&self.buffer)
+        }
+    }
+
+    let mut reader = LineReader {
+        lines: "  one\ntwo  ".lines(),
+        buffer: String::new(),
+    };
+    assert_eq!(reader.next_line(), Some("ONE"));
+    assert_eq!(reader.next_line(), Some("TWO"));
+    assert_eq!(reader.next_line(), None);
+
+    //Not allowed: the first line is still borrowed when the buffer is overwritten
+    // let first = reader.next_line().unwrap();
+    // let second = reader.next_line().unwrap();
+    // println!("{} {}", first, second);
+    //error[E0499]: cannot borrow `reader` as mutable more than once at a time
+}
+
+pub fn main() {
+    annotated_items();
+    elision();
+    higher_ranked_bounds();
+    self_referential();
+    streaming_parser();
+}
@@ -31,6 +31,7 @@ fn syntax_designators() {
     print_result!(1 + 2);
 }
 
+#[allow(clippy::eq_op)]
 fn syntax_overload() {
     macro_rules! test {
         ($left:expr, and $right:expr) => {
@@ -55,6 +56,7 @@ fn syntax_overload() {
     test!(true; or false);
 }
 
+#[allow(clippy::eq_op)]
 fn syntax_repeat() {
     macro_rules! find_min {
           // Base case:
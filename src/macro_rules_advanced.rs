@@ -0,0 +1,390 @@
+/*
+ * Continuation of macro_rules.rs
+ * - tt-muncher: rule consumes a few tokens from the front and recurses on the rest ($($rest:tt)*)
+ * - internal rules: @name prefix selects helper rules, keeps them inside one macro
+ * - counting: no built-in counter, only recursion or tricks with $(...)*
+ * - #[macro_export]: macro is placed at the crate root (rust_by_example::hashmap!),
+ *   whatever module it is defined in; $crate expands to a path to the defining crate,
+ *   so expansions work from other crates too (see tests/macro_rules_advanced.rs)
+ * - hygiene: local variables and labels created by a macro are invisible to the caller,
+ *   items (fn, struct, const, mod) are not hygienic
+ */
+
+//Reexports used by expansions through $crate paths:
+//the caller doesn't need to import anything, or even know these exist
+#[doc(hidden)]
+pub mod __private {
+    pub use std::collections::HashMap;
+}
+
+/// Builds a `HashMap` from `key => value` entries and `..iterable` spreads.
+///
+/// ```
+/// let defaults = vec![("host", "localhost"), ("port", "80")];
+/// let config = rust_by_example::hashmap! {
+///     ..defaults,
+///     "port" => "8080",
+/// };
+/// assert_eq!(config["host"], "localhost");
+/// assert_eq!(config["port"], "8080");
+/// ```
+#[macro_export]
+macro_rules! hashmap {
+    //internal rules first: nothing left to munch
+    (@munch $map:ident;) => {};
+    //..spread, then the rest
+    (@munch $map:ident; .. $other:expr $(, $($rest:tt)*)?) => {
+        $map.extend($other);
+        $crate::hashmap!(@munch $map; $($($rest)*)?);
+    };
+    //key => value, then the rest
+    (@munch $map:ident; $key:expr => $value:expr $(, $($rest:tt)*)?) => {
+        $map.insert($key, $value);
+        $crate::hashmap!(@munch $map; $($($rest)*)?);
+    };
+    //public entry point: anything else
+    ($($body:tt)*) => {{
+        //hashmap!{} never inserts
+        #[allow(unused_mut)]
+        let mut map = $crate::macro_rules_advanced::__private::HashMap::new();
+        $crate::hashmap!(@munch map; $($body)*);
+        map
+    }};
+}
+
+/// Counts token trees in three different ways; all of them are constant expressions.
+///
+/// ```
+/// use rust_by_example::count_tts;
+///
+/// const N: usize = count_tts!(a b (c d) [e]);
+/// assert_eq!(N, 4);
+/// assert_eq!(count_tts!(@recursive x y z), 3);
+/// assert_eq!(count_tts!(@halving a b c d e), 5);
+/// ```
+#[macro_export]
+macro_rules! count_tts {
+    //1. Replace every token with () and take the length of the slice: no recursion
+    (@replace $_t:tt $sub:expr) => {
+        $sub
+    };
+    //2. Recursion: simple, but limited by #![recursion_limit] (128 by default)
+    (@recursive) => { 0usize };
+    (@recursive $head:tt $($tail:tt)*) => {
+        1usize + $crate::count_tts!(@recursive $($tail)*)
+    };
+    //3. Halving: consume pairs, recursion depth is log2(n)
+    (@halving) => { 0usize };
+    (@halving $odd:tt $($a:tt $b:tt)*) => {
+        ($crate::count_tts!(@halving $($a)*) << 1) | 1
+    };
+    (@halving $($a:tt $even:tt)*) => {
+        $crate::count_tts!(@halving $($a)*) << 1
+    };
+    ($($t:tt)*) => {
+        <[()]>::len(&[$($crate::count_tts!(@replace $t ())),*])
+    };
+}
+
+/// Generates a bitflags-style struct: one associated constant per flag,
+/// set operations and a `Debug` listing the names of set flags.
+///
+/// ```
+/// rust_by_example::bitflags! {
+///     pub struct Permissions: u8 {
+///         const READ = 0b001;
+///         const WRITE = 0b010;
+///     }
+/// }
+///
+/// let rw = Permissions::READ | Permissions::WRITE;
+/// assert!(rw.contains(Permissions::READ));
+/// assert_eq!(format!("{:?}", rw), "Permissions(READ | WRITE)");
+/// ```
+#[macro_export]
+macro_rules! bitflags {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident: $repr:ty {
+            $(const $flag:ident = $value:expr;)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+        $vis struct $name {
+            bits: $repr,
+        }
+
+        #[allow(dead_code)]
+        impl $name {
+            $(pub const $flag: $name = $name { bits: $value };)*
+            //$(...)* repeats anything, including parts of an expression
+            const ALL_BITS: $repr = 0 $(| $value)*;
+            const NAMES: [(&'static str, $repr); $crate::count_tts!($($flag)*)] =
+                [$((stringify!($flag), $value)),*];
+
+            pub const fn empty() -> $name {
+                $name { bits: 0 }
+            }
+
+            pub const fn all() -> $name {
+                $name { bits: Self::ALL_BITS }
+            }
+
+            pub const fn bits(&self) -> $repr {
+                self.bits
+            }
+
+            //unknown bits are rejected
+            pub fn from_bits(bits: $repr) -> Option<$name> {
+                if bits & !Self::ALL_BITS == 0 {
+                    Some($name { bits })
+                } else {
+                    None
+                }
+            }
+
+            pub const fn is_empty(&self) -> bool {
+                self.bits == 0
+            }
+
+            pub const fn contains(&self, other: $name) -> bool {
+                self.bits & other.bits == other.bits
+            }
+
+            pub fn insert(&mut self, other: $name) {
+                self.bits |= other.bits;
+            }
+
+            pub fn remove(&mut self, other: $name) {
+                self.bits &= !other.bits;
+            }
+        }
+
+        $crate::bitflags!(@binary_op $name, BitOr, bitor, BitOrAssign, bitor_assign, |);
+        $crate::bitflags!(@binary_op $name, BitAnd, bitand, BitAndAssign, bitand_assign, &);
+        $crate::bitflags!(@binary_op $name, BitXor, bitxor, BitXorAssign, bitxor_assign, ^);
+
+        //complement stays within known flags
+        impl ::core::ops::Not for $name {
+            type Output = $name;
+
+            fn not(self) -> $name {
+                $name { bits: !self.bits & Self::ALL_BITS }
+            }
+        }
+
+        impl ::core::fmt::Debug for $name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                let names: ::std::vec::Vec<&str> = Self::NAMES
+                    .iter()
+                    .filter(|(_, bits)| self.bits & bits == *bits)
+                    .map(|(name, _)| *name)
+                    .collect();
+                write!(f, "{}({})", stringify!($name), names.join(" | "))
+            }
+        }
+    };
+
+    //internal rule: the same impl for three operators, the operator itself is a tt
+    (@binary_op $name:ident, $trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $op:tt) => {
+        impl ::core::ops::$trait for $name {
+            type Output = $name;
+
+            fn $method(self, other: $name) -> $name {
+                $name { bits: self.bits $op other.bits }
+            }
+        }
+
+        impl ::core::ops::$assign_trait for $name {
+            fn $assign_method(&mut self, other: $name) {
+                *self = *self $op other;
+            }
+        }
+    };
+}
+
+fn tt_muncher() {
+    let defaults = vec![("color", "red"), ("size", "M")];
+    let order = crate::hashmap! {
+        "item" => "shirt",
+        ..defaults,
+        "size" => "L",
+    };
+    assert_eq!(order.len(), 3);
+    assert_eq!(order["size"], "L");
+
+    //values are any expressions
+    let squares = crate::hashmap! { 2 => 2 * 2, 3 => 3 * 3, 4 => 4 * 4 };
+    assert_eq!(squares[&3], 9);
+
+    let empty: std::collections::HashMap<i32, i32> = crate::hashmap! {};
+    assert!(empty.is_empty());
+}
+
+fn counting() {
+    //array length must be a constant: all three forms are usable there
+    let by_length = [0u8; crate::count_tts!(a b c)];
+    let by_recursion = [0u8; crate::count_tts!(@recursive a b c)];
+    let by_halving = [0u8; crate::count_tts!(@halving a b c)];
+    assert_eq!(by_length.len(), 3);
+    assert_eq!(by_recursion.len(), 3);
+    assert_eq!(by_halving.len(), 3);
+}
+
+fn generated_flags() {
+    crate::bitflags! {
+        struct Permissions: u8 {
+            const READ = 0b100;
+            const WRITE = 0b010;
+            const EXECUTE = 0b001;
+        }
+    }
+
+    let mut permissions = Permissions::READ | Permissions::WRITE;
+    assert!(permissions.contains(Permissions::READ));
+    assert!(!permissions.contains(Permissions::EXECUTE));
+    permissions.remove(Permissions::WRITE);
+    assert_eq!(permissions, Permissions::READ);
+    assert_eq!(
+        !Permissions::READ,
+        Permissions::WRITE | Permissions::EXECUTE
+    );
+    assert_eq!(Permissions::all().bits(), 0b111);
+    assert_eq!(
+        format!("{:?}", Permissions::all()),
+        "Permissions(READ | WRITE | EXECUTE)"
+    );
+}
+
+fn hygiene() {
+    //1. locals declared inside a macro don't leak
+    macro_rules! declare_x {
+        () => {
+            let x = 42;
+            let _ = x;
+        };
+    }
+    let x = 1;
+    declare_x!();
+    assert_eq!(x, 1);
+
+    //2. identifier passed by the caller belongs to the caller: it leaks on purpose
+    macro_rules! declare {
+        ($name:ident) => {
+            let $name = 42;
+        };
+    }
+    declare!(answer);
+    assert_eq!(answer, 42);
+
+    //3. items are not hygienic: fn declared inside the macro is visible afterwards
+    macro_rules! make_helper {
+        () => {
+            fn helper() -> &'static str {
+                "visible"
+            }
+        };
+    }
+    make_helper!();
+    assert_eq!(helper(), "visible");
+
+    //4. locals are resolved where the macro is defined, not where it is called
+    let y = "definition site";
+    macro_rules! read_y {
+        () => {
+            y
+        };
+    }
+    {
+        let y = "call site";
+        assert_eq!(y, "call site");
+        assert_eq!(read_y!(), "definition site");
+    }
+    //Not allowed: macro defined before `z` exists can't see it
+    // macro_rules! read_z { () => { z }; }
+    // let z = 1;
+    // read_z!();
+    //error[E0425]: cannot find value `z` in this scope
+}
+
+pub fn main() {
+    tt_muncher();
+    counting();
+    generated_flags();
+    hygiene();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    #[test]
+    fn hashmap_later_entries_win() {
+        let map = crate::hashmap! { "a" => 1, "a" => 2 };
+        assert_eq!(map, vec![("a", 2)].into_iter().collect::<HashMap<_, _>>());
+    }
+
+    #[test]
+    fn hashmap_spreads_any_iterable() {
+        let base: HashMap<&str, i32> = crate::hashmap! { "a" => 1 };
+        let map = crate::hashmap! { ..base, ..vec![("b", 2)], "c" => 3 };
+        assert_eq!(map.len(), 3);
+        assert_eq!(map["a"] + map["b"] + map["c"], 6);
+    }
+
+    #[test]
+    fn hashmap_without_trailing_comma() {
+        let map = crate::hashmap! { 1 => "one", 2 => "two" };
+        assert_eq!(map[&2], "two");
+    }
+
+    #[test]
+    fn count_strategies_agree() {
+        assert_eq!(crate::count_tts!(), 0);
+        assert_eq!(crate::count_tts!(@recursive), 0);
+        assert_eq!(crate::count_tts!(@halving), 0);
+        //a group counts as one tree
+        assert_eq!(crate::count_tts!((a b) [c] {d e f}), 3);
+        assert_eq!(crate::count_tts!(@halving 1 2 3 4 5 6 7 8 9 10 11), 11);
+        assert_eq!(crate::count_tts!(@recursive 1 2 3 4 5 6 7), 7);
+    }
+
+    crate::bitflags! {
+        #[doc = "Attributes are forwarded to the struct"]
+        struct Flags: u16 {
+            const A = 1;
+            const B = 2;
+            const C = 4;
+        }
+    }
+
+    #[test]
+    fn bitflags_operators() {
+        let mut flags = Flags::A;
+        flags |= Flags::C;
+        assert_eq!(flags.bits(), 5);
+        assert_eq!(flags & Flags::C, Flags::C);
+        assert_eq!(flags ^ Flags::A, Flags::C);
+        flags &= Flags::B;
+        assert!(flags.is_empty());
+        assert_eq!(!Flags::empty(), Flags::all());
+    }
+
+    #[test]
+    fn bitflags_from_bits_rejects_unknown() {
+        assert_eq!(Flags::from_bits(3), Some(Flags::A | Flags::B));
+        assert_eq!(Flags::from_bits(8), None);
+    }
+
+    #[test]
+    fn bitflags_debug() {
+        assert_eq!(format!("{:?}", Flags::empty()), "Flags()");
+        assert_eq!(format!("{:?}", Flags::B | Flags::C), "Flags(B | C)");
+    }
+
+    #[test]
+    fn hygiene() {
+        super::hygiene();
+    }
+}
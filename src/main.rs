@@ -1,42 +1,93 @@
+mod async_await;
 mod attributes;
 mod cargo;
+mod collections;
 mod crates;
 mod custom_types;
 mod error_handling;
+mod error_hierarchies;
 mod expressions;
 mod flow_of_control;
 mod functions;
 mod generics;
+mod iterators;
+mod lifetimes;
 mod macro_rules;
 mod modules;
+mod networking;
+mod operator_overloading;
+mod panics;
+mod patterns;
 mod primitives;
 mod printing;
+mod proc_macros;
+mod processes;
 mod scoping_rules;
+mod std_io;
 mod std_library_types;
+mod string_types;
+mod trait_objects;
 mod traits;
 mod variable_binding;
 
+use rust_by_example::{
+    capstone_chat, capstone_http, capstone_interpreter, const_generics, json, kv_store,
+    macro_rules_advanced, testing, variance,
+};
+
 //todo make code runnable?
 //todo better comments?
 
 extern crate termion;
 
 fn main() {
+    //hidden subcommand: a chapter re-invokes this binary as a child process
+    if std::env::args().nth(1).as_deref() == Some(processes::CHILD_COMMAND) {
+        return processes::child();
+    }
+    //`cargo run -- repl`: interactive interpreter instead of the chapters
+    if std::env::args().nth(1).as_deref() == Some(capstone_interpreter::REPL_COMMAND) {
+        return capstone_interpreter::repl::run().unwrap();
+    }
+
     printing::main();
     primitives::main();
     custom_types::main();
     variable_binding::main();
     expressions::main();
     flow_of_control::main();
+    patterns::main();
     functions::main();
     modules::main();
     crates::main();
     cargo::main();
     attributes::main();
     generics::main();
+    const_generics::main();
     scoping_rules::main();
+    lifetimes::main();
+    variance::main();
     traits::main();
+    operator_overloading::main();
+    trait_objects::main();
+    iterators::main();
     macro_rules::main();
+    macro_rules_advanced::main();
+    proc_macros::main();
     error_handling::main();
+    error_hierarchies::main();
+    panics::main();
+    processes::main();
+    networking::main();
+    capstone_http::main();
+    capstone_interpreter::main();
+    capstone_chat::main();
+    kv_store::main();
+    json::main();
     std_library_types::main();
+    collections::main();
+    std_io::main();
+    async_await::main();
+    string_types::main();
+    testing::main();
 }
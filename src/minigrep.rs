@@ -0,0 +1,532 @@
+/*
+ * Library half of the minigrep binary (src/bin/minigrep.rs, the layout from cargo.rs)
+ * The binary only reads the environment and picks the exit code; everything testable is here:
+ * - Config::parse: arguments by hand, no parser crate
+ * - search: matching lines with match positions, plus context lines
+ * - files: what to search, directories walked with -r
+ * - run: search every file, print, collect per-file errors instead of stopping
+ *
+ * Errors: Error::Usage for bad arguments, Error::Io with the path for file problems
+ * (see error_hierarchies for wrapping errors with source())
+ */
+
+use std::error;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+pub const USAGE: &str = "\
+usage: minigrep [OPTIONS] QUERY [PATH...]
+  -i            ignore case (also when IGNORE_CASE is set)
+  -r            search directories recursively
+  -n            show line numbers
+  -C NUM        show NUM lines of context around matches
+  --color WHEN  auto, always or never
+  -h, --help    show this message
+Reads standard input when no PATH is given, or for a PATH of -.";
+
+#[derive(Debug)]
+pub enum Error {
+    Usage(String),
+    Io { path: PathBuf, source: io::Error },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Usage(message) => write!(f, "{}", message),
+            Error::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Usage(_) => None,
+            Error::Io { source, .. } => Some(source),
+        }
+    }
+}
+
+fn io_error(path: &Path) -> impl FnOnce(io::Error) -> Error + '_ {
+    move |source| Error::Io {
+        path: path.to_path_buf(),
+        source,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    pub query: String,
+    //empty: standard input, and so is a path of "-"
+    pub paths: Vec<PathBuf>,
+    pub ignore_case: bool,
+    pub recursive: bool,
+    pub line_numbers: bool,
+    pub context: usize,
+    pub color: Color,
+    pub help: bool,
+}
+
+impl Config {
+    /// Parses the arguments after the program name; `ignore_case` is the environment's default.
+    ///
+    /// ```
+    /// use rust_by_example::minigrep::Config;
+    ///
+    /// let args = ["-n", "-C", "2", "needle", "src"].iter().map(|s| s.to_string());
+    /// let config = Config::parse(args, true).unwrap();
+    /// assert_eq!((config.query.as_str(), config.context), ("needle", 2));
+    /// assert!(config.ignore_case && config.line_numbers && !config.recursive);
+    /// ```
+    pub fn parse<I>(args: I, ignore_case: bool) -> Result<Config, Error>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut config = Config {
+            query: String::new(),
+            paths: Vec::new(),
+            ignore_case,
+            recursive: false,
+            line_numbers: false,
+            context: 0,
+            color: Color::Auto,
+            help: false,
+        };
+        let mut query = None;
+        let mut args = args.into_iter();
+        //after "--" everything is positional, even if it starts with '-'
+        let mut options_done = false;
+
+        while let Some(arg) = args.next() {
+            if options_done || !arg.starts_with('-') || arg == "-" {
+                match query {
+                    None => query = Some(arg),
+                    Some(_) => config.paths.push(PathBuf::from(arg)),
+                }
+                continue;
+            }
+            //option values: "-C 2", "-C2", "--color never", "--color=never"
+            let mut value = |name: &str, inline: &str| match inline {
+                "" => args
+                    .next()
+                    .ok_or_else(|| Error::Usage(format!("{} needs a value", name))),
+                _ => Ok(inline.trim_start_matches('=').to_string()),
+            };
+            match arg.as_str() {
+                "--" => options_done = true,
+                "-h" | "--help" => config.help = true,
+                "-i" => config.ignore_case = true,
+                "-r" => config.recursive = true,
+                "-n" => config.line_numbers = true,
+                _ if arg.starts_with("-C") => {
+                    let number = value("-C", &arg[2..])?;
+                    config.context = number.parse().map_err(|_| {
+                        Error::Usage(format!("invalid context length {:?}", number))
+                    })?;
+                }
+                _ if arg.starts_with("--color") => {
+                    config.color = match value("--color", &arg["--color".len()..])?.as_str() {
+                        "auto" => Color::Auto,
+                        "always" => Color::Always,
+                        "never" => Color::Never,
+                        other => return Err(Error::Usage(format!("invalid --color {:?}", other))),
+                    };
+                }
+                //combined short flags: -rni
+                _ if arg.len() > 2 && arg[1..].chars().all(|c| "irn".contains(c)) => {
+                    for flag in arg[1..].chars() {
+                        match flag {
+                            'i' => config.ignore_case = true,
+                            'r' => config.recursive = true,
+                            _ => config.line_numbers = true,
+                        }
+                    }
+                }
+                _ => return Err(Error::Usage(format!("unknown option {}", arg))),
+            }
+        }
+
+        match query {
+            Some(query) => config.query = query,
+            None if config.help => {}
+            None => return Err(Error::Usage("missing QUERY".to_string())),
+        }
+        Ok(config)
+    }
+}
+
+/// Byte ranges of non-overlapping occurrences of `query` in `line`.
+///
+/// Case-insensitive matching compares characters one by one after `to_lowercase`:
+/// ranges stay valid in the original line even where lowercasing changes byte lengths.
+pub fn find_all(line: &str, query: &str, ignore_case: bool) -> Vec<Range<usize>> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+    if !ignore_case {
+        return line
+            .match_indices(query)
+            .map(|(start, found)| start..start + found.len())
+            .collect();
+    }
+
+    let same = |a: char, b: char| a == b || a.to_lowercase().eq(b.to_lowercase());
+    let mut ranges = Vec::new();
+    let mut next_allowed = 0;
+    for (start, _) in line.char_indices() {
+        if start < next_allowed {
+            continue;
+        }
+        let mut candidate = line[start..].char_indices();
+        let mut end = start;
+        let matched = query.chars().all(|q| match candidate.next() {
+            Some((offset, c)) if same(c, q) => {
+                end = start + offset + c.len_utf8();
+                true
+            }
+            _ => false,
+        });
+        if matched {
+            ranges.push(start..end);
+            next_allowed = end;
+        }
+    }
+    ranges
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line<'a> {
+    //1-based
+    pub number: usize,
+    pub text: &'a str,
+    //empty for context lines
+    pub matches: Vec<Range<usize>>,
+}
+
+/// Matching lines, each group extended by `context` lines on both sides;
+/// groups that touch or overlap are merged, like grep does.
+///
+/// ```
+/// use rust_by_example::minigrep::search;
+///
+/// let text = "one\ntwo\nthree\nfour\nfive\nsix\nseven";
+/// let groups = search("f", text, false, 1);
+/// let numbers: Vec<Vec<usize>> = groups
+///     .iter()
+///     .map(|group| group.iter().map(|line| line.number).collect())
+///     .collect();
+/// //four and five are matches, three and six are context
+/// assert_eq!(numbers, [[3, 4, 5, 6]]);
+/// ```
+pub fn search<'a>(
+    query: &str,
+    contents: &'a str,
+    ignore_case: bool,
+    context: usize,
+) -> Vec<Vec<Line<'a>>> {
+    let lines: Vec<&str> = contents.lines().collect();
+    let matches: Vec<Vec<Range<usize>>> = lines
+        .iter()
+        .map(|line| find_all(line, query, ignore_case))
+        .collect();
+
+    //index ranges to print, merged while building
+    let mut windows: Vec<Range<usize>> = Vec::new();
+    for (index, _) in matches.iter().enumerate().filter(|(_, m)| !m.is_empty()) {
+        let window = index.saturating_sub(context)..(index + context + 1).min(lines.len());
+        match windows.last_mut() {
+            Some(last) if window.start <= last.end => last.end = window.end,
+            _ => windows.push(window),
+        }
+    }
+
+    windows
+        .into_iter()
+        .map(|window| {
+            window
+                .map(|index| Line {
+                    number: index + 1,
+                    text: lines[index],
+                    matches: matches[index].clone(),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Files to search: `paths` themselves, or everything below them with `recursive`.
+/// Directory entries are sorted so the output is deterministic; symlinks found while walking
+/// are skipped, like grep -r does, so a link back up the tree can't send it in circles.
+pub fn files(paths: &[PathBuf], recursive: bool) -> (Vec<PathBuf>, Vec<Error>) {
+    fn walk(dir: &Path, files: &mut Vec<PathBuf>, errors: &mut Vec<Error>) {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(err) => return errors.push(io_error(dir)(err)),
+        };
+        let mut entries: Vec<fs::DirEntry> = entries.filter_map(|e| e.ok()).collect();
+        entries.sort_by_key(|entry| entry.path());
+        for entry in entries {
+            let path = entry.path();
+            //file_type from the entry, unlike path.is_dir(), doesn't follow symlinks
+            match entry.file_type() {
+                Ok(kind) if kind.is_dir() => walk(&path, files, errors),
+                Ok(kind) if kind.is_symlink() => {}
+                Ok(_) => files.push(path),
+                Err(err) => errors.push(io_error(&path)(err)),
+            }
+        }
+    }
+
+    let mut files = Vec::new();
+    let mut errors = Vec::new();
+    for path in paths {
+        if !path.is_dir() {
+            files.push(path.clone());
+        } else if recursive {
+            walk(path, &mut files, &mut errors);
+        } else {
+            let source = io::Error::other("is a directory (use -r)");
+            errors.push(Error::Io {
+                path: path.clone(),
+                source,
+            });
+        }
+    }
+    (files, errors)
+}
+
+/// One output line: optional path and line number, `:` after matches, `-` after context.
+pub fn format_line(path: Option<&Path>, line: &Line, line_numbers: bool, color: bool) -> String {
+    use termion::{color as c, style};
+
+    let separator = if line.matches.is_empty() { '-' } else { ':' };
+    let mut out = String::new();
+    if let Some(path) = path {
+        if color {
+            out += &format!("{}{}{}", c::Fg(c::Magenta), path.display(), c::Fg(c::Reset));
+        } else {
+            out += &path.display().to_string();
+        }
+        out.push(separator);
+    }
+    if line_numbers {
+        if color {
+            out += &format!("{}{}{}", c::Fg(c::Green), line.number, c::Fg(c::Reset));
+        } else {
+            out += &line.number.to_string();
+        }
+        out.push(separator);
+    }
+    if !color {
+        out += line.text;
+        return out;
+    }
+    //highlight: text before each match, the match in bold red, the rest
+    let mut last = 0;
+    for range in &line.matches {
+        out += &line.text[last..range.start];
+        out += &format!(
+            "{}{}{}{}{}",
+            style::Bold,
+            c::Fg(c::Red),
+            &line.text[range.clone()],
+            c::Fg(c::Reset),
+            style::Reset
+        );
+        last = range.end;
+    }
+    out += &line.text[last..];
+    out
+}
+
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub matched: bool,
+    //problems with single files: reported, the other files are still searched
+    pub errors: Vec<Error>,
+}
+
+/// Searches stdin or every file and writes the results to `out`.
+pub fn run<W: Write>(config: &Config, color: bool, out: &mut W) -> io::Result<Summary> {
+    let mut summary = Summary::default();
+    let mut inputs: Vec<(Option<PathBuf>, String)> = Vec::new();
+
+    let stdin = || {
+        let mut contents = String::new();
+        io::Read::read_to_string(&mut io::stdin(), &mut contents).map(|_| contents)
+    };
+
+    if config.paths.is_empty() {
+        inputs.push((None, stdin()?));
+    } else {
+        let (files, errors) = files(&config.paths, config.recursive);
+        summary.errors = errors;
+        for path in files {
+            //"-" among the paths is standard input, shown under the name grep uses for it
+            let read = if path == Path::new("-") {
+                stdin().map(|contents| (PathBuf::from("(standard input)"), contents))
+            } else {
+                fs::read_to_string(&path).map(|contents| (path.clone(), contents))
+            };
+            match read {
+                Ok((path, contents)) => inputs.push((Some(path), contents)),
+                Err(err) => summary.errors.push(io_error(&path)(err)),
+            }
+        }
+    }
+
+    //like grep: file names only when more than one file could match
+    let show_paths = config.recursive || config.paths.len() > 1;
+    let mut first_group = true;
+    for (path, contents) in &inputs {
+        for group in search(&config.query, contents, config.ignore_case, config.context) {
+            summary.matched = true;
+            //"--" between groups, only when context makes groups meaningful
+            if config.context > 0 && !first_group {
+                writeln!(out, "--")?;
+            }
+            first_group = false;
+            for line in &group {
+                let path = path.as_deref().filter(|_| show_paths);
+                writeln!(
+                    out,
+                    "{}",
+                    format_line(path, line, config.line_numbers, color)
+                )?;
+            }
+        }
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+//expected match positions are written as [start..end]: a list of ranges, not a range of numbers
+#[allow(clippy::single_range_in_vec_init)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    fn numbers(groups: &[Vec<Line>]) -> Vec<Vec<usize>> {
+        groups
+            .iter()
+            .map(|group| group.iter().map(|line| line.number).collect())
+            .collect()
+    }
+
+    #[test]
+    fn parses_flags_and_positionals() {
+        let config = Config::parse(args("-rn -C3 --color=never fn src tests"), false).unwrap();
+        assert!(config.recursive && config.line_numbers && !config.ignore_case);
+        assert_eq!(config.context, 3);
+        assert_eq!(config.color, Color::Never);
+        assert_eq!(config.query, "fn");
+        assert_eq!(config.paths, [PathBuf::from("src"), PathBuf::from("tests")]);
+
+        //"--" ends the options: a query starting with '-'
+        let config = Config::parse(args("-i -- -n file"), false).unwrap();
+        assert!(config.ignore_case && !config.line_numbers);
+        assert_eq!(config.query, "-n");
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        let message = |line: &str| Config::parse(args(line), false).unwrap_err().to_string();
+        assert_eq!(message(""), "missing QUERY");
+        assert_eq!(message("-x query"), "unknown option -x");
+        assert_eq!(message("query -C"), "-C needs a value");
+        assert_eq!(message("-C many query"), "invalid context length \"many\"");
+        assert_eq!(
+            message("--color sometimes q"),
+            "invalid --color \"sometimes\""
+        );
+        assert!(Config::parse(args("--help"), false).unwrap().help);
+    }
+
+    #[test]
+    fn case_sensitive() {
+        let contents = "Rust:\nsafe, fast, productive.\nPick three.\nDuct tape.";
+        let groups = search("duct", contents, false, 0);
+        assert_eq!(numbers(&groups), [[2]]);
+        assert_eq!(groups[0][0].text, "safe, fast, productive.");
+        assert_eq!(groups[0][0].matches, [15..19]);
+    }
+
+    #[test]
+    fn case_insensitive() {
+        let contents = "Rust:\nsafe, fast, productive.\nPick three.\nTrust me.";
+        assert_eq!(numbers(&search("rUsT", contents, true, 0)), [[1], [4]]);
+        //ranges point into the original text, even when lowercase has another byte length
+        assert_eq!(find_all("Straße STRASSE", "straße", true), [0..7]);
+        assert_eq!(find_all("İx ix", "ix", true), [4..6]);
+        assert_eq!(find_all("aaaa", "aa", true), [0..2, 2..4]);
+        assert!(find_all("anything", "", true).is_empty());
+    }
+
+    #[test]
+    fn context_groups_merge() {
+        let contents = "a\nmatch\nb\nc\nd\ne\nmatch\nf\nmatch";
+        assert_eq!(
+            numbers(&search("match", contents, false, 1)),
+            [vec![1, 2, 3], vec![6, 7, 8, 9]]
+        );
+        //two lines of context: the gap closes, one group
+        assert_eq!(numbers(&search("match", contents, false, 2)).len(), 1);
+        let groups = search("match", contents, false, 1);
+        assert!(groups[0][0].matches.is_empty());
+        assert!(!groups[0][1].matches.is_empty());
+    }
+
+    #[test]
+    fn formats_plain_and_coloured() {
+        let line = Line {
+            number: 7,
+            text: "let x = x + 1;",
+            matches: vec![4..5, 8..9],
+        };
+        let path = Path::new("src/main.rs");
+        assert_eq!(
+            format_line(Some(path), &line, true, false),
+            "src/main.rs:7:let x = x + 1;"
+        );
+        let context = Line {
+            matches: Vec::new(),
+            ..line.clone()
+        };
+        assert_eq!(format_line(None, &context, true, false), "7-let x = x + 1;");
+
+        let coloured = format_line(None, &line, false, true);
+        let red = termion::color::Fg(termion::color::Red).to_string();
+        assert_eq!(coloured.matches(&red).count(), 2);
+        //without the escape sequences it's the original line
+        let plain: String = strip_escapes(&coloured);
+        assert_eq!(plain, line.text);
+    }
+
+    //removes ESC [ ... m sequences
+    fn strip_escapes(text: &str) -> String {
+        let mut plain = String::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(|&c| c == 'm');
+            } else {
+                plain.push(c);
+            }
+        }
+        plain
+    }
+}
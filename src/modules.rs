@@ -16,6 +16,8 @@
 //2.1 You can see objects in descendants if all objects in path are pub
 //2.2 pub(X) may add additional restriction for ancestors
 
+//pub(self) spelled out on purpose: it's the same as private
+#[allow(clippy::needless_pub_self)]
 mod my_mod {
 
     fn _private_function() {}
@@ -97,7 +99,7 @@ fn structures() {
 
         impl<T> ClosedBox<T> {
             pub fn new(contents: T) -> ClosedBox<T> {
-                ClosedBox { contents: contents }
+                ClosedBox { contents }
             }
 
             pub fn unclassify_contents(&self) -> &T {
@@ -159,7 +161,7 @@ fn mod_access() {
 fn use_declaration() {
     mod use_mod {
         pub fn public_function() -> &'static str {
-            &"cake"
+            "cake"
         }
     }
 
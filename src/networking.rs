@@ -0,0 +1,231 @@
+/*
+ * std::net: blocking TCP and UDP sockets
+ * - TcpListener::bind + accept/incoming: server side, one TcpStream per connection
+ * - TcpStream::connect: client side; TcpStream is Read + Write
+ * - UdpSocket: datagrams, no connection, no ordering or delivery guarantees
+ * - port 0: the OS picks a free port, local_addr() tells which one
+ * Everything here binds to 127.0.0.1 only: no network access needed
+ *
+ * One thread per connection is the simplest server model;
+ * shared state goes into Arc (see std_library_types::reference_counting) + Mutex or atomics
+ */
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, UdpSocket};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+//what every connection handler can see
+#[derive(Default)]
+struct ServerState {
+    connections: AtomicUsize,
+    //every line echoed, in arrival order
+    log: Mutex<Vec<String>>,
+}
+
+//Line echo: replies to every line with the same line, until the client closes its side
+fn handle_echo(stream: TcpStream, state: &ServerState) -> io::Result<()> {
+    state.connections.fetch_add(1, Ordering::SeqCst);
+    //two handles to one socket: buffered reading, direct writing
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        state.log.lock().unwrap().push(line.clone());
+        writeln!(writer, "{}", line)?;
+    }
+    Ok(())
+}
+
+//Accepts `clients` connections, a thread each, then returns
+fn echo_server(listener: TcpListener, state: Arc<ServerState>, clients: usize) {
+    let handlers: Vec<_> = listener
+        .incoming()
+        .take(clients)
+        .map(|stream| {
+            let stream = stream.unwrap();
+            let state = Arc::clone(&state);
+            thread::spawn(move || handle_echo(stream, &state).unwrap())
+        })
+        .collect();
+    for handler in handlers {
+        handler.join().unwrap();
+    }
+}
+
+fn tcp_echo() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    assert!(address.ip().is_loopback());
+    assert_ne!(address.port(), 0);
+
+    let state = Arc::new(ServerState::default());
+    let server = {
+        let state = Arc::clone(&state);
+        thread::spawn(move || echo_server(listener, state, 2))
+    };
+
+    //one round trip per line
+    let client = TcpStream::connect(address).unwrap();
+    let mut reader = BufReader::new(client.try_clone().unwrap());
+    let mut writer = client;
+    for message in ["hello", "echo"] {
+        writeln!(writer, "{}", message).unwrap();
+        let mut reply = String::new();
+        reader.read_line(&mut reply).unwrap();
+        assert_eq!(reply, format!("{}\n", message));
+    }
+    //closing the write half: the server sees EOF, finishes, closes its side
+    writer.shutdown(Shutdown::Write).unwrap();
+    let mut rest = String::new();
+    reader.read_to_string(&mut rest).unwrap();
+    assert_eq!(rest, "");
+
+    //second client: write everything, shut down, read everything
+    let mut client = TcpStream::connect(address).unwrap();
+    assert_eq!(client.peer_addr().unwrap(), address);
+    client.write_all(b"one\ntwo\n").unwrap();
+    client.shutdown(Shutdown::Write).unwrap();
+    let mut replies = String::new();
+    client.read_to_string(&mut replies).unwrap();
+    assert_eq!(replies, "one\ntwo\n");
+
+    server.join().unwrap();
+    //all handler threads are done: the only Arc left is ours
+    assert_eq!(Arc::strong_count(&state), 1);
+    assert_eq!(state.connections.load(Ordering::SeqCst), 2);
+    assert_eq!(*state.log.lock().unwrap(), ["hello", "echo", "one", "two"]);
+}
+
+fn timeouts() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+
+    //a server that accepts and then says nothing
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut buffer = [0; 1];
+        //wait for the client to give up and close
+        stream.read(&mut buffer).unwrap()
+    });
+
+    let mut client = TcpStream::connect_timeout(&address, Duration::from_secs(5)).unwrap();
+    client
+        .set_read_timeout(Some(Duration::from_millis(50)))
+        .unwrap();
+    //the OS may round the value to its timer granularity
+    assert!(client.read_timeout().unwrap().is_some());
+
+    let start = Instant::now();
+    let mut buffer = [0; 16];
+    let error = client.read(&mut buffer).unwrap_err();
+    //WouldBlock on unix, TimedOut on windows
+    assert!(matches!(
+        error.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+    ));
+    assert!(start.elapsed() >= Duration::from_millis(50));
+
+    //a timeout doesn't close the connection, dropping the client does
+    drop(client);
+    //server's read returns 0: end of stream
+    assert_eq!(server.join().unwrap(), 0);
+}
+
+fn non_blocking_accept() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    listener.set_nonblocking(true).unwrap();
+
+    //nobody connected yet: accept returns immediately instead of blocking
+    let error = listener.accept().unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::WouldBlock);
+
+    let client = thread::spawn(move || {
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream.write_all(b"ping").unwrap();
+    });
+
+    //poll loop: other work could be done between attempts
+    let mut attempts = 0;
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let (mut stream, peer) = loop {
+        match listener.accept() {
+            Ok(connection) => break connection,
+            Err(error) if error.kind() == io::ErrorKind::WouldBlock => {
+                attempts += 1;
+                assert!(Instant::now() < deadline, "no connection");
+                thread::sleep(Duration::from_millis(5));
+            }
+            Err(error) => panic!("accept failed: {}", error),
+        }
+    };
+    assert!(peer.ip().is_loopback());
+    assert!(attempts < 1000);
+
+    //accepted sockets may inherit non-blocking mode: switch back for a plain read
+    stream.set_nonblocking(false).unwrap();
+    let mut message = String::new();
+    stream.read_to_string(&mut message).unwrap();
+    assert_eq!(message, "ping");
+    client.join().unwrap();
+}
+
+fn udp_ping_pong() {
+    let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let server_address = server.local_addr().unwrap();
+    server
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+
+    //answers each "ping N" with "pong N", to whoever sent it, until "stop"
+    let responder = thread::spawn(move || {
+        let mut buffer = [0; 64];
+        let mut answered = 0;
+        loop {
+            let (size, sender) = server.recv_from(&mut buffer).unwrap();
+            let message = std::str::from_utf8(&buffer[..size]).unwrap();
+            if message == "stop" {
+                return answered;
+            }
+            let reply = message.replace("ping", "pong");
+            server.send_to(reply.as_bytes(), sender).unwrap();
+            answered += 1;
+        }
+    });
+
+    let client = UdpSocket::bind("127.0.0.1:0").unwrap();
+    client
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    //connect on UDP only sets the default destination and filters incoming datagrams
+    client.connect(server_address).unwrap();
+
+    let mut buffer = [0; 64];
+    for i in 0..3 {
+        client.send(format!("ping {}", i).as_bytes()).unwrap();
+        let size = client.recv(&mut buffer).unwrap();
+        assert_eq!(&buffer[..size], format!("pong {}", i).as_bytes());
+    }
+
+    //datagram boundaries are kept: a too small buffer truncates, the rest is lost
+    client.send(b"ping long message").unwrap();
+    let mut small = [0; 4];
+    let size = client.recv(&mut small).unwrap();
+    assert_eq!(&small[..size], b"pong");
+
+    client.send(b"stop").unwrap();
+    assert_eq!(responder.join().unwrap(), 4);
+
+    //addresses can be parsed and built directly
+    let parsed: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+    assert_eq!(parsed.port(), 8080);
+}
+
+pub fn main() {
+    tcp_echo();
+    timeouts();
+    non_blocking_accept();
+    udp_ping_pong();
+}
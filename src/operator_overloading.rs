@@ -0,0 +1,621 @@
+/*
+ * Continuation of traits::operator_overloading
+ * Operators are sugar for std::ops / std::cmp traits:
+ * a + b -> Add::add(a, b), a += b -> AddAssign::add_assign(&mut a, b), -a -> Neg::neg(a)
+ * a[i] -> *Index::index(&a, i), a[i] = x -> *IndexMut::index_mut(&mut a, i) = x
+ * !a -> Not, a & b / a | b / a ^ b / a << n / a >> n -> BitAnd, BitOr, BitXor, Shl, Shr
+ * a < b -> PartialOrd, a == b -> PartialEq
+ *
+ * Binary operator traits take operands by value: for non-Copy types (or to avoid copies)
+ * impls for references are added too, e.g. impl Add<&V> for &V
+ * std generates them with a macro, so does this file (forward_ref_binop, forward_ref_unop)
+ */
+
+use std::collections::HashSet;
+use std::ops::{
+    Add, AddAssign, BitAnd, BitOr, BitXor, Index, IndexMut, Mul, MulAssign, Neg, Not, Shl, Shr,
+    Sub, SubAssign,
+};
+
+//Given `impl Op<T> for T`, adds `&T op &T`, `T op &T` and `&T op T` for Copy types
+macro_rules! forward_ref_binop {
+    (impl $imp:ident, $method:ident for $t:ty, $u:ty) => {
+        impl<'a> $imp<$u> for &'a $t {
+            type Output = <$t as $imp<$u>>::Output;
+
+            fn $method(self, other: $u) -> Self::Output {
+                $imp::$method(*self, other)
+            }
+        }
+
+        impl<'a> $imp<&'a $u> for $t {
+            type Output = <$t as $imp<$u>>::Output;
+
+            fn $method(self, other: &'a $u) -> Self::Output {
+                $imp::$method(self, *other)
+            }
+        }
+
+        impl<'a, 'b> $imp<&'a $u> for &'b $t {
+            type Output = <$t as $imp<$u>>::Output;
+
+            fn $method(self, other: &'a $u) -> Self::Output {
+                $imp::$method(*self, *other)
+            }
+        }
+    };
+}
+
+//Given `impl Op for T`, adds `Op for &T`, e.g. -&v, for Copy types
+macro_rules! forward_ref_unop {
+    (impl $imp:ident, $method:ident for $t:ty) => {
+        impl<'a> $imp for &'a $t {
+            type Output = <$t as $imp>::Output;
+
+            fn $method(self) -> Self::Output {
+                $imp::$method(*self)
+            }
+        }
+    };
+}
+
+//Integer components: laws hold exactly, and Eq/Ord/Hash can be derived (f64 has neither)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+struct Vector2 {
+    x: i64,
+    y: i64,
+}
+
+fn v(x: i64, y: i64) -> Vector2 {
+    Vector2 { x, y }
+}
+
+impl Add for Vector2 {
+    type Output = Vector2;
+
+    fn add(self, other: Vector2) -> Vector2 {
+        v(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Vector2 {
+    type Output = Vector2;
+
+    fn sub(self, other: Vector2) -> Vector2 {
+        v(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Neg for Vector2 {
+    type Output = Vector2;
+
+    fn neg(self) -> Vector2 {
+        v(-self.x, -self.y)
+    }
+}
+
+//Vector * scalar
+impl Mul<i64> for Vector2 {
+    type Output = Vector2;
+
+    fn mul(self, k: i64) -> Vector2 {
+        v(self.x * k, self.y * k)
+    }
+}
+
+//scalar * Vector: the left operand's type implements the trait
+impl Mul<Vector2> for i64 {
+    type Output = Vector2;
+
+    fn mul(self, vector: Vector2) -> Vector2 {
+        vector * self
+    }
+}
+
+//Vector * Vector: dot product, Output doesn't have to be Self
+impl Mul for Vector2 {
+    type Output = i64;
+
+    fn mul(self, other: Vector2) -> i64 {
+        self.x * other.x + self.y * other.y
+    }
+}
+
+forward_ref_unop!(impl Neg, neg for Vector2);
+forward_ref_binop!(impl Add, add for Vector2, Vector2);
+forward_ref_binop!(impl Sub, sub for Vector2, Vector2);
+forward_ref_binop!(impl Mul, mul for Vector2, i64);
+forward_ref_binop!(impl Mul, mul for Vector2, Vector2);
+
+//*Assign: in place, usually defined through the binary operator
+impl AddAssign for Vector2 {
+    fn add_assign(&mut self, other: Vector2) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Vector2 {
+    fn sub_assign(&mut self, other: Vector2) {
+        *self = *self - other;
+    }
+}
+
+impl MulAssign<i64> for Vector2 {
+    fn mul_assign(&mut self, k: i64) {
+        *self = *self * k;
+    }
+}
+
+//v[0] is x, v[1] is y
+impl Index<usize> for Vector2 {
+    type Output = i64;
+
+    fn index(&self, index: usize) -> &i64 {
+        match index {
+            0 => &self.x,
+            1 => &self.y,
+            _ => panic!("Vector2 index out of bounds: {}", index),
+        }
+    }
+}
+
+impl IndexMut<usize> for Vector2 {
+    fn index_mut(&mut self, index: usize) -> &mut i64 {
+        match index {
+            0 => &mut self.x,
+            1 => &mut self.y,
+            _ => panic!("Vector2 index out of bounds: {}", index),
+        }
+    }
+}
+
+//Ordering by length first, then lexicographically: must agree with Eq
+impl Vector2 {
+    fn length_squared(&self) -> i64 {
+        *self * *self
+    }
+}
+
+impl Ord for Vector2 {
+    fn cmp(&self, other: &Vector2) -> std::cmp::Ordering {
+        self.length_squared()
+            .cmp(&other.length_squared())
+            .then(self.x.cmp(&other.x))
+            .then(self.y.cmp(&other.y))
+    }
+}
+
+impl PartialOrd for Vector2 {
+    fn partial_cmp(&self, other: &Vector2) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+//2x2 row-major matrix
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Matrix {
+    rows: [[i64; 2]; 2],
+}
+
+impl Matrix {
+    const IDENTITY: Matrix = Matrix {
+        rows: [[1, 0], [0, 1]],
+    };
+
+    fn new(a: i64, b: i64, c: i64, d: i64) -> Matrix {
+        Matrix {
+            rows: [[a, b], [c, d]],
+        }
+    }
+}
+
+impl Add for Matrix {
+    type Output = Matrix;
+
+    fn add(self, other: Matrix) -> Matrix {
+        let mut result = self;
+        for (row, other_row) in result.rows.iter_mut().zip(other.rows.iter()) {
+            for (cell, other_cell) in row.iter_mut().zip(other_row.iter()) {
+                *cell += other_cell;
+            }
+        }
+        result
+    }
+}
+
+impl Mul for Matrix {
+    type Output = Matrix;
+
+    fn mul(self, other: Matrix) -> Matrix {
+        let mut result = Matrix::new(0, 0, 0, 0);
+        for i in 0..2 {
+            for j in 0..2 {
+                result[(i, j)] = (0..2).map(|k| self[(i, k)] * other[(k, j)]).sum();
+            }
+        }
+        result
+    }
+}
+
+//Matrix * Vector
+impl Mul<Vector2> for Matrix {
+    type Output = Vector2;
+
+    fn mul(self, vector: Vector2) -> Vector2 {
+        v(
+            self[(0, 0)] * vector.x + self[(0, 1)] * vector.y,
+            self[(1, 0)] * vector.x + self[(1, 1)] * vector.y,
+        )
+    }
+}
+
+impl Mul<i64> for Matrix {
+    type Output = Matrix;
+
+    fn mul(self, k: i64) -> Matrix {
+        let mut result = self;
+        for row in result.rows.iter_mut() {
+            for cell in row.iter_mut() {
+                *cell *= k;
+            }
+        }
+        result
+    }
+}
+
+impl MulAssign for Matrix {
+    fn mul_assign(&mut self, other: Matrix) {
+        *self = *self * other;
+    }
+}
+
+forward_ref_binop!(impl Add, add for Matrix, Matrix);
+forward_ref_binop!(impl Mul, mul for Matrix, Matrix);
+forward_ref_binop!(impl Mul, mul for Matrix, Vector2);
+forward_ref_binop!(impl Mul, mul for Matrix, i64);
+
+//Index type doesn't have to be usize: m[(row, column)]
+impl Index<(usize, usize)> for Matrix {
+    type Output = i64;
+
+    fn index(&self, (row, column): (usize, usize)) -> &i64 {
+        &self.rows[row][column]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Matrix {
+    fn index_mut(&mut self, (row, column): (usize, usize)) -> &mut i64 {
+        &mut self.rows[row][column]
+    }
+}
+
+//Newtype: only operations that make sense for money are implemented
+//Money + Money and Money * i64 are fine, Money * Money is not
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+struct Money {
+    cents: i64,
+}
+
+impl Money {
+    fn new(units: i64, cents: i64) -> Money {
+        Money {
+            cents: units * 100 + cents,
+        }
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+
+    fn add(self, other: Money) -> Money {
+        Money {
+            cents: self.cents + other.cents,
+        }
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+
+    fn sub(self, other: Money) -> Money {
+        Money {
+            cents: self.cents - other.cents,
+        }
+    }
+}
+
+impl Mul<i64> for Money {
+    type Output = Money;
+
+    fn mul(self, quantity: i64) -> Money {
+        Money {
+            cents: self.cents * quantity,
+        }
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+
+    fn neg(self) -> Money {
+        Money { cents: -self.cents }
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, other: Money) {
+        self.cents += other.cents;
+    }
+}
+
+impl SubAssign for Money {
+    fn sub_assign(&mut self, other: Money) {
+        self.cents -= other.cents;
+    }
+}
+
+//iter().sum() works through Sum, not Add
+impl std::iter::Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money::default(), Add::add)
+    }
+}
+
+forward_ref_unop!(impl Neg, neg for Money);
+forward_ref_binop!(impl Add, add for Money, Money);
+forward_ref_binop!(impl Sub, sub for Money, Money);
+forward_ref_binop!(impl Mul, mul for Money, i64);
+
+//Bit set of up to 32 flags
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Mask(u32);
+
+impl Not for Mask {
+    type Output = Mask;
+
+    fn not(self) -> Mask {
+        Mask(!self.0)
+    }
+}
+
+impl BitAnd for Mask {
+    type Output = Mask;
+
+    fn bitand(self, other: Mask) -> Mask {
+        Mask(self.0 & other.0)
+    }
+}
+
+impl BitOr for Mask {
+    type Output = Mask;
+
+    fn bitor(self, other: Mask) -> Mask {
+        Mask(self.0 | other.0)
+    }
+}
+
+impl BitXor for Mask {
+    type Output = Mask;
+
+    fn bitxor(self, other: Mask) -> Mask {
+        Mask(self.0 ^ other.0)
+    }
+}
+
+//shift amount is a different type
+//a shift like the one on u32, except that shifting by 32 or more empties the mask instead of
+//overflowing: every flag has been shifted out
+impl Shl<u32> for Mask {
+    type Output = Mask;
+
+    fn shl(self, bits: u32) -> Mask {
+        Mask(self.0.checked_shl(bits).unwrap_or(0))
+    }
+}
+
+impl Shr<u32> for Mask {
+    type Output = Mask;
+
+    fn shr(self, bits: u32) -> Mask {
+        Mask(self.0.checked_shr(bits).unwrap_or(0))
+    }
+}
+
+forward_ref_unop!(impl Not, not for Mask);
+forward_ref_binop!(impl BitAnd, bitand for Mask, Mask);
+forward_ref_binop!(impl BitOr, bitor for Mask, Mask);
+forward_ref_binop!(impl BitXor, bitxor for Mask, Mask);
+forward_ref_binop!(impl Shl, shl for Mask, u32);
+forward_ref_binop!(impl Shr, shr for Mask, u32);
+
+//op_ref: clippy suggests dropping the & on Copy types, here the impls are the point
+#[allow(clippy::op_ref)]
+fn vectors() {
+    let a = v(1, 2);
+    let b = v(3, -4);
+
+    assert_eq!(a + b, v(4, -2));
+    assert_eq!(a - b, v(-2, 6));
+    assert_eq!(-a, v(-1, -2));
+    assert_eq!(a * 3, v(3, 6));
+    assert_eq!(3 * a, v(3, 6));
+    assert_eq!(a * b, -5);
+
+    //reference operands: nothing is moved or copied explicitly
+    assert_eq!(&a + &b, a + b);
+    assert_eq!(a + &b, &a + b);
+    assert_eq!(&a * 3, a * 3);
+    assert_eq!(-&a, -a);
+
+    let mut c = a;
+    c += b;
+    c -= a;
+    c *= 2;
+    assert_eq!(c, v(6, -8));
+
+    c[0] = 10;
+    c[1] += 1;
+    assert_eq!((c[0], c[1]), (10, -7));
+
+    //Ord: sort by length
+    let mut vectors = vec![v(3, 4), v(1, 0), v(0, -2), v(-1, 0)];
+    vectors.sort();
+    assert_eq!(vectors, [v(-1, 0), v(1, 0), v(0, -2), v(3, 4)]);
+    assert!(v(1, 1) < v(0, 2));
+    assert_eq!(vectors.iter().max(), Some(&v(3, 4)));
+}
+
+#[allow(clippy::op_ref)]
+fn matrices() {
+    let rotate = Matrix::new(0, -1, 1, 0);
+    let scale = Matrix::new(2, 0, 0, 3);
+
+    assert_eq!(rotate * v(1, 0), v(0, 1));
+    assert_eq!(scale * v(1, 1), v(2, 3));
+    //matrix multiplication is not commutative
+    assert_eq!((scale * rotate) * v(1, 0), v(0, 3));
+    assert_eq!((rotate * scale) * v(1, 0), v(0, 2));
+    assert_ne!(scale * rotate, rotate * scale);
+
+    let mut m = Matrix::IDENTITY;
+    for _ in 0..4 {
+        m *= rotate;
+    }
+    assert_eq!(m, Matrix::IDENTITY);
+    assert_eq!(&rotate * &rotate, Matrix::IDENTITY * -1);
+
+    m[(0, 1)] = 5;
+    assert_eq!(m.rows, [[1, 5], [0, 1]]);
+    assert_eq!(m + m, m * 2);
+}
+
+fn money() {
+    let price = Money::new(2, 50);
+    let mut wallet = Money::new(10, 0);
+
+    let total = price * 3;
+    assert_eq!(total, Money::new(7, 50));
+    wallet -= total;
+    assert_eq!(wallet, Money::new(2, 50));
+    wallet += Money::new(0, 25);
+    assert_eq!(wallet.cents, 275);
+    assert_eq!(-price, Money { cents: -250 });
+    assert_eq!(-&price, -price);
+
+    //Not allowed: Mul<Money> for Money is not implemented
+    // let _ = price * price;
+    //error[E0308]: mismatched types
+
+    let items = [Money::new(1, 0), Money::new(0, 99), Money::new(3, 1)];
+    assert_eq!(items.iter().copied().sum::<Money>(), Money::new(5, 0));
+    assert_eq!(items.iter().max(), Some(&Money::new(3, 1)));
+
+    //Hash + Eq: usable as a set element
+    let unique: HashSet<Money> = [price, price, total].iter().copied().collect();
+    assert_eq!(unique.len(), 2);
+}
+
+#[allow(clippy::op_ref)]
+fn masks() {
+    let read = Mask(0b001);
+    let write = Mask(0b010);
+    let exec = Mask(0b100);
+
+    let rw = read | write;
+    assert_eq!(rw & write, write);
+    assert_eq!(rw ^ read, write);
+    assert_eq!(!rw & exec, exec);
+    assert_eq!(&rw | &exec, Mask(0b111));
+    assert_eq!(!&rw, !rw);
+    assert_eq!(&read << 2, read << 2);
+    assert_eq!(exec >> &2, &exec >> &2);
+    assert_eq!(read << 2, exec);
+    assert_eq!(exec >> 2, read);
+    //bits shifted past either end are gone
+    assert_eq!(read >> 1, Mask(0));
+    assert_eq!(Mask(1 << 31) << 1, Mask(0));
+    assert_eq!(exec << 32, Mask(0));
+}
+
+/*
+ * Property-style checks: laws every sensible impl should satisfy,
+ * checked over a grid of sample values instead of random ones
+ */
+fn algebraic_laws() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    let samples: Vec<i64> = vec![-7, -1, 0, 1, 2, 5];
+    let vectors: Vec<Vector2> = samples
+        .iter()
+        .flat_map(|&x| samples.iter().map(move |&y| v(x, y)))
+        .collect();
+    let zero = Vector2::default();
+
+    for &a in &vectors {
+        //identity and inverse
+        assert_eq!(a + zero, a);
+        assert_eq!(a + -a, zero);
+        assert_eq!(a * 1, a);
+        assert_eq!(Matrix::IDENTITY * a, a);
+        for &b in &vectors {
+            //commutativity
+            assert_eq!(a + b, b + a);
+            assert_eq!(a * b, b * a);
+            //subtraction is adding the inverse
+            assert_eq!(a - b, a + -b);
+            //Ord agrees with Eq, and is antisymmetric
+            assert_eq!(a.cmp(&b) == std::cmp::Ordering::Equal, a == b);
+            assert_eq!(a.cmp(&b), b.cmp(&a).reverse());
+            for &k in &samples {
+                //distributivity of scalar multiplication
+                assert_eq!((a + b) * k, a * k + b * k);
+                //linearity of matrix application
+                let m = Matrix::new(k, a.x, b.y, 1);
+                assert_eq!(m * (a + b), m * a + m * b);
+            }
+        }
+    }
+
+    //associativity
+    for &a in vectors.iter().step_by(5) {
+        for &b in vectors.iter().step_by(7) {
+            for &c in vectors.iter().step_by(3) {
+                assert_eq!((a + b) + c, a + (b + c));
+                let (ma, mb, mc) = (
+                    Matrix::new(a.x, a.y, b.x, b.y),
+                    Matrix::new(b.y, c.x, a.y, c.y),
+                    Matrix::new(c.x, c.y, a.x, b.x),
+                );
+                assert_eq!((ma * mb) * mc, ma * (mb * mc));
+                assert_eq!((ma * mb) * c, ma * (mb * c));
+            }
+        }
+    }
+
+    //De Morgan's laws
+    for a in (0..16).map(Mask) {
+        for b in (0..16).map(Mask) {
+            assert_eq!(!(a & b), !a | !b);
+            assert_eq!(!(a | b), !a & !b);
+            assert_eq!(a ^ b ^ b, a);
+        }
+    }
+
+    //equal values must have equal hashes
+    fn hash<T: Hash>(value: &T) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+    assert_eq!(hash(&(v(1, 2) + v(3, 4))), hash(&v(4, 6)));
+    assert_eq!(hash(&(Money::new(1, 0) * 2)), hash(&Money::new(2, 0)));
+}
+
+pub fn main() {
+    vectors();
+    matrices();
+    money();
+    masks();
+    algebraic_laws();
+}
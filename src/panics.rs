@@ -0,0 +1,254 @@
+/*
+ * Continuation of error_handling::panic
+ * panic!: unrecoverable error in the current thread
+ * 1. the panic hook runs (default: prints "thread 'main' panicked at ..." and maybe a backtrace)
+ * 2. the stack unwinds: destructors of every live value run, innermost frame first
+ * 3. unwinding stops at catch_unwind, or at the thread boundary (JoinHandle::join returns Err)
+ *
+ * With panic = "abort" in [profile.*] there is no unwinding: the process aborts after the hook,
+ * and catch_unwind catches nothing. catch_unwind is not try/catch: use it at FFI boundaries,
+ * in thread pools and test harnesses, not for regular error handling (that's Result)
+ */
+
+use crate::traits::Droppable;
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Mutex, PoisonError};
+
+//messages recorded by the hook, instead of printing them
+static PANICS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+//payload is Box<dyn Any + Send>: &'static str for literals, String for formatted messages
+fn payload_text(payload: &(dyn Any + Send)) -> String {
+    if let Some(text) = payload.downcast_ref::<&str>() {
+        text.to_string()
+    } else if let Some(text) = payload.downcast_ref::<String>() {
+        text.clone()
+    } else {
+        String::from("<non-string payload>")
+    }
+}
+
+//Runs f with a recording hook installed, restores the previous hook, returns the messages
+fn recording_panics<T, F: FnOnce() -> T>(f: F) -> (T, Vec<String>) {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(|info| {
+        let location = info.location().map_or(String::new(), |location| {
+            format!(" at line {}", location.line())
+        });
+        //the hook must not panic itself: ignore poisoning
+        let mut panics = PANICS.lock().unwrap_or_else(PoisonError::into_inner);
+        panics.push(format!("{}{}", payload_text(info.payload()), location));
+    }));
+
+    let result = f();
+
+    panic::set_hook(previous);
+    let messages = std::mem::take(&mut *PANICS.lock().unwrap_or_else(PoisonError::into_inner));
+    (result, messages)
+}
+
+fn hooks() {
+    let (result, messages) = recording_panics(|| {
+        panic::catch_unwind(|| {
+            let divisor = "0".parse::<i32>().unwrap();
+            if divisor == 0 {
+                panic!("division by {}", divisor);
+            }
+        })
+    });
+    assert!(result.is_err());
+    assert_eq!(messages.len(), 1);
+    assert!(messages[0].starts_with("division by 0 at line "));
+
+    //hook runs in the panicking thread, before unwinding; its output replaces the default one
+    let ((), messages) = recording_panics(|| {
+        let handle = std::thread::spawn(|| panic!("in a thread"));
+        assert!(handle.join().is_err());
+    });
+    assert_eq!(messages.len(), 1);
+    assert!(messages[0].starts_with("in a thread"));
+}
+
+fn catching_and_resuming() {
+    let ((), messages) = recording_panics(|| {
+        //Ok: the closure's value
+        assert_eq!(panic::catch_unwind(|| 40 + 2).unwrap(), 42);
+
+        //Err: the payload
+        let literal = panic::catch_unwind(|| panic!("literal")).unwrap_err();
+        assert_eq!(literal.downcast_ref::<&str>(), Some(&"literal"));
+        //with runtime arguments the message is formatted: the payload is a String
+        let code = "4".parse::<i32>().unwrap();
+        let formatted = panic::catch_unwind(|| panic!("{}-{}", code, 2)).unwrap_err();
+        assert_eq!(formatted.downcast_ref::<String>().unwrap(), "4-2");
+
+        //any Send + 'static value can be a payload
+        #[derive(Debug, PartialEq)]
+        struct Fatal {
+            code: i32,
+        }
+        let custom = panic::catch_unwind(|| panic::panic_any(Fatal { code: 7 })).unwrap_err();
+        assert_eq!(custom.downcast_ref::<Fatal>(), Some(&Fatal { code: 7 }));
+        assert_eq!(payload_text(custom.as_ref()), "<non-string payload>");
+
+        //resume_unwind: continue unwinding with the same payload, without calling the hook
+        let inner_seen = std::cell::Cell::new(false);
+        let outer = panic::catch_unwind(AssertUnwindSafe(|| {
+            if let Err(payload) = panic::catch_unwind(|| panic!("rethrown")) {
+                inner_seen.set(true);
+                panic::resume_unwind(payload);
+            }
+        }))
+        .unwrap_err();
+        assert!(inner_seen.get());
+        assert_eq!(payload_text(outer.as_ref()), "rethrown");
+    });
+    //literal, formatted, Fatal and rethrown: resume_unwind added nothing
+    assert_eq!(messages.len(), 4);
+}
+
+fn drop_while_unwinding() {
+    use std::cell::RefCell;
+
+    //records drops, then lets the wrapped Droppable print its own message
+    struct Watched<'a> {
+        inner: Droppable,
+        log: &'a RefCell<Vec<String>>,
+    }
+
+    impl Drop for Watched<'_> {
+        fn drop(&mut self) {
+            let state = if std::thread::panicking() {
+                "unwinding"
+            } else {
+                "normal"
+            };
+            self.log
+                .borrow_mut()
+                .push(format!("{} ({})", self.inner.name, state));
+        }
+    }
+
+    let log = RefCell::new(Vec::new());
+    let watch = |name| Watched {
+        inner: Droppable { name },
+        log: &log,
+    };
+
+    let (result, _) = recording_panics(|| {
+        panic::catch_unwind(AssertUnwindSafe(|| {
+            let _outer = watch("outer");
+            {
+                let _scoped = watch("scoped");
+            }
+            let _first = watch("first");
+            let _second = watch("second");
+            panic!("unwind");
+        }))
+    });
+    assert!(result.is_err());
+    //reverse order of declaration, like a normal scope exit
+    assert_eq!(
+        *log.borrow(),
+        [
+            "scoped (normal)",
+            "second (unwinding)",
+            "first (unwinding)",
+            "outer (unwinding)"
+        ]
+    );
+    //a panic inside Drop while already unwinding aborts the process
+}
+
+/*
+ * UnwindSafe: marker for values that can't be observed in a broken state after a caught panic
+ * &mut T and &RefCell<T> are not UnwindSafe: the closure could have been halfway through
+ * changing them. AssertUnwindSafe says "I checked, or I don't care"
+ */
+fn unwind_safety() {
+    struct Accounts {
+        balances: Vec<i64>,
+    }
+
+    impl Accounts {
+        fn transfer(&mut self, from: usize, to: usize, amount: i64) {
+            self.balances[from] -= amount;
+            //panics on a bad index: the debit above already happened
+            self.balances[to] += amount;
+        }
+
+        fn total(&self) -> i64 {
+            self.balances.iter().sum()
+        }
+    }
+
+    let mut accounts = Accounts {
+        balances: vec![100, 50],
+    };
+
+    //Not allowed: &mut Accounts is not UnwindSafe
+    // panic::catch_unwind(|| accounts.transfer(0, 5, 30));
+    //error[E0277]: the type `&mut Accounts` may not be safely transferred across an unwind boundary
+
+    let (result, messages) =
+        recording_panics(|| panic::catch_unwind(AssertUnwindSafe(|| accounts.transfer(0, 5, 30))));
+    assert!(result.is_err());
+    assert!(messages[0].starts_with("index out of bounds"));
+    //exactly the broken invariant UnwindSafe warns about: 30 disappeared
+    assert_eq!(accounts.balances, [70, 50]);
+    assert_eq!(accounts.total(), 120);
+
+    //owned and shared immutable data is UnwindSafe: no wrapper needed
+    let numbers = [1, 2, 3];
+    let sum = panic::catch_unwind(|| numbers.iter().sum::<i32>());
+    assert_eq!(sum.unwrap(), 6);
+}
+
+/*
+ * Mutex poisoning: if a thread panics while holding the lock, the mutex is marked poisoned
+ * and lock() returns Err(PoisonError) to everybody after, the same concern as UnwindSafe.
+ * The guard is still inside the error: the data can be inspected, repaired and used
+ */
+fn poisoned_mutex() {
+    use std::sync::Arc;
+    use std::thread;
+
+    let queue = Arc::new(Mutex::new(vec![1, 2, 3]));
+
+    let ((), messages) = recording_panics(|| {
+        let queue = Arc::clone(&queue);
+        let worker = thread::spawn(move || {
+            let mut items = queue.lock().unwrap();
+            items.push(4);
+            panic!("worker crashed mid-update");
+        });
+        assert!(worker.join().is_err());
+    });
+    assert_eq!(messages.len(), 1);
+
+    assert!(queue.is_poisoned());
+    let error = queue.lock().unwrap_err();
+    //recover: take the guard out of the error, fix the data
+    let mut items = error.into_inner();
+    assert_eq!(*items, [1, 2, 3, 4]);
+    items.retain(|&item| item <= 3);
+    drop(items);
+
+    //still poisoned until cleared explicitly
+    assert!(queue.lock().is_err());
+    queue.clear_poison();
+    assert_eq!(*queue.lock().unwrap(), [1, 2, 3]);
+
+    //common shortcut: ignore poisoning altogether
+    let len = queue.lock().unwrap_or_else(PoisonError::into_inner).len();
+    assert_eq!(len, 3);
+}
+
+pub fn main() {
+    hooks();
+    catching_and_resuming();
+    drop_while_unwinding();
+    unwind_safety();
+    poisoned_mutex();
+}
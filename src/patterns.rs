@@ -0,0 +1,473 @@
+/*
+ * Patterns appear in: let, function/closure params, match arms, if let, while let, for, let else
+ * Irrefutable patterns (always match): let, params, for
+ * Refutable patterns (might not match): match arms, if let, while let, let else
+ * Building blocks:
+ * - literals, ranges (a..=b), wildcard _, rest ..
+ * - bindings: x, ref x, ref mut x, x @ subpattern
+ * - destructuring: tuples, structs, enums, slices, references (&x)
+ * - alternatives: p1 | p2, guards: if condition (match only)
+ */
+
+fn slice_patterns() {
+    fn describe(xs: &[i32]) -> String {
+        match xs {
+            [] => "empty".to_owned(),
+            [x] => format!("one: {}", x),
+            [first, second] => format!("two: {} {}", first, second),
+            //.. matches any number of elements, only once per slice pattern
+            [first, .., last] if first == last => format!("same ends: {}", first),
+            //rest can be bound to a subslice with @
+            [first, middle @ .., last] => {
+                format!("{} then {} more then {}", first, middle.len(), last)
+            }
+        }
+    }
+
+    assert_eq!(describe(&[]), "empty");
+    assert_eq!(describe(&[1]), "one: 1");
+    assert_eq!(describe(&[1, 2]), "two: 1 2");
+    assert_eq!(describe(&[7, 0, 0, 7]), "same ends: 7");
+    assert_eq!(describe(&[1, 2, 3, 4, 5]), "1 then 3 more then 5");
+
+    //arrays have known length: pattern without .. is irrefutable
+    let [r, g, b] = [255u8, 128, 0];
+    assert_eq!((r, g, b), (255, 128, 0));
+    let [head, tail @ ..] = [1, 2, 3];
+    assert_eq!(head, 1);
+    assert_eq!(tail, [2, 3]);
+
+    //recursion on slices
+    fn sum(xs: &[i32]) -> i32 {
+        match xs {
+            [] => 0,
+            [head, tail @ ..] => head + sum(tail),
+        }
+    }
+    assert_eq!(sum(&[1, 2, 3, 4]), 10);
+
+    //slices of strings
+    fn command(words: &[&str]) -> Option<String> {
+        match words {
+            ["go", direction] => Some(format!("going {}", direction)),
+            ["pick", "up", item] | ["take", item] => Some(format!("taking {}", item)),
+            ["drop", items @ ..] if !items.is_empty() => {
+                Some(format!("dropping {}", items.join(", ")))
+            }
+            _ => None,
+        }
+    }
+    assert_eq!(command(&["go", "north"]), Some("going north".to_owned()));
+    assert_eq!(
+        command(&["pick", "up", "key"]),
+        Some("taking key".to_owned())
+    );
+    assert_eq!(command(&["take", "key"]), Some("taking key".to_owned()));
+    assert_eq!(
+        command(&["drop", "a", "b"]),
+        Some("dropping a, b".to_owned())
+    );
+    assert_eq!(command(&["drop"]), None);
+}
+
+fn nested_enums() {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Color {
+        Rgb(u8, u8, u8),
+        Hsv(u16, u8, u8),
+    }
+
+    #[derive(Debug)]
+    enum Shape {
+        Circle { radius: u32, fill: Option<Color> },
+        Square(u32, Option<Color>),
+    }
+
+    fn describe(shape: &Shape) -> String {
+        match shape {
+            //several levels at once: enum -> struct variant -> Option -> tuple variant
+            Shape::Circle {
+                fill: Some(Color::Rgb(255, 0, 0)),
+                ..
+            } => "red circle".to_owned(),
+            Shape::Circle {
+                radius,
+                fill: Some(Color::Rgb(r, g, b)),
+            } => format!("circle {} of rgb({}, {}, {})", radius, r, g, b),
+            Shape::Circle { radius, fill: None } => format!("empty circle {}", radius),
+            //alternatives inside nested patterns
+            Shape::Circle {
+                fill: Some(Color::Hsv(..)),
+                ..
+            }
+            | Shape::Square(_, Some(Color::Hsv(..))) => "hsv shape".to_owned(),
+            Shape::Square(side @ 0..=9, _) => format!("small square {}", side),
+            Shape::Square(side, fill) => format!("square {} {:?}", side, fill),
+        }
+    }
+
+    let red = Some(Color::Rgb(255, 0, 0));
+    assert_eq!(
+        describe(&Shape::Circle {
+            radius: 1,
+            fill: red
+        }),
+        "red circle"
+    );
+    assert_eq!(
+        describe(&Shape::Circle {
+            radius: 2,
+            fill: Some(Color::Rgb(1, 2, 3))
+        }),
+        "circle 2 of rgb(1, 2, 3)"
+    );
+    assert_eq!(
+        describe(&Shape::Circle {
+            radius: 3,
+            fill: None
+        }),
+        "empty circle 3"
+    );
+    assert_eq!(
+        describe(&Shape::Square(1, Some(Color::Hsv(0, 0, 0)))),
+        "hsv shape"
+    );
+    assert_eq!(describe(&Shape::Square(5, red)), "small square 5");
+    assert_eq!(describe(&Shape::Square(10, None)), "square 10 None");
+}
+
+fn matches_macro() {
+    #[derive(Debug)]
+    enum Token {
+        Number(i64),
+        Plus,
+        Minus,
+        Ident(String),
+    }
+
+    //matches!(value, pattern) is a shortcut for match with `true` and `_ => false` arms
+    fn is_operator(token: &Token) -> bool {
+        matches!(token, Token::Plus | Token::Minus)
+    }
+
+    let tokens = [
+        Token::Number(1),
+        Token::Plus,
+        Token::Ident("x".to_owned()),
+        Token::Minus,
+        Token::Number(-5),
+    ];
+    assert_eq!(tokens.iter().filter(|t| is_operator(t)).count(), 2);
+
+    //guards are allowed too
+    let negatives = tokens
+        .iter()
+        .filter(|t| matches!(t, Token::Number(n) if *n < 0))
+        .count();
+    assert_eq!(negatives, 1);
+
+    //works without PartialEq
+    assert!(matches!(tokens[2], Token::Ident(ref name) if name == "x"));
+    assert!(matches!(tokens[0], Token::Number(1..=9)));
+}
+
+fn range_patterns() {
+    fn classify(c: char) -> &'static str {
+        match c {
+            'a' | 'e' | 'i' | 'o' | 'u' => "vowel",
+            'a'..='z' => "consonant",
+            'A'..='Z' => "upper",
+            '0'..='9' => "digit",
+            //ranges work for any char, including non-ASCII ones
+            'α'..='ω' => "greek",
+            _ => "other",
+        }
+    }
+    assert_eq!(classify('e'), "vowel");
+    assert_eq!(classify('x'), "consonant");
+    assert_eq!(classify('Q'), "upper");
+    assert_eq!(classify('7'), "digit");
+    assert_eq!(classify('λ'), "greek");
+    assert_eq!(classify('!'), "other");
+
+    //integer ranges have to cover the whole type for exhaustiveness
+    fn grade(score: u8) -> char {
+        match score {
+            90..=100 => 'A',
+            80..=89 => 'B',
+            70..=79 => 'C',
+            0..=69 => 'F',
+            //without this arm: error, 101..=255 not covered
+            101..=u8::MAX => '?',
+        }
+    }
+    assert_eq!(grade(95), 'A');
+    assert_eq!(grade(80), 'B');
+    assert_eq!(grade(12), 'F');
+    assert_eq!(grade(200), '?');
+
+    //constants can be used as range bounds
+    const FREEZING: i32 = 0;
+    const BOILING: i32 = 100;
+    fn state(celsius: i32) -> &'static str {
+        match celsius {
+            i32::MIN..=FREEZING => "ice",
+            BOILING..=i32::MAX => "steam",
+            _ => "water",
+        }
+    }
+    assert_eq!(state(-10), "ice");
+    assert_eq!(state(0), "ice");
+    assert_eq!(state(42), "water");
+    assert_eq!(state(100), "steam");
+}
+
+/*
+ * Binding modes:
+ * matching a non-reference pattern against a reference switches default binding mode
+ * & -> bindings become `ref`, &mut -> bindings become `ref mut`
+ * so `match &value { Some(x) => .. }` binds x: &T without writing `&Some(ref x)`
+ */
+fn binding_modes() {
+    let pair = (String::from("cake"), 42);
+
+    //explicit: & in pattern + ref binding
+    let &(ref name, number) = &pair;
+    assert_eq!(name, "cake");
+    assert_eq!(number, 42);
+
+    //default binding mode: same types
+    let (name, number) = &pair;
+    let _: &String = name;
+    let _: &i32 = number;
+
+    //by value would move the String out of pair
+    // let (moved, _) = pair;
+    //by ref keeps pair usable
+    let (ref borrowed, _) = pair;
+    assert_eq!(borrowed, "cake");
+    assert_eq!(pair.0, "cake");
+
+    //&mut: bindings become mutable references
+    let mut maybe = Some(String::from("cake"));
+    if let Some(s) = &mut maybe {
+        s.push_str(" is a lie");
+    }
+    assert_eq!(maybe.as_deref(), Some("cake is a lie"));
+
+    //&pattern dereferences, works only for Copy data
+    let numbers = [1, 2, 3];
+    let doubled: Vec<i32> = numbers.iter().map(|&x| x * 2).collect();
+    assert_eq!(doubled, [2, 4, 6]);
+
+    //@ binds the whole value while also testing it
+    let message = Some(7);
+    let text = match message {
+        Some(n @ 1..=9) => format!("digit {}", n),
+        Some(n) => format!("number {}", n),
+        None => "nothing".to_owned(),
+    };
+    assert_eq!(text, "digit 7");
+}
+
+fn let_else() {
+    //let PATTERN = EXPR else { must diverge };
+    //bindings are available after the statement, unlike if let
+    fn parse_pair(input: &str) -> Result<(String, u32), String> {
+        let Some((key, value)) = input.split_once('=') else {
+            return Err(format!("no '=' in {:?}", input));
+        };
+        let Ok(value) = value.trim().parse::<u32>() else {
+            return Err(format!("bad value for {}", key.trim()));
+        };
+        Ok((key.trim().to_owned(), value))
+    }
+
+    assert_eq!(parse_pair("age = 42"), Ok(("age".to_owned(), 42)));
+    assert_eq!(parse_pair("age"), Err("no '=' in \"age\"".to_owned()));
+    assert_eq!(parse_pair("age = old"), Err("bad value for age".to_owned()));
+
+    //slice patterns + let else
+    fn first_two(xs: &[i32]) -> i32 {
+        let [a, b, ..] = xs else {
+            return -1;
+        };
+        a + b
+    }
+    assert_eq!(first_two(&[1, 2, 3]), 3);
+    assert_eq!(first_two(&[1]), -1);
+
+    //else block can panic, break, continue too
+    let mut total = 0;
+    for item in ["1", "x", "3"] {
+        let Ok(n) = item.parse::<i32>() else {
+            continue;
+        };
+        total += n;
+    }
+    assert_eq!(total, 4);
+}
+
+fn refutability() {
+    let opt: Option<i32> = Some(42);
+
+    //Not allowed: let requires an irrefutable pattern, None is not covered
+    // let Some(x) = opt;
+    //error[E0005]: refutable pattern in local binding
+
+    //Not allowed: for the same reason in function params
+    // fn f(Some(x): Option<i32>) {}
+
+    //Allowed: refutable pattern with a fallback arm or a diverging else
+    let x = match opt {
+        Some(x) if x > 0 => x,
+        Some(x) => -x,
+        None => 0,
+    };
+    let Some(y) = opt else { unreachable!() };
+    assert_eq!(x, y);
+
+    //Allowed, but warns: irrefutable pattern in if let is pointless
+    #[allow(irrefutable_let_patterns)]
+    let z = if let w = 42 { w } else { unreachable!() };
+    assert_eq!(z, 42);
+
+    //Not allowed: match must be exhaustive
+    // match opt {
+    //     Some(x) => x,
+    // }
+    //error[E0004]: non-exhaustive patterns: `None` not covered
+
+    //tuple struct patterns are irrefutable: there is nothing else to match
+    struct Meters(f64);
+    let Meters(m) = Meters(1.5);
+    assert_eq!(m, 1.5);
+}
+
+/*
+ * Exhaustive matching over a recursive type:
+ * adding a variant to Expr makes every non-exhaustive match a compile error
+ */
+fn expression_evaluator() {
+    #[derive(Debug)]
+    enum Expr {
+        Num(f64),
+        Neg(Box<Expr>),
+        Bin(Box<Expr>, Op, Box<Expr>),
+        If {
+            cond: Box<Expr>,
+            then: Box<Expr>,
+            otherwise: Box<Expr>,
+        },
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    enum Op {
+        Add,
+        Sub,
+        Mul,
+        Div,
+    }
+
+    fn eval(expr: &Expr) -> Result<f64, String> {
+        match expr {
+            Expr::Num(n) => Ok(*n),
+            Expr::Neg(inner) => Ok(-eval(inner)?),
+            //nested pattern: division by literal zero is caught before evaluation
+            Expr::Bin(_, Op::Div, rhs) if matches!(**rhs, Expr::Num(n) if n == 0.0) => {
+                Err("division by zero".to_owned())
+            }
+            Expr::Bin(lhs, op, rhs) => {
+                let (l, r) = (eval(lhs)?, eval(rhs)?);
+                match op {
+                    Op::Add => Ok(l + r),
+                    Op::Sub => Ok(l - r),
+                    Op::Mul => Ok(l * r),
+                    Op::Div if r == 0.0 => Err("division by zero".to_owned()),
+                    Op::Div => Ok(l / r),
+                }
+            }
+            Expr::If {
+                cond,
+                then,
+                otherwise,
+            } => {
+                if eval(cond)? != 0.0 {
+                    eval(then)
+                } else {
+                    eval(otherwise)
+                }
+            }
+        }
+    }
+
+    //constant folding: patterns over boxed children, rebuilds the tree
+    fn simplify(expr: Expr) -> Expr {
+        match expr {
+            Expr::Neg(inner) => match simplify(*inner) {
+                Expr::Neg(double) => *double,
+                other => Expr::Neg(Box::new(other)),
+            },
+            Expr::Bin(lhs, op, rhs) => match (simplify(*lhs), op, simplify(*rhs)) {
+                (Expr::Num(l), Op::Add, Expr::Num(r)) => Expr::Num(l + r),
+                (Expr::Num(l), Op::Mul, Expr::Num(r)) => Expr::Num(l * r),
+                (x, Op::Add, Expr::Num(z)) | (Expr::Num(z), Op::Add, x) if z == 0.0 => x,
+                (x, Op::Mul, Expr::Num(o)) | (Expr::Num(o), Op::Mul, x) if o == 1.0 => x,
+                (l, op, r) => Expr::Bin(Box::new(l), op, Box::new(r)),
+            },
+            other => other,
+        }
+    }
+
+    fn num(n: f64) -> Box<Expr> {
+        Box::new(Expr::Num(n))
+    }
+
+    //-(2 * (3 + 4))
+    let expr = Expr::Neg(Box::new(Expr::Bin(
+        num(2.0),
+        Op::Mul,
+        Box::new(Expr::Bin(num(3.0), Op::Add, num(4.0))),
+    )));
+    assert_eq!(eval(&expr), Ok(-14.0));
+
+    let div = Expr::Bin(
+        num(1.0),
+        Op::Div,
+        Box::new(Expr::Bin(num(1.0), Op::Sub, num(1.0))),
+    );
+    assert_eq!(eval(&div), Err("division by zero".to_owned()));
+    let literal_div = Expr::Bin(num(1.0), Op::Div, num(0.0));
+    assert_eq!(eval(&literal_div), Err("division by zero".to_owned()));
+
+    let branch = Expr::If {
+        cond: Box::new(Expr::Bin(num(1.0), Op::Sub, num(1.0))),
+        then: num(10.0),
+        otherwise: num(20.0),
+    };
+    assert_eq!(eval(&branch), Ok(20.0));
+
+    //--(x * 1 + 0) with a non-constant x keeps the If but drops the noise
+    let noisy = Expr::Neg(Box::new(Expr::Neg(Box::new(Expr::Bin(
+        Box::new(Expr::Bin(Box::new(branch), Op::Mul, num(1.0))),
+        Op::Add,
+        num(0.0),
+    )))));
+    let simplified = simplify(noisy);
+    assert!(matches!(simplified, Expr::If { .. }));
+    assert_eq!(eval(&simplified), Ok(20.0));
+    assert!(
+        matches!(simplify(expr), Expr::Neg(ref inner) if matches!(**inner, Expr::Num(n) if n == 14.0))
+    );
+}
+
+pub fn main() {
+    slice_patterns();
+    nested_enums();
+    matches_macro();
+    range_patterns();
+    binding_modes();
+    let_else();
+    refutability();
+    expression_evaluator();
+}
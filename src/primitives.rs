@@ -1,4 +1,4 @@
-/**
+/*
  * signed/unsigned ints, floats, char, bool, unit
  * type specifiers in literals
  * _ as separators in literals
@@ -50,7 +50,7 @@ fn literals() {
     assert_eq!(c, 'A');
 
     let b: bool = false;
-    assert_eq!(b, false);
+    assert!(!b);
 
     let unit: () = ();
     //Does not implement Display
@@ -113,7 +113,7 @@ fn tuples() {
     assert_eq!(pair, (true, 42));
 
     //Accessing pair elements
-    assert_eq!(pair.0, true);
+    assert!(pair.0);
     assert_eq!(pair.1, 42);
 }
 
@@ -3,6 +3,8 @@
  * Numbered/named parameters
  * Formatting: :b, width, hex/binary/oct, precision
  * Display vs Debug: why, {} and {:?}
+ * Custom impls that honour the flags: width, fill, alignment, precision, +, #
+ * Debug builders, hex/binary/pointer traits, fmt::Write and format_args!
  */
 fn different_prints() {
     //format creates formatted String
@@ -26,6 +28,8 @@ fn different_prints() {
     println!();
 }
 
+//literals as arguments: the point is how they are referred to
+#[allow(clippy::print_literal)]
 fn print_with_positional_args() {
     //Numbered arguments
     println!(
@@ -59,6 +63,8 @@ fn print_with_special_formatting() {
 }
 
 fn debug_trait() {
+    //only ever printed: Debug doesn't count as reading the field
+    #[allow(dead_code)]
     #[derive(Debug)]
     struct DebugPrintable(i32);
 
@@ -83,22 +89,31 @@ fn display_trait() {
 
     impl fmt::Display for List {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-            let vec = &self.0;
+            use std::fmt::Write;
 
-            write!(f, "[")?;
+            //writing piece by piece straight into f would ignore width and fill:
+            //build the whole text first, String implements fmt::Write
+            let mut text = String::new();
+            write!(text, "[")?;
 
-            for (count, v) in vec.iter().enumerate() {
+            for (count, v) in self.0.iter().enumerate() {
                 if count != 0 {
-                    write!(f, ", ")?;
+                    write!(text, ", ")?;
                 }
-                write!(f, "{}", v)?;
+                write!(text, "{}", v)?;
             }
 
-            write!(f, "]")
+            write!(text, "]")?;
+            //pad applies width, fill and alignment (and precision: cuts the text)
+            f.pad(&text)
         }
     }
 
     println!("Custom implemented display: {}", List(vec![1, 2]));
+    assert_eq!(format!("{}", List(vec![1, 2])), "[1, 2]");
+    assert_eq!(format!("{:>10}", List(vec![1, 2])), "    [1, 2]");
+    assert_eq!(format!("{:*^10}", List(vec![1, 2])), "**[1, 2]**");
+    assert_eq!(format!("{:.4}|", List(vec![1, 2, 3])), "[1, |");
 
     println!();
 }
@@ -110,6 +125,305 @@ fn formatting() {
     println!("Format as oct {:.2}", 234.4567);
 }
 
+fn formatter_flags() {
+    use std::fmt::{self, Write};
+
+    //the easy way for text: pad() does width, fill, alignment, and precision as a cut
+    struct Name(&'static str);
+
+    impl fmt::Display for Name {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.pad(self.0)
+        }
+    }
+
+    assert_eq!(format!("[{}]", Name("Ferris")), "[Ferris]");
+    assert_eq!(format!("[{:>8}]", Name("Ferris")), "[  Ferris]");
+    assert_eq!(format!("[{:-^10}]", Name("Ferris")), "[--Ferris--]");
+    assert_eq!(format!("[{:.3}]", Name("Ferris")), "[Fer]");
+    //text aligns left by default
+    assert_eq!(format!("[{:5.3}]", Name("Ferris")), "[Fer  ]");
+
+    //a number-like type: precision means digits, so every flag is handled by hand
+    struct Celsius(f64);
+
+    impl fmt::Display for Celsius {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            //{:.2}: digits after the point, one when not asked
+            let precision = f.precision().unwrap_or(1);
+            //{:#}: the unit spelled out
+            let unit = if f.alternate() {
+                " degrees Celsius"
+            } else {
+                "°C"
+            };
+            //{:+}: a sign for positive values too, left to f64's own Display: -0.0 keeps its minus
+            let text = if f.sign_plus() {
+                format!("{:+.*}{}", precision, self.0, unit)
+            } else {
+                format!("{:.*}{}", precision, self.0, unit)
+            };
+
+            //width counts characters, not bytes: ° is two bytes
+            let padding = f.width().unwrap_or(0).saturating_sub(text.chars().count());
+            //numbers align right by default; center puts the odd one on the right, like std
+            let (before, after) = match f.align() {
+                Some(fmt::Alignment::Left) => (0, padding),
+                Some(fmt::Alignment::Center) => (padding / 2, padding - padding / 2),
+                Some(fmt::Alignment::Right) | None => (padding, 0),
+            };
+            let fill = f.fill();
+            for _ in 0..before {
+                f.write_char(fill)?;
+            }
+            f.write_str(&text)?;
+            for _ in 0..after {
+                f.write_char(fill)?;
+            }
+            Ok(())
+        }
+    }
+
+    assert_eq!(format!("{}", Celsius(21.456)), "21.5°C");
+    assert_eq!(format!("{:.2}", Celsius(21.456)), "21.46°C");
+    assert_eq!(format!("{:+}", Celsius(21.456)), "+21.5°C");
+    assert_eq!(format!("{:+}", Celsius(-3.0)), "-3.0°C");
+    assert_eq!(format!("{:+}", Celsius(0.0)), "+0.0°C");
+    assert_eq!(format!("{:+}", Celsius(-0.0)), "-0.0°C");
+    assert_eq!(format!("{:#.0}", Celsius(21.456)), "21 degrees Celsius");
+    assert_eq!(format!("[{:10}]", Celsius(5.0)), "[     5.0°C]");
+    assert_eq!(format!("[{:<10}]", Celsius(5.0)), "[5.0°C     ]");
+    assert_eq!(format!("[{:_^11.2}]", Celsius(5.0)), "[__5.00°C___]");
+    //width and precision from arguments
+    assert_eq!(
+        format!("[{:>w$.p$}]", Celsius(5.0), w = 8, p = 0),
+        "[     5°C]"
+    );
+    println!("{:+.1} / {:#}", Celsius(36.6), Celsius(-40.0));
+}
+
+fn debug_builders() {
+    use std::fmt;
+
+    //derive(Debug) would show every field: a manual impl picks
+    struct Account {
+        user: String,
+        password: String,
+        logins: u32,
+    }
+
+    impl fmt::Debug for Account {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("Account")
+                .field("user", &self.user)
+                .field("logins", &self.logins)
+                //prints `..`: there is more than what's shown
+                .finish_non_exhaustive()
+        }
+    }
+
+    struct Point(i32, i32);
+
+    impl fmt::Debug for Point {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_tuple("Point")
+                .field(&self.0)
+                .field(&self.1)
+                .finish()
+        }
+    }
+
+    //a stack shows its top first
+    struct Stack(Vec<i32>);
+
+    impl fmt::Debug for Stack {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_list().entries(self.0.iter().rev()).finish()
+        }
+    }
+
+    struct Inventory(Vec<(&'static str, u32)>);
+
+    impl fmt::Debug for Inventory {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_map()
+                .entries(self.0.iter().map(|(item, count)| (item, count)))
+                .finish()
+        }
+    }
+
+    let account = Account {
+        user: "ferris".to_string(),
+        password: "hunter2".to_string(),
+        logins: 3,
+    };
+    assert_eq!(
+        format!("{:?}", account),
+        r#"Account { user: "ferris", logins: 3, .. }"#
+    );
+    assert!(!format!("{:#?}", account).contains(&account.password));
+    assert_eq!(
+        format!("{:#?}", account),
+        "Account {\n    user: \"ferris\",\n    logins: 3,\n    ..\n}"
+    );
+
+    assert_eq!(format!("{:?}", Point(1, -2)), "Point(1, -2)");
+    //the flags reach every field
+    assert_eq!(format!("{:03?}", Point(1, -2)), "Point(001, -02)");
+    assert_eq!(format!("{:?}", Stack(vec![1, 2, 3])), "[3, 2, 1]");
+
+    let inventory = Inventory(vec![("apples", 3), ("pears", 0)]);
+    assert_eq!(format!("{:?}", inventory), r#"{"apples": 3, "pears": 0}"#);
+    assert_eq!(
+        format!("{:#?}", inventory),
+        "{\n    \"apples\": 3,\n    \"pears\": 0,\n}"
+    );
+    println!("{:#?}", Stack(vec![1, 2]));
+}
+
+fn numeric_traits() {
+    use std::fmt;
+    use std::rc::Rc;
+
+    //unix permission bits: Display is the rwx form, the number formats delegate to u16,
+    //which keeps every flag working ({:#o}, {:06x}, ...)
+    struct Mode(u16);
+
+    impl fmt::Display for Mode {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let text: String = (0..9)
+                .map(|bit| {
+                    if self.0 & (0o400 >> bit) == 0 {
+                        '-'
+                    } else {
+                        ['r', 'w', 'x'][bit % 3]
+                    }
+                })
+                .collect();
+            f.pad(&text)
+        }
+    }
+
+    impl fmt::Octal for Mode {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            fmt::Octal::fmt(&self.0, f)
+        }
+    }
+
+    impl fmt::LowerHex for Mode {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            fmt::LowerHex::fmt(&self.0, f)
+        }
+    }
+
+    impl fmt::Binary for Mode {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            fmt::Binary::fmt(&self.0, f)
+        }
+    }
+
+    let mode = Mode(0o750);
+    assert_eq!(format!("{}", mode), "rwxr-x---");
+    assert_eq!(format!("{:o}", mode), "750");
+    assert_eq!(format!("{:#o}", mode), "0o750");
+    assert_eq!(format!("{:x}", mode), "1e8");
+    assert_eq!(format!("{:#06x}", mode), "0x01e8");
+    assert_eq!(format!("{:b}", mode), "111101000");
+    //the 0b prefix counts towards the width
+    assert_eq!(format!("{:#014b}", mode), "0b000111101000");
+
+    //{:p}: where the shared value lives, the same for every clone of the Rc
+    struct Shared(Rc<String>);
+
+    impl fmt::Pointer for Shared {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            fmt::Pointer::fmt(&Rc::as_ptr(&self.0), f)
+        }
+    }
+
+    let first = Shared(Rc::new("data".to_string()));
+    let second = Shared(Rc::clone(&first.0));
+    let other = Shared(Rc::new("data".to_string()));
+    assert_eq!(format!("{:p}", first), format!("{:p}", second));
+    assert_eq!(
+        format!("{:p}", first),
+        format!("{:p}", Rc::as_ptr(&first.0))
+    );
+    assert_ne!(format!("{:p}", first), format!("{:p}", other));
+    assert!(format!("{:p}", first).starts_with("0x"));
+    println!("{} = {:#o}, shared at {:p}", mode, mode, first);
+}
+
+fn write_into_strings() {
+    use std::fmt::{self, Write};
+
+    //fmt::Write: write! and writeln! into anything that accepts &str, String included
+    let mut report = String::new();
+    for (name, score) in [("ferris", 10), ("corro", 7)].iter() {
+        writeln!(report, "{:<8}{:>3}", name, score).unwrap();
+    }
+    assert_eq!(report, "ferris   10\ncorro     7\n");
+
+    //an own fmt::Write only needs write_str; this one indents every line
+    struct Indented {
+        out: String,
+        at_line_start: bool,
+    }
+
+    impl fmt::Write for Indented {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            for c in s.chars() {
+                if self.at_line_start && c != '\n' {
+                    self.out.push_str("    ");
+                }
+                self.at_line_start = c == '\n';
+                self.out.push(c);
+            }
+            Ok(())
+        }
+    }
+
+    let mut indented = Indented {
+        out: String::new(),
+        at_line_start: true,
+    };
+    write!(indented, "{}", report).unwrap();
+    assert_eq!(indented.out, "    ferris   10\n    corro     7\n");
+
+    //format_args!: the arguments checked and captured, nothing formatted or allocated yet;
+    //how a function of our own takes println!-style arguments
+    fn log(out: &mut String, level: &str, args: fmt::Arguments<'_>) {
+        writeln!(out, "[{}] {}", level, args).unwrap();
+    }
+
+    //the usual wrapper, so callers don't have to write format_args! themselves
+    macro_rules! log {
+        ($out:expr, $level:expr, $($arg:tt)*) => {
+            log($out, $level, format_args!($($arg)*))
+        };
+    }
+
+    let mut log_text = String::new();
+    log(
+        &mut log_text,
+        "info",
+        format_args!("{} connected from {}", "ferris", "127.0.0.1"),
+    );
+    log!(&mut log_text, "warn", "disk {}% full", 93);
+    assert_eq!(
+        log_text,
+        "[info] ferris connected from 127.0.0.1\n[warn] disk 93% full\n"
+    );
+
+    //as_str: the text itself when there was nothing to format
+    assert_eq!(format_args!("constant").as_str(), Some("constant"));
+    let count = 1;
+    assert_eq!(format_args!("{} item", count).as_str(), None);
+    //fmt::format: Arguments to String, which is all format! does
+    assert_eq!(fmt::format(format_args!("{:>4}", 7)), "   7");
+    print!("{}", indented.out);
+}
+
 pub fn main() {
     different_prints();
     println!();
@@ -128,4 +442,16 @@ pub fn main() {
 
     formatting();
     println!();
+
+    formatter_flags();
+    println!();
+
+    debug_builders();
+    println!();
+
+    numeric_traits();
+    println!();
+
+    write_into_strings();
+    println!();
 }
@@ -0,0 +1,148 @@
+/*
+ * Procedural macros are defined in the rbe-derive workspace member (rbe-derive/src/lib.rs)
+ * Workspace: root Cargo.toml has [workspace] members = ["rbe-derive"],
+ * and depends on it with rbe-derive = { path = "rbe-derive" }
+ *
+ * Hygiene: unlike macro_rules!, tokens produced with Span::call_site() resolve
+ * as if they were written at the call site, e.g. the derive refers to `Describe`
+ * and needs the trait to be in scope where #[derive(Describe)] is used
+ */
+
+//macros and traits live in different namespaces: derive macro and trait can share a name
+use rbe_derive::{log_entry, screaming_consts, Describe};
+
+pub trait Describe {
+    fn type_name() -> &'static str;
+    fn fields() -> &'static [(&'static str, &'static str)];
+}
+
+fn derive_macro() {
+    use std::collections::HashMap;
+
+    #[derive(Describe)]
+    struct Point {
+        x: f64,
+        y: f64,
+    }
+
+    #[derive(Describe)]
+    #[allow(dead_code)]
+    pub struct Config {
+        pub name: String,
+        //comma inside <...> doesn't split the field
+        limits: HashMap<String, (u32, u32)>,
+        callback: fn(i32) -> i32,
+        r#type: Option<&'static str>,
+        check: Option<fn(&mut [u8]) -> bool>,
+        magic: [u8; 4],
+        hook: Box<dyn Fn() + Send>,
+    }
+
+    #[derive(Describe)]
+    struct Meters(f64);
+
+    #[derive(Describe)]
+    struct Unit;
+
+    let point = Point { x: 1.0, y: 2.0 };
+    assert_eq!(point.x + point.y, 3.0);
+    assert_eq!(Point::type_name(), "Point");
+    assert_eq!(Point::fields(), &[("x", "f64"), ("y", "f64")]);
+
+    assert_eq!(Config::type_name(), "Config");
+    let names: Vec<&str> = Config::fields().iter().map(|(name, _)| *name).collect();
+    assert_eq!(
+        names,
+        ["name", "limits", "callback", "type", "check", "magic", "hook"]
+    );
+    assert_eq!(Config::fields()[1].1, "HashMap<String, (u32, u32)>");
+    assert_eq!(Config::fields()[2].1, "fn(i32) -> i32");
+    assert_eq!(Config::fields()[3].1, "Option<&'static str>");
+    assert_eq!(Config::fields()[4].1, "Option<fn(&mut [u8]) -> bool>");
+    assert_eq!(Config::fields()[5].1, "[u8; 4]");
+    assert_eq!(Config::fields()[6].1, "Box<dyn Fn() + Send>");
+
+    assert_eq!(Meters(1.5).0, 1.5);
+    assert_eq!(Meters::fields(), &[("0", "f64")]);
+    assert!(Unit::fields().is_empty());
+
+    //generic code over anything that derives Describe
+    fn render<T: Describe>() -> String {
+        let fields: Vec<String> = T::fields()
+            .iter()
+            .map(|(name, ty)| format!("{}: {}", name, ty))
+            .collect();
+        format!("{} {{ {} }}", T::type_name(), fields.join(", "))
+    }
+    assert_eq!(render::<Point>(), "Point { x: f64, y: f64 }");
+
+    //Not allowed: macro reports an error through compile_error!
+    // #[derive(Describe)]
+    // enum Direction { Up, Down }
+    //error: Describe can only be derived for structs
+}
+
+fn attribute_macro() {
+    use std::cell::RefCell;
+
+    thread_local! {
+        static CALLS: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn record(name: &'static str) {
+        CALLS.with(|calls| calls.borrow_mut().push(name));
+    }
+
+    //function body is wrapped: record("add") runs first
+    #[log_entry(record)]
+    fn add(a: i32, b: i32) -> i32 {
+        a + b
+    }
+
+    #[log_entry(record)]
+    fn factorial(n: u64) -> u64 {
+        if n == 0 {
+            return 1;
+        }
+        n * factorial(n - 1)
+    }
+
+    //without arguments it prints "> entering greet" to stderr
+    #[log_entry]
+    fn greet() -> &'static str {
+        "hello"
+    }
+
+    assert_eq!(add(1, 2), 3);
+    assert_eq!(factorial(3), 6);
+    assert_eq!(greet(), "hello");
+
+    let calls = CALLS.with(|calls| calls.borrow().clone());
+    assert_eq!(
+        calls,
+        ["add", "factorial", "factorial", "factorial", "factorial"]
+    );
+}
+
+fn function_like_macro() {
+    //identifiers MAX_SPEED and GREETING don't appear anywhere in the source
+    screaming_consts! {
+        max_speed: u32 = 120,
+        greeting: &str = "hello",
+        limits: [u8; 3] = [1, 2, 3],
+    }
+
+    assert_eq!(MAX_SPEED, 120);
+    assert_eq!(GREETING, "hello");
+    assert_eq!(LIMITS.len(), 3);
+
+    //consts can be used in const contexts, like array lengths
+    let buffer = [0u8; MAX_SPEED as usize];
+    assert_eq!(buffer.len(), 120);
+}
+
+pub fn main() {
+    derive_macro();
+    attribute_macro();
+    function_like_macro();
+}
@@ -0,0 +1,272 @@
+/*
+ * std::process
+ * - Command: builder for a child process (program, args, env, current dir, stdio)
+ * - output(): run to completion, capture stdout/stderr (stdin is /dev/null)
+ * - status(): run to completion, inherit stdio
+ * - spawn(): start and return a Child handle: talk to it through pipes, wait or kill it
+ * - Stdio::piped() creates a pipe, inherit() shares the parent's, null() discards
+ * Programs are run directly, not through a shell: no globbing, quoting or pipes
+ * unless a shell is spawned explicitly (/bin/sh -c "...")
+ */
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Command, Stdio};
+
+//hidden subcommand: main() calls child() instead of the chapters when it's the first argument
+pub const CHILD_COMMAND: &str = "__processes_child";
+
+//sh -c with the script, arguments become $1, $2, ... ($0 is the name)
+#[cfg(unix)]
+fn sh(script: &str) -> Command {
+    let mut command = Command::new("/bin/sh");
+    command.arg("-c").arg(script).arg("sh");
+    command
+}
+
+#[cfg(unix)]
+fn shell_commands() {
+    //output(): waits, collects stdout and stderr as Vec<u8>
+    let output = Command::new("/bin/sh")
+        .args(["-c", "echo hello; echo oops >&2"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"hello\n");
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "oops\n");
+
+    //pipelines are a shell feature
+    let output = sh("printf 'b\\na\\nc\\n' | sort | wc -l").output().unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "3");
+
+    //arguments are passed as-is: no word splitting, no injection through $1
+    let output = sh("echo \"$1\"")
+        .arg("two words; rm -rf /")
+        .output()
+        .unwrap();
+    assert_eq!(output.stdout, b"two words; rm -rf /\n");
+
+    //exit statuses
+    let status = sh("exit 3").status().unwrap();
+    assert!(!status.success());
+    assert_eq!(status.code(), Some(3));
+
+    //killed by a signal: no exit code
+    use std::os::unix::process::ExitStatusExt;
+    let status = sh("kill -TERM $$").status().unwrap();
+    assert_eq!((status.code(), status.signal()), (None, Some(15)));
+
+    //spawning a program that doesn't exist fails in the parent
+    let error = Command::new("/nonexistent/program").spawn().unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+}
+
+#[cfg(not(unix))]
+fn shell_commands() {}
+
+#[cfg(unix)]
+fn pipes() {
+    //write to the child's stdin, read its stdout
+    let mut child = Command::new("sort")
+        .arg("-r")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    //take() moves the pipe out of the Child: dropping it closes the pipe = EOF for sort
+    //(writing everything before reading could deadlock for large outputs:
+    //use a thread for one of the two ends then)
+    {
+        let mut stdin = child.stdin.take().unwrap();
+        stdin.write_all(b"banana\napple\ncherry\n").unwrap();
+    }
+    let mut sorted = String::new();
+    child
+        .stdout
+        .take()
+        .unwrap()
+        .read_to_string(&mut sorted)
+        .unwrap();
+    assert!(child.wait().unwrap().success());
+    assert_eq!(sorted, "cherry\nbanana\napple\n");
+
+    //wait_with_output: closes stdin, reads both pipes, waits
+    let child = sh("tr a-z A-Z")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.as_ref().unwrap().write_all(b"shout").unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(output.stdout, b"SHOUT");
+
+    //connecting two children: stdout of one is stdin of the other
+    let mut producer = sh("printf '3\\n1\\n2\\n'")
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let consumer = Command::new("sort")
+        .stdin(Stdio::from(producer.stdout.take().unwrap()))
+        .output()
+        .unwrap();
+    producer.wait().unwrap();
+    assert_eq!(consumer.stdout, b"1\n2\n3\n");
+}
+
+#[cfg(not(unix))]
+fn pipes() {}
+
+#[cfg(unix)]
+fn environment_and_directory() {
+    let directory = std::env::temp_dir();
+
+    let output = sh("echo \"$GREETING from $(pwd)\"")
+        .env("GREETING", "hi")
+        .current_dir(&directory)
+        .output()
+        .unwrap();
+    let expected = format!("hi from {}\n", directory.canonicalize().unwrap().display());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), expected);
+
+    //env_clear: only what's set explicitly is passed on
+    std::env::set_var("RBE_PARENT_VARIABLE", "inherited");
+    let inherited = sh("echo \"[$RBE_PARENT_VARIABLE]\"").output().unwrap();
+    let cleared = sh("echo \"[$RBE_PARENT_VARIABLE]\"")
+        .env_clear()
+        .output()
+        .unwrap();
+    std::env::remove_var("RBE_PARENT_VARIABLE");
+    assert_eq!(inherited.stdout, b"[inherited]\n");
+    assert_eq!(cleared.stdout, b"[]\n");
+}
+
+#[cfg(not(unix))]
+fn environment_and_directory() {}
+
+#[cfg(unix)]
+fn wait_and_kill() {
+    use std::time::{Duration, Instant};
+
+    let start = Instant::now();
+    let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+
+    //try_wait: non-blocking, None while running
+    assert!(child.try_wait().unwrap().is_none());
+    assert!(child.id() > 0);
+
+    //kill sends SIGKILL; wait reaps the process (no zombie) and returns its status
+    child.kill().unwrap();
+    let status = child.wait().unwrap();
+    assert!(!status.success());
+    assert!(start.elapsed() < Duration::from_secs(10));
+
+    //polling with a deadline: a simple timeout
+    let mut child = Command::new("sleep").arg("0.1").spawn().unwrap();
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let status = loop {
+        if let Some(status) = child.try_wait().unwrap() {
+            break Some(status);
+        }
+        if Instant::now() > deadline {
+            child.kill().unwrap();
+            child.wait().unwrap();
+            break None;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    };
+    assert!(status.unwrap().success());
+}
+
+#[cfg(not(unix))]
+fn wait_and_kill() {}
+
+/*
+ * IPC without external tools: this binary runs itself with CHILD_COMMAND
+ * Line protocol over stdin/stdout: one request line, one response line
+ *   upper <text>  -> <TEXT>
+ *   add <a> <b>   -> <a + b>
+ *   env <name>    -> value of the variable in the child
+ *   exit <code>   -> child exits with the code, no response
+ */
+pub fn child() {
+    let stdin = std::io::stdin();
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+
+    for line in stdin.lock().lines() {
+        let line = line.unwrap();
+        let (command, argument) = line.split_once(' ').unwrap_or((&line, ""));
+        let response = match command {
+            "upper" => argument.to_uppercase(),
+            "add" => argument
+                .split_whitespace()
+                .map(|n| n.parse::<i64>().unwrap_or(0))
+                .sum::<i64>()
+                .to_string(),
+            "env" => std::env::var(argument).unwrap_or_default(),
+            "exit" => std::process::exit(argument.parse().unwrap_or(1)),
+            _ => format!("error: unknown command {}", command),
+        };
+        writeln!(out, "{}", response).unwrap();
+        //Rust's stdout is line-buffered even into a pipe (C's stdio isn't): flush to be explicit
+        out.flush().unwrap();
+    }
+}
+
+fn self_invocation() {
+    let exe = std::env::current_exe().unwrap();
+    let mut child = Command::new(exe)
+        .arg(CHILD_COMMAND)
+        .env("RBE_CHILD_ID", "7")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut stdin = child.stdin.take().unwrap();
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+
+    //request-response: one line at a time, no deadlock since both sides flush
+    let mut ask = |request: &str| -> String {
+        writeln!(stdin, "{}", request).unwrap();
+        let mut response = String::new();
+        stdout.read_line(&mut response).unwrap();
+        response.trim_end().to_string()
+    };
+
+    assert_eq!(ask("upper pipes"), "PIPES");
+    assert_eq!(ask("add 40 1 1"), "42");
+    assert_eq!(ask("env RBE_CHILD_ID"), "7");
+    assert_eq!(ask("jump"), "error: unknown command jump");
+
+    writeln!(stdin, "exit 5").unwrap();
+    let status = child.wait().unwrap();
+    assert_eq!(status.code(), Some(5));
+
+    //closing stdin ends the child's loop: exit code 0
+    let exe = std::env::current_exe().unwrap();
+    let output = Command::new(exe)
+        .arg(CHILD_COMMAND)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .and_then(|mut child| {
+            child
+                .stdin
+                .take()
+                .unwrap()
+                .write_all(b"upper a\nupper b\n")?;
+            child.wait_with_output()
+        })
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"A\nB\n");
+}
+
+pub fn main() {
+    shell_commands();
+    pipes();
+    environment_and_directory();
+    wait_and_kill();
+    self_invocation();
+}
@@ -61,6 +61,7 @@ fn ownership_and_moves() {
     assert_eq!(mutable_box.as_ref(), &1);
 }
 
+#[allow(clippy::borrowed_box)]
 fn moving_vs_borrowing() {
     fn move_i32(_: Box<i32>) {}
     fn borrow_i32(_: &Box<i32>) {}
@@ -102,6 +103,7 @@ fn borrowing_vs_mutable_borrowing() {
     assert_eq!(y_immutable.as_ref(), &42);
 }
 
+#[allow(clippy::toplevel_ref_arg)]
 fn ref_pattern() {
     let mut a = Box::new(32);
     //These two are the same
@@ -122,48 +124,52 @@ fn ref_pattern() {
     assert_eq!(tuple.1, 4);
 }
 
-fn lifetimes() {
-    //lifetime is determined by where variable is declared
-    //scope os is determined by where variable is used
+//"<'a>": the lifetime of print_ref must not exceed the lifetime 'a
+//"x: &'a i32": the lifetime of 'a is constrained by &a
+//defaults to 'static if not constrained
+#[allow(clippy::needless_lifetimes)]
+pub(crate) fn print_ref<'a>(x: &'a i32) {
+    println!("x is {}", x);
+}
 
-    //"<'a>": the lifetime of print_ref must not exceed the lifetime 'a
-    //"x: &'a i32": the lifetime of 'a is constrained by &a
-    //defaults to 'static if not constrained
-    fn _print_ref<'a>(x: &'a i32) {
-        println!("x is {}", x);
+pub(crate) struct Owner(pub(crate) i32);
+impl Owner {
+    #[allow(clippy::needless_lifetimes)]
+    pub(crate) fn add_one<'a>(&'a mut self) {
+        self.0 += 1
     }
+}
 
-    struct _Owner(i32);
-    impl _Owner {
-        fn _add_one<'a>(&'a mut self) {
-            self.0 += 1
-        }
-    }
+pub(crate) struct Borrowed<'a>(pub(crate) &'a i32);
+pub(crate) struct NamedBorrowed<'a> {
+    pub(crate) x: &'a i32,
+    pub(crate) y: &'a i32,
+}
+pub(crate) enum Either<'a> {
+    Num(i32),
+    Ref(&'a i32),
+}
 
-    struct _Borrowed<'a>(&'a i32);
-    struct _NamedBorrowed<'a> {
-        x: &'a i32,
-        y: &'a i32,
-    }
-    enum _Either<'a> {
-        Num(i32),
-        Ref(&'a i32),
+impl<'a> Default for Borrowed<'a> {
+    fn default() -> Self {
+        Self(&10)
     }
+}
 
-    impl<'a> Default for _Borrowed<'a> {
-        fn default() -> Self {
-            Self(&10)
-        }
-    }
+//T: 'a  --  all references in T must outlive lifetime 'a
+//T: Trait + 'a  --  type T implements Trait and all references in T must outlive 'a
+pub(crate) fn print_ref2<'a, T>(t: &'a T)
+where
+    T: Debug + 'a,
+{
+    println!("x is {:?}", t);
+}
 
-    //T: 'a  --  all references in T must outlive lifetime 'a
-    //T: Trait + 'a  --  type T implements Trait and all references in T must outlive 'a
-    fn _print_ref2<'a, T>(t: &'a T)
-    where
-        T: Debug + 'a,
-    {
-        println!("x is {:?}", t);
-    }
+fn lifetimes() {
+    //lifetime is determined by where variable is declared
+    //scope os is determined by where variable is used
+
+    //annotated functions and types are at module level, used in lifetimes.rs
 
     //A longer lifetime can be coerced into a shorter one
     //Here, two lifetimes are coerced into the shorter one
@@ -0,0 +1,354 @@
+/*
+ * std::fs and std::io
+ * - Read / Write: byte streams (File, TcpStream, Stdin, &[u8], Vec<u8>, ...)
+ * - BufRead: buffered reading by lines or until a delimiter
+ * - every operation returns io::Result: errors carry an io::ErrorKind
+ * - File is closed when dropped; errors on close are ignored, call sync_all to see them
+ * - unbuffered File does a syscall per read/write call: wrap in BufReader/BufWriter
+ *
+ * All examples work in a fresh directory under env::temp_dir(), removed at the end
+ */
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+//Per-run sandbox: the directory and everything in it is removed on drop
+struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    fn new(name: &str) -> io::Result<TempDir> {
+        let path = std::env::temp_dir().join(format!("rbe-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&path)?;
+        Ok(TempDir { path })
+    }
+
+    fn join(&self, name: &str) -> PathBuf {
+        self.path.join(name)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        //can't return an error from drop: best effort
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+fn create_and_open(dir: &TempDir) -> io::Result<()> {
+    let path = dir.join("hello.txt");
+
+    //create: truncates if the file exists
+    let mut file = File::create(&path)?;
+    file.write_all(b"Hello, ")?;
+    let name = "world";
+    write!(file, "{}!", name)?;
+    drop(file);
+
+    //open: read-only, fails if missing
+    let mut contents = String::new();
+    File::open(&path)?.read_to_string(&mut contents)?;
+    assert_eq!(contents, "Hello, world!");
+
+    //shortcuts for whole files
+    fs::write(&path, "replaced")?;
+    assert_eq!(fs::read_to_string(&path)?, "replaced");
+    assert_eq!(fs::read(&path)?, b"replaced");
+
+    let missing = File::open(dir.join("missing.txt"));
+    assert_eq!(missing.unwrap_err().kind(), io::ErrorKind::NotFound);
+
+    //create_new: fails if the file exists, no check-then-create race
+    let again = OpenOptions::new().write(true).create_new(true).open(&path);
+    assert_eq!(again.unwrap_err().kind(), io::ErrorKind::AlreadyExists);
+    Ok(())
+}
+
+fn buffered(dir: &TempDir) -> io::Result<()> {
+    let path = dir.join("numbers.txt");
+
+    //BufWriter: collects writes in memory, one syscall per 8 KiB
+    {
+        let mut writer = BufWriter::new(File::create(&path)?);
+        for i in 1..=1000 {
+            writeln!(writer, "{}", i)?;
+        }
+        //flush explicitly: drop flushes too, but ignores the error
+        writer.flush()?;
+    }
+
+    //BufReader::lines: one String per line, without the line ending
+    let reader = BufReader::new(File::open(&path)?);
+    let mut sum = 0u64;
+    let mut count = 0;
+    for line in reader.lines() {
+        sum += line?.parse::<u64>().unwrap();
+        count += 1;
+    }
+    assert_eq!((count, sum), (1000, 500_500));
+
+    //read_line reuses one buffer, and keeps the '\n'
+    let mut reader = BufReader::new(File::open(&path)?);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    assert_eq!(line, "1\n");
+    line.clear();
+    reader.read_line(&mut line)?;
+    assert_eq!(line, "2\n");
+
+    //split on any byte
+    fs::write(&path, "a,b,,c")?;
+    let fields: Vec<Vec<u8>> = BufReader::new(File::open(&path)?)
+        .split(b',')
+        .collect::<io::Result<_>>()?;
+    assert_eq!(fields, [&b"a"[..], b"b", b"", b"c"]);
+    Ok(())
+}
+
+fn append(dir: &TempDir) -> io::Result<()> {
+    let path = dir.join("log.txt");
+
+    //append + create: open or create, every write goes to the end
+    let log = |message: &str| -> io::Result<()> {
+        let mut file = OpenOptions::new().append(true).create(true).open(&path)?;
+        writeln!(file, "{}", message)
+    };
+    log("started")?;
+    log("working")?;
+    log("done")?;
+    assert_eq!(fs::read_to_string(&path)?, "started\nworking\ndone\n");
+
+    //read + write without truncating: overwrite in place
+    use std::io::{Seek, SeekFrom};
+    let mut file = OpenOptions::new().read(true).write(true).open(&path)?;
+    file.seek(SeekFrom::Start(8))?;
+    file.write_all(b"WORKING")?;
+    file.seek(SeekFrom::End(-5))?;
+    let mut tail = String::new();
+    file.read_to_string(&mut tail)?;
+    assert_eq!(tail, "done\n");
+    assert_eq!(fs::read_to_string(&path)?, "started\nWORKING\ndone\n");
+    Ok(())
+}
+
+//Recursive walk: read_dir yields entries of one directory, in no particular order
+fn walk(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        //file_type from the entry: no extra syscall on most platforms, doesn't follow symlinks
+        if entry.file_type()?.is_dir() {
+            walk(&entry.path(), files)?;
+        } else {
+            files.push(entry.path());
+        }
+    }
+    Ok(())
+}
+
+fn directories(dir: &TempDir) -> io::Result<()> {
+    let root = dir.join("project");
+    fs::create_dir_all(root.join("src/bin"))?;
+    fs::create_dir(root.join("tests"))?;
+    for file in [
+        "Cargo.toml",
+        "src/main.rs",
+        "src/bin/tool.rs",
+        "tests/it.rs",
+    ] {
+        fs::write(root.join(file), file)?;
+    }
+
+    let mut files = Vec::new();
+    walk(&root, &mut files)?;
+    let mut relative: Vec<String> = files
+        .iter()
+        .map(|path| path.strip_prefix(&root).unwrap().display().to_string())
+        .collect();
+    relative.sort();
+    assert_eq!(
+        relative,
+        [
+            "Cargo.toml",
+            "src/bin/tool.rs",
+            "src/main.rs",
+            "tests/it.rs"
+        ]
+    );
+
+    let rust_files = files
+        .iter()
+        .filter(|path| path.extension().is_some_and(|ext| ext == "rs"))
+        .count();
+    assert_eq!(rust_files, 3);
+
+    //rename/copy/remove
+    fs::rename(root.join("tests/it.rs"), root.join("tests/integration.rs"))?;
+    let copied = fs::copy(root.join("Cargo.toml"), root.join("Cargo.toml.bak"))?;
+    assert_eq!(copied, "Cargo.toml".len() as u64);
+    //remove_dir only removes empty directories
+    assert!(fs::remove_dir(root.join("tests")).is_err());
+    fs::remove_file(root.join("tests/integration.rs"))?;
+    fs::remove_dir(root.join("tests"))?;
+    assert!(!root.join("tests").exists());
+    Ok(())
+}
+
+fn metadata(dir: &TempDir) -> io::Result<()> {
+    let path = dir.join("data.bin");
+    fs::write(&path, [0u8; 1234])?;
+
+    let metadata = fs::metadata(&path)?;
+    assert!(metadata.is_file());
+    assert_eq!(metadata.len(), 1234);
+    assert!(metadata.modified()?.elapsed().is_ok());
+
+    //readonly is the portable part of permissions
+    let mut permissions = metadata.permissions();
+    assert!(!permissions.readonly());
+    permissions.set_readonly(true);
+    fs::set_permissions(&path, permissions)?;
+    assert!(fs::metadata(&path)?.permissions().readonly());
+
+    //unix mode bits through an extension trait
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o640))?;
+        let mode = fs::metadata(&path)?.permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+    }
+
+    //writable again before removing: Windows refuses to delete read-only files
+    let mut permissions = fs::metadata(&path)?.permissions();
+    #[allow(clippy::permissions_set_readonly_false)]
+    permissions.set_readonly(false);
+    fs::set_permissions(&path, permissions)?;
+    fs::remove_file(&path)?;
+    assert!(fs::metadata(&path).is_err());
+    Ok(())
+}
+
+/*
+ * Implementing Read and Write: one required method each
+ * read fills (part of) the buffer and returns how many bytes, 0 = end of stream
+ * write consumes (part of) the buffer and returns how many bytes;
+ * read_exact, read_to_end, write_all, write_fmt, ... are provided on top
+ */
+mod memory {
+    use std::collections::VecDeque;
+    use std::io::{self, Read, Write};
+
+    //FIFO byte pipe with a bounded capacity: writes may be partial
+    pub struct Pipe {
+        buffer: VecDeque<u8>,
+        capacity: usize,
+        pub writes: usize,
+    }
+
+    impl Pipe {
+        pub fn new(capacity: usize) -> Pipe {
+            Pipe {
+                buffer: VecDeque::new(),
+                capacity,
+                writes: 0,
+            }
+        }
+    }
+
+    impl Write for Pipe {
+        fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+            self.writes += 1;
+            let free = self.capacity - self.buffer.len();
+            if free == 0 && !data.is_empty() {
+                return Err(io::Error::new(io::ErrorKind::WriteZero, "pipe is full"));
+            }
+            let accepted = data.len().min(free);
+            self.buffer.extend(&data[..accepted]);
+            Ok(accepted)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Read for Pipe {
+        fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+            let count = out.len().min(self.buffer.len());
+            for (slot, byte) in out.iter_mut().zip(self.buffer.drain(..count)) {
+                *slot = byte;
+            }
+            Ok(count)
+        }
+    }
+}
+
+fn custom_read_write(dir: &TempDir) -> io::Result<()> {
+    use memory::Pipe;
+
+    let mut pipe = Pipe::new(64);
+    //write_all retries until everything is accepted
+    pipe.write_all(b"first line\n")?;
+    writeln!(pipe, "second {}", 2)?;
+
+    //BufReader works over any Read
+    let mut reader = BufReader::new(&mut pipe);
+    let mut first = String::new();
+    reader.read_line(&mut first)?;
+    assert_eq!(first, "first line\n");
+    let mut rest = String::new();
+    reader.read_to_string(&mut rest)?;
+    assert_eq!(rest, "second 2\n");
+
+    //partial writes: 10 bytes of room, write_all gives up with WriteZero
+    let mut small = Pipe::new(10);
+    let error = small.write_all(b"more than ten bytes").unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::WriteZero);
+    let mut kept = [0; 10];
+    small.read_exact(&mut kept)?;
+    assert_eq!(&kept, b"more than ");
+
+    //io::copy between any Read and any Write: file -> pipe
+    let path = dir.join("source.txt");
+    fs::write(&path, "copied through a pipe")?;
+    let mut pipe = Pipe::new(1024);
+    let copied = io::copy(&mut File::open(&path)?, &mut pipe)?;
+    assert_eq!(copied, 21);
+    let mut text = String::new();
+    pipe.read_to_string(&mut text)?;
+    assert_eq!(text, "copied through a pipe");
+
+    //BufWriter batches small writes into one write call
+    let mut pipe = Pipe::new(1024);
+    {
+        let mut writer = BufWriter::new(&mut pipe);
+        for word in ["a", "b", "c", "d"] {
+            writer.write_all(word.as_bytes())?;
+        }
+    }
+    assert_eq!(pipe.writes, 1);
+
+    //std has in-memory Read/Write too: &[u8] reads, Vec<u8> writes, Cursor does both
+    let mut output = Vec::new();
+    io::copy(&mut &b"bytes"[..], &mut output)?;
+    assert_eq!(output, b"bytes");
+    Ok(())
+}
+
+pub fn main() {
+    let dir = TempDir::new("std-io").unwrap();
+    let sandbox = dir.path.clone();
+
+    create_and_open(&dir).unwrap();
+    buffered(&dir).unwrap();
+    append(&dir).unwrap();
+    directories(&dir).unwrap();
+    metadata(&dir).unwrap();
+    custom_read_write(&dir).unwrap();
+
+    drop(dir);
+    assert!(!sandbox.exists());
+}
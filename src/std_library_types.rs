@@ -14,8 +14,8 @@ fn boxing() {
         }
     }
     let point: Point = Default::default();
-    let boxed_point: Box<Point> = Box::new(Default::default());
-    let double_boxed_point: Box<Box<Point>> = Box::new(Box::new(Default::default()));
+    let boxed_point: Box<Point> = Box::default();
+    let double_boxed_point: Box<Box<Point>> = Box::default();
 
     assert_eq!(mem::size_of_val(&point), 16);
     assert_eq!(mem::size_of_val(&boxed_point), 8);
@@ -113,17 +113,83 @@ fn hash_set() {
 
     use std::collections::HashSet;
 
-    let _a: HashSet<i32> = hashset(vec![1, 2, 3]);
-    let _b: HashSet<i32> = hashset(vec![2, 3, 4]);
+    let a: HashSet<i32> = hashset(vec![1, 2, 3]);
+    let b: HashSet<i32> = hashset(vec![2, 3, 4]);
+
+    //methods return lazy iterators over references: collect to get a set
+    let union: HashSet<i32> = a.union(&b).copied().collect();
+    let intersection: HashSet<i32> = a.intersection(&b).copied().collect();
+    let a_minus_b: HashSet<i32> = a.difference(&b).copied().collect();
+    let sym_dif: HashSet<i32> = a.symmetric_difference(&b).copied().collect();
+
+    assert_eq!(union, hashset(vec![1, 2, 3, 4]));
+    assert_eq!(intersection, hashset(vec![2, 3]));
+    assert_eq!(a_minus_b, hashset(vec![1]));
+    assert_eq!(
+        b.difference(&a).copied().collect::<HashSet<_>>(),
+        hashset(vec![4])
+    );
+    assert_eq!(sym_dif, hashset(vec![1, 4]));
+
+    //operator forms on references: build a new HashSet (elements must be Clone)
+    assert_eq!(&a | &b, union);
+    assert_eq!(&a & &b, intersection);
+    assert_eq!(&a - &b, a_minus_b);
+    assert_eq!(&a ^ &b, sym_dif);
+
+    //laws: a ^ b == (a | b) - (a & b) == (a - b) | (b - a)
+    assert_eq!(&union - &intersection, sym_dif);
+    assert_eq!(&a_minus_b | &(&b - &a), sym_dif);
+
+    //relations
+    assert!(intersection.is_subset(&a));
+    assert!(union.is_superset(&b));
+    assert!(!a.is_subset(&b));
+    assert!(a_minus_b.is_disjoint(&b));
+    assert!(!a.is_disjoint(&b));
+
+    //custom key: Eq and Hash must agree (a == b => hash(a) == hash(b))
+    //Here case-insensitive, so both compare and hash the lowercase form
+    use std::hash::Hasher;
+
+    #[derive(Debug, Clone)]
+    struct Tag(String);
+
+    impl PartialEq for Tag {
+        fn eq(&self, other: &Tag) -> bool {
+            self.0.eq_ignore_ascii_case(&other.0)
+        }
+    }
+
+    impl Eq for Tag {}
+
+    impl Hash for Tag {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            for byte in self.0.bytes() {
+                state.write_u8(byte.to_ascii_lowercase());
+            }
+            //terminator, like str's Hash: ("ab", "c") and ("a", "bc") hash differently in tuples
+            state.write_u8(0xff);
+        }
+    }
 
-    let _union: HashSet<i32> = hashset(vec![1, 2, 3, 4]);
-    let _intersection = hashset(vec![2, 3]);
-    let _a_minus_b = hashset(vec![1i32, 4]);
-    let _sym_dif = hashset(vec![1, 4]);
+    let tag = |name: &str| Tag(name.to_string());
+    let post: HashSet<Tag> = vec![tag("Rust"), tag("rust"), tag("Macros")]
+        .into_iter()
+        .collect();
+    let other: HashSet<Tag> = vec![tag("RUST"), tag("async")].into_iter().collect();
+
+    //"Rust" and "rust" are the same element: the first one inserted is kept
+    assert_eq!(post.len(), 2);
+    assert!(post.contains(&tag("MACROS")));
+    let common: Vec<&Tag> = post.intersection(&other).collect();
+    assert_eq!(common.len(), 1);
+    assert_eq!(common[0].0, "Rust");
+    assert_eq!((&post | &other).len(), 3);
 }
 
 fn reference_counting() {
-    //Arc: Rc for shared ownership between threads
+    //Arc: Rc for shared ownership between threads (used for server state in networking.rs)
     use std::rc::Rc;
     let x = 42;
     let rc = Rc::new(x);
@@ -0,0 +1,227 @@
+/*
+ * Owned and borrowed string-like types
+ * Owned - Borrowed: purpose
+ * String - &str: unicode (always valid UTF-8)
+ * Vec<u8> - &[u8]: binary data, byte strings
+ * PathBuf - &Path: file names (thin wrapper around OsString/&OsStr)
+ * OsString - &OsStr: env variables, command line arguments (platform encoding)
+ * CString - &CStr: C libs (nul-terminated, no interior nuls)
+ *
+ * Owned types Deref into borrowed ones, borrowed types go back with to_owned()/to_*_buf()
+ */
+
+fn conversions() {
+    use std::ffi::{CStr, CString, OsStr, OsString};
+    use std::path::{Path, PathBuf};
+
+    //String <-> &str
+    let owned: String = "cake".to_owned();
+    let borrowed: &str = &owned;
+    assert_eq!(borrowed.to_string(), owned);
+
+    //Vec<u8> <-> &[u8]
+    let bytes: Vec<u8> = owned.clone().into_bytes();
+    let byte_slice: &[u8] = &bytes;
+    assert_eq!(byte_slice, b"cake");
+    assert_eq!(byte_slice.to_vec(), bytes);
+
+    //OsString <-> &OsStr
+    let os_string: OsString = OsString::from("cake");
+    let os_str: &OsStr = os_string.as_os_str();
+    assert_eq!(os_str.to_os_string(), os_string);
+    //&str -> &OsStr is free, &OsStr -> &str may fail
+    assert_eq!(OsStr::new("cake"), os_str);
+    assert_eq!(os_str.to_str(), Some("cake"));
+    assert_eq!(os_string.into_string(), Ok(owned.clone()));
+
+    //PathBuf <-> &Path
+    let path_buf: PathBuf = PathBuf::from("/tmp/cake.txt");
+    let path: &Path = path_buf.as_path();
+    assert_eq!(path.to_path_buf(), path_buf);
+    //Path is an OsStr underneath
+    assert_eq!(path.as_os_str(), OsStr::new("/tmp/cake.txt"));
+    assert_eq!(path.to_str(), Some("/tmp/cake.txt"));
+    assert_eq!(path_buf.into_os_string(), OsString::from("/tmp/cake.txt"));
+
+    //CString <-> &CStr
+    let c_string: CString = CString::new(owned.clone()).unwrap();
+    let c_str: &CStr = c_string.as_c_str();
+    assert_eq!(c_str.to_owned(), c_string);
+    //nul terminator is stored, but not exposed by to_bytes
+    assert_eq!(c_str.to_bytes(), b"cake");
+    assert_eq!(c_str.to_bytes_with_nul(), b"cake\0");
+    assert_eq!(c_string.into_string(), Ok(owned));
+}
+
+fn utf8_decoding() {
+    use std::str;
+
+    let valid: &[u8] = "ℝust".as_bytes();
+    assert_eq!(valid.len(), 6);
+    //'ℝ' followed by a truncated 'ℝ' (3 bytes, only 2 present) and 'x'
+    let invalid: Vec<u8> = vec![0xE2, 0x84, 0x9D, 0xE2, 0x84, b'x'];
+
+    //Fallible: borrowed
+    assert_eq!(str::from_utf8(valid), Ok("ℝust"));
+    let error = str::from_utf8(&invalid).unwrap_err();
+    //everything before valid_up_to can be used safely
+    assert_eq!(error.valid_up_to(), 3);
+    assert_eq!(error.error_len(), Some(2));
+    assert_eq!(str::from_utf8(&invalid[..error.valid_up_to()]), Ok("ℝ"));
+
+    //Fallible: owned, the bytes can be recovered from the error
+    let error = String::from_utf8(invalid.clone()).unwrap_err();
+    assert_eq!(error.utf8_error().valid_up_to(), 3);
+    assert_eq!(error.into_bytes(), invalid);
+
+    //Lossy: invalid sequences are replaced with U+FFFD
+    //Cow::Borrowed if nothing was replaced, Cow::Owned otherwise
+    use std::borrow::Cow;
+    assert!(matches!(
+        String::from_utf8_lossy(valid),
+        Cow::Borrowed("ℝust")
+    ));
+    let lossy = String::from_utf8_lossy(&invalid);
+    assert!(matches!(lossy, Cow::Owned(_)));
+    assert_eq!(lossy, "ℝ\u{FFFD}x");
+}
+
+fn byte_strings() {
+    //byte string literal is &[u8; N], not &str
+    let literal: &[u8; 5] = b"cake\n";
+    let raw: &[u8] = br"cake\n";
+    assert_eq!(literal.len(), 5);
+    assert_eq!(raw.len(), 6);
+
+    //only ASCII and \xHH escapes are allowed
+    let escaped = b"\x52\x75\x73\x74";
+    assert_eq!(escaped, b"Rust");
+
+    //ASCII helpers work without decoding
+    assert_eq!(literal.to_ascii_uppercase(), b"CAKE\n".to_vec());
+    assert!(literal.is_ascii());
+    assert_eq!(literal.trim_ascii_end(), b"cake");
+}
+
+fn path_components() {
+    use std::ffi::OsStr;
+    use std::path::{Component, Path, PathBuf};
+
+    let path = Path::new("/usr/local/lib/libcake.so.1");
+
+    assert_eq!(path.parent(), Some(Path::new("/usr/local/lib")));
+    assert_eq!(path.file_name(), Some(OsStr::new("libcake.so.1")));
+    //stem and extension split at the last dot
+    assert_eq!(path.file_stem(), Some(OsStr::new("libcake.so")));
+    assert_eq!(path.extension(), Some(OsStr::new("1")));
+    assert!(path.is_absolute());
+    assert!(path.starts_with("/usr/local"));
+    assert_eq!(
+        path.strip_prefix("/usr").unwrap(),
+        Path::new("local/lib/libcake.so.1")
+    );
+
+    let components: Vec<Component> = path.components().collect();
+    assert_eq!(components.len(), 5);
+    assert_eq!(components[0], Component::RootDir);
+    assert_eq!(components[1], Component::Normal(OsStr::new("usr")));
+
+    //components are normalized: repeated separators and "." are dropped, ".." is kept
+    let messy = Path::new("a//b/./c/../d");
+    let normal: Vec<&OsStr> = messy.iter().collect();
+    assert_eq!(normal, ["a", "b", "c", "..", "d"]);
+
+    //building paths
+    let mut path_buf = PathBuf::from("/tmp");
+    path_buf.push("cake");
+    path_buf.push("recipe.txt");
+    assert_eq!(path_buf, Path::new("/tmp/cake/recipe.txt"));
+    path_buf.set_extension("md");
+    assert_eq!(path_buf, Path::new("/tmp/cake/recipe.md"));
+    assert!(path_buf.pop());
+    assert_eq!(path_buf, Path::new("/tmp/cake"));
+
+    //pushing an absolute path replaces the whole path
+    path_buf.push("/etc");
+    assert_eq!(path_buf, Path::new("/etc"));
+
+    //non-mutating versions
+    let joined = Path::new("/tmp").join("cake").with_extension("txt");
+    assert_eq!(joined, Path::new("/tmp/cake.txt"));
+    assert_eq!(joined.with_file_name("pie.txt"), Path::new("/tmp/pie.txt"));
+
+    //display() is needed for formatting, since Path might not be UTF-8
+    assert_eq!(format!("{}", joined.display()), "/tmp/cake.txt");
+}
+
+fn c_strings() {
+    use std::ffi::{CStr, CString};
+
+    //interior nul can't be represented in a C string
+    let error = CString::new("ca\0ke").unwrap_err();
+    assert_eq!(error.nul_position(), 2);
+    //original bytes are returned back
+    assert_eq!(error.into_vec(), b"ca\0ke");
+
+    //&CStr from bytes: exactly one nul, at the end
+    assert!(CStr::from_bytes_with_nul(b"cake\0").is_ok());
+    assert!(CStr::from_bytes_with_nul(b"cake").is_err());
+    assert!(CStr::from_bytes_with_nul(b"ca\0ke\0").is_err());
+    //or stop at the first nul
+    let until_nul = CStr::from_bytes_until_nul(b"ca\0ke\0").unwrap();
+    assert_eq!(until_nul.to_bytes(), b"ca");
+
+    //C strings are not required to be UTF-8
+    let latin1 = CStr::from_bytes_with_nul(b"caf\xE9\0").unwrap();
+    assert!(latin1.to_str().is_err());
+    assert_eq!(latin1.to_string_lossy(), "caf\u{FFFD}");
+
+    //pointer passed to C must outlive the call: keep CString alive
+    let owned = CString::new("cake").unwrap();
+    let ptr = owned.as_ptr();
+    //never do CString::new(..).unwrap().as_ptr(): the temporary is dropped immediately
+    let back = unsafe { CStr::from_ptr(ptr) };
+    assert_eq!(back.to_str(), Ok("cake"));
+}
+
+#[cfg(target_os = "linux")]
+fn os_strings() {
+    use std::env;
+    use std::ffi::{OsStr, OsString};
+    //on unix OsStr is an arbitrary byte sequence
+    use std::os::unix::ffi::{OsStrExt, OsStringExt};
+
+    let key = "RUST_BY_EXAMPLE_OS_STR";
+
+    //valid UTF-8 round trip
+    env::set_var(key, "cake");
+    assert_eq!(env::var_os(key), Some(OsString::from("cake")));
+    assert_eq!(env::var(key), Ok("cake".to_owned()));
+
+    //not UTF-8, but still a valid env variable value
+    let not_utf8: &OsStr = OsStr::from_bytes(b"caf\xE9");
+    assert_eq!(not_utf8.to_str(), None);
+    assert_eq!(not_utf8.to_string_lossy(), "caf\u{FFFD}");
+
+    env::set_var(key, not_utf8);
+    let read_back = env::var_os(key).unwrap();
+    assert_eq!(read_back, not_utf8);
+    assert_eq!(read_back.clone().into_vec(), b"caf\xE9");
+    //env::var insists on UTF-8 and hands back the original OsString
+    assert_eq!(env::var(key), Err(env::VarError::NotUnicode(read_back)));
+
+    env::remove_var(key);
+    assert_eq!(env::var_os(key), None);
+}
+
+#[cfg(not(target_os = "linux"))]
+fn os_strings() {}
+
+pub fn main() {
+    conversions();
+    utf8_decoding();
+    byte_strings();
+    path_components();
+    c_strings();
+    os_strings();
+}
@@ -0,0 +1,203 @@
+/*
+ * Three kinds of tests, all run by `cargo test`
+ * 1. Unit tests: #[cfg(test)] module next to the code, can reach private items
+ * 2. Doc tests: code blocks in /// comments of public items, compiled as an external crate
+ * 3. Integration tests: every file in ./tests is a separate crate using only the public API
+ *    shared helpers go to tests/common/mod.rs, so they are not treated as a test crate
+ *
+ * Useful flags
+ * cargo test rectangle              (run tests whose name contains "rectangle")
+ * cargo test --test testing         (run a single integration test crate)
+ * cargo test --doc                  (run doc tests only)
+ * cargo test -- --ignored           (run ignored tests only)
+ * cargo test -- --test-threads=1 --show-output
+ */
+
+use std::fmt;
+
+/// Axis-aligned rectangle with integer sides.
+///
+/// ```
+/// use rust_by_example::testing::Rectangle;
+///
+/// let big = Rectangle::new(8, 7);
+/// let small = Rectangle::new(5, 1);
+/// assert!(big.can_hold(&small));
+/// assert!(!small.can_hold(&big));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rectangle {
+    width: u32,
+    height: u32,
+}
+
+impl Rectangle {
+    pub fn new(width: u32, height: u32) -> Rectangle {
+        Rectangle { width, height }
+    }
+
+    /// Returns the area, saturating at `u32::MAX`.
+    ///
+    /// ```
+    /// # use rust_by_example::testing::Rectangle;
+    /// assert_eq!(Rectangle::new(3, 4).area(), 12);
+    /// assert_eq!(Rectangle::new(u32::MAX, 2).area(), u32::MAX);
+    /// ```
+    pub fn area(&self) -> u32 {
+        self.width.saturating_mul(self.height)
+    }
+
+    /// True if `other` fits inside `self` without rotation.
+    pub fn can_hold(&self, other: &Rectangle) -> bool {
+        self.width > other.width && self.height > other.height
+    }
+}
+
+/// Integer division that panics with a readable message on zero.
+///
+/// ```should_panic
+/// rust_by_example::testing::divide(1, 0);
+/// ```
+pub fn divide(dividend: i32, divisor: i32) -> i32 {
+    if divisor == 0 {
+        panic!("Divide-by-zero error: {} / 0", dividend);
+    }
+    dividend / divisor
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PercentError {
+    NotANumber(String),
+    OutOfRange(u32),
+}
+
+impl fmt::Display for PercentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PercentError::NotANumber(input) => write!(f, "{:?} is not a number", input),
+            PercentError::OutOfRange(value) => write!(f, "{} is greater than 100", value),
+        }
+    }
+}
+
+impl std::error::Error for PercentError {}
+
+/// Parses strings like `"42%"` or `"42"` into a value in `0..=100`.
+///
+/// Doc tests can use `?` when the block ends with `Ok(())`:
+///
+/// ```
+/// use rust_by_example::testing::{parse_percent, PercentError};
+///
+/// assert_eq!(parse_percent(" 42% ")?, 42);
+/// assert_eq!(parse_percent("142%"), Err(PercentError::OutOfRange(142)));
+/// # Ok::<(), PercentError>(())
+/// ```
+///
+/// Private items are not visible from doc tests:
+///
+/// ```compile_fail
+/// rust_by_example::testing::strip_percent("42%");
+/// ```
+pub fn parse_percent(input: &str) -> Result<u8, PercentError> {
+    let digits = strip_percent(input.trim());
+    let value: u32 = digits
+        .parse()
+        .map_err(|_| PercentError::NotANumber(digits.to_owned()))?;
+    if value > 100 {
+        return Err(PercentError::OutOfRange(value));
+    }
+    Ok(value as u8)
+}
+
+fn strip_percent(input: &str) -> &str {
+    input.strip_suffix('%').unwrap_or(input)
+}
+
+pub fn main() {
+    assert_eq!(Rectangle::new(3, 4).area(), 12);
+    assert_eq!(divide(7, 2), 3);
+    assert_eq!(parse_percent("42%"), Ok(42));
+}
+
+#[cfg(test)]
+mod tests {
+    //everything from the parent module, including private items
+    use super::*;
+
+    #[test]
+    fn larger_can_hold_smaller() {
+        let larger = Rectangle::new(8, 7);
+        let smaller = Rectangle::new(5, 1);
+        assert!(larger.can_hold(&smaller));
+    }
+
+    #[test]
+    fn smaller_cannot_hold_larger() {
+        let larger = Rectangle::new(8, 7);
+        let smaller = Rectangle::new(5, 1);
+        //custom message is printed on failure
+        assert!(
+            !smaller.can_hold(&larger),
+            "{:?} should not hold {:?}",
+            smaller,
+            larger
+        );
+    }
+
+    #[test]
+    fn area_saturates() {
+        assert_eq!(Rectangle::new(u32::MAX, u32::MAX).area(), u32::MAX);
+        assert_ne!(Rectangle::new(2, 2).area(), 5);
+    }
+
+    //passes only if the panic message contains `expected`
+    #[test]
+    #[should_panic(expected = "Divide-by-zero error")]
+    fn divide_by_zero_panics() {
+        divide(1, 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn divide_overflow_panics() {
+        divide(i32::MIN, -1);
+    }
+
+    //Err fails the test, so ? can be used instead of unwrap
+    #[test]
+    fn parses_percent() -> Result<(), PercentError> {
+        assert_eq!(parse_percent("0%")?, 0);
+        assert_eq!(parse_percent("100")?, 100);
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_bad_percent() {
+        assert_eq!(
+            parse_percent("abc%"),
+            Err(PercentError::NotANumber("abc".to_owned()))
+        );
+        assert_eq!(
+            parse_percent("101%").unwrap_err().to_string(),
+            "101 is greater than 100"
+        );
+    }
+
+    //private function, unreachable from doc and integration tests
+    #[test]
+    fn strips_single_suffix() {
+        assert_eq!(strip_percent("42%"), "42");
+        assert_eq!(strip_percent("42%%"), "42%");
+        assert_eq!(strip_percent("42"), "42");
+    }
+
+    //skipped unless `cargo test -- --ignored` or `--include-ignored`
+    #[test]
+    #[ignore]
+    fn every_percent_round_trips() {
+        for value in 0..=100u8 {
+            assert_eq!(parse_percent(&format!("{}%", value)), Ok(value));
+        }
+    }
+}
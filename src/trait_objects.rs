@@ -0,0 +1,401 @@
+/*
+ * Trait object: dyn Trait behind a pointer (&dyn, Box<dyn>, Rc<dyn>, ...)
+ * - fat pointer: (pointer to data, pointer to vtable)
+ * - vtable: drop glue, size, align, then one fn pointer per method
+ * - method call = load fn pointer from vtable + indirect call (dynamic dispatch)
+ * - generics instead produce one copy per type (static dispatch, monomorphization)
+ *
+ * Dyn compatibility (object safety): every method callable through the vtable must
+ * - take self by reference (&self, &mut self, Box<Self>, ...)
+ * - not return Self, not have type parameters
+ * Methods that break the rules can be opted out with `where Self: Sized`
+ */
+
+use crate::generics::HasArea;
+use std::any::Any;
+use std::f64::consts::PI;
+
+trait Named {
+    fn name(&self) -> String;
+}
+
+//Any as a supertrait: every Shape can be upcast to dyn Any and downcast back
+trait Shape: Named + Any {
+    fn area(&self) -> f64;
+
+    //Not callable on dyn Shape, but doesn't prevent dyn Shape from existing
+    fn scaled(&self, factor: f64) -> Self
+    where
+        Self: Sized;
+
+    fn describe_with<F: Fn(&str, f64) -> String>(&self, format: F) -> String
+    where
+        Self: Sized,
+    {
+        format(&self.name(), self.area())
+    }
+
+    //pre-upcasting workaround for downcasting, still common in older code
+    fn as_any(&self) -> &dyn Any;
+}
+
+#[derive(Debug, PartialEq)]
+struct Circle {
+    radius: f64,
+}
+
+#[derive(Debug, PartialEq)]
+struct Square {
+    side: f64,
+}
+
+#[derive(Debug, PartialEq)]
+struct Rectangle {
+    width: f64,
+    height: f64,
+}
+
+impl Named for Circle {
+    fn name(&self) -> String {
+        format!("circle r={}", self.radius)
+    }
+}
+
+impl Named for Square {
+    fn name(&self) -> String {
+        format!("square {}", self.side)
+    }
+}
+
+impl Named for Rectangle {
+    fn name(&self) -> String {
+        format!("rectangle {}x{}", self.width, self.height)
+    }
+}
+
+impl Shape for Circle {
+    fn area(&self) -> f64 {
+        PI * self.radius * self.radius
+    }
+
+    fn scaled(&self, factor: f64) -> Circle {
+        Circle {
+            radius: self.radius * factor,
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Shape for Square {
+    fn area(&self) -> f64 {
+        self.side * self.side
+    }
+
+    fn scaled(&self, factor: f64) -> Square {
+        Square {
+            side: self.side * factor,
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Shape for Rectangle {
+    fn area(&self) -> f64 {
+        self.width * self.height
+    }
+
+    fn scaled(&self, factor: f64) -> Rectangle {
+        Rectangle {
+            width: self.width * factor,
+            height: self.height * factor,
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+fn heterogeneous_collections() {
+    use std::mem::size_of;
+
+    //different concrete types in one Vec: only possible behind a pointer
+    let shapes: Vec<Box<dyn Shape>> = vec![
+        Box::new(Circle { radius: 1.0 }),
+        Box::new(Square { side: 2.0 }),
+        Box::new(Rectangle {
+            width: 2.0,
+            height: 3.0,
+        }),
+    ];
+
+    let total: f64 = shapes.iter().map(|shape| shape.area()).sum();
+    assert!((total - (PI + 4.0 + 6.0)).abs() < 1e-9);
+
+    let largest = shapes
+        .iter()
+        .max_by(|a, b| a.area().partial_cmp(&b.area()).unwrap())
+        .unwrap();
+    assert_eq!(largest.name(), "rectangle 2x3");
+
+    //fat pointers: data + vtable
+    assert_eq!(size_of::<&dyn Shape>(), 2 * size_of::<usize>());
+    assert_eq!(size_of::<Box<dyn Shape>>(), 2 * size_of::<usize>());
+    assert_eq!(size_of::<&Circle>(), size_of::<usize>());
+
+    //Self: Sized methods are available on concrete types only
+    let circle = Circle { radius: 1.0 };
+    assert_eq!(circle.scaled(2.0), Circle { radius: 2.0 });
+    assert_eq!(
+        circle.describe_with(|name, area| format!("{}: {:.2}", name, area)),
+        "circle r=1: 3.14"
+    );
+    //Not allowed: the method requires Self: Sized, dyn Shape is not
+    // shapes[0].scaled(2.0);
+    //error: the `scaled` method cannot be invoked on a trait object
+}
+
+fn dyn_compatibility() {
+    //Not allowed: generic method without `where Self: Sized`
+    // trait Visitor {
+    //     fn visit<T>(&self, value: T);
+    // }
+    // fn f(_: &dyn Visitor) {}
+    //error[E0038]: the trait `Visitor` is not dyn compatible
+    //  (a vtable would need an entry for every T)
+
+    //Not allowed: returning Self
+    // trait Duplicate {
+    //     fn duplicate(&self) -> Self;
+    // }
+    // fn g(_: &dyn Duplicate) {}
+    //error[E0038]: the trait `Duplicate` is not dyn compatible
+    //  (size of the returned value is unknown to the caller)
+
+    //Not allowed: Sized as a supertrait (Clone: Sized)
+    // fn h(_: &dyn Clone) {}
+    //error[E0038]: the trait `Clone` is not dyn compatible
+
+    //Allowed: the same methods behind `where Self: Sized`
+    trait Visitor {
+        fn visit_str(&self, value: &str) -> usize;
+
+        fn visit<T: ToString>(&self, value: T) -> usize
+        where
+            Self: Sized,
+        {
+            self.visit_str(&value.to_string())
+        }
+    }
+
+    struct Counter;
+    impl Visitor for Counter {
+        fn visit_str(&self, value: &str) -> usize {
+            value.len()
+        }
+    }
+
+    let visitor: &dyn Visitor = &Counter;
+    assert_eq!(visitor.visit_str("cake"), 4);
+    assert_eq!(Counter.visit(12345), 5);
+
+    //Self: Sized on the whole trait makes it not dyn compatible at all
+    //(useful to forbid trait objects on purpose)
+}
+
+fn upcasting_and_downcasting() {
+    let shapes: Vec<Box<dyn Shape>> = vec![
+        Box::new(Circle { radius: 1.0 }),
+        Box::new(Square { side: 2.0 }),
+    ];
+
+    //upcasting: dyn Shape -> dyn Named (supertrait), vtable pointer is replaced
+    let named: Vec<&dyn Named> = shapes
+        .iter()
+        .map(|shape| shape.as_ref() as &dyn Named)
+        .collect();
+    assert_eq!(named[1].name(), "square 2");
+
+    //downcasting through dyn Any: checked against TypeId at runtime
+    let any: &dyn Any = shapes[0].as_ref();
+    assert_eq!(any.downcast_ref::<Circle>(), Some(&Circle { radius: 1.0 }));
+    assert_eq!(any.downcast_ref::<Square>(), None);
+    //same through the as_any method
+    assert!(shapes[1].as_any().is::<Square>());
+
+    //owned downcast: Box<dyn Any> -> Box<T>, or the box back on failure
+    let mut squares = Vec::new();
+    let mut others = 0;
+    for shape in shapes {
+        let any: Box<dyn Any> = shape;
+        match any.downcast::<Square>() {
+            Ok(square) => squares.push(*square),
+            Err(_) => others += 1,
+        }
+    }
+    assert_eq!(squares, [Square { side: 2.0 }]);
+    assert_eq!(others, 1);
+}
+
+/*
+ * What the compiler generates for dyn Shape, written by hand:
+ * a struct of fn pointers per implementing type, and a (data, vtable) pair
+ */
+mod manual_vtable {
+    use super::Shape;
+    use std::alloc::Layout;
+
+    pub struct ShapeVTable {
+        pub drop: unsafe fn(*mut ()),
+        pub layout: Layout,
+        pub area: unsafe fn(*const ()) -> f64,
+        pub name: unsafe fn(*const ()) -> String,
+    }
+
+    //fn pointers are monomorphized per T, same as the compiler's vtable entries
+    unsafe fn drop_box<T>(data: *mut ()) {
+        drop(Box::from_raw(data as *mut T));
+    }
+
+    unsafe fn area<T: Shape>(data: *const ()) -> f64 {
+        (*(data as *const T)).area()
+    }
+
+    unsafe fn name<T: Shape>(data: *const ()) -> String {
+        (*(data as *const T)).name()
+    }
+
+    trait HasVTable {
+        const VTABLE: ShapeVTable;
+    }
+
+    impl<T: Shape> HasVTable for T {
+        const VTABLE: ShapeVTable = ShapeVTable {
+            drop: drop_box::<T>,
+            layout: Layout::new::<T>(),
+            area: area::<T>,
+            name: name::<T>,
+        };
+    }
+
+    //Box<dyn Shape>, spelled out
+    pub struct DynShape {
+        data: *mut (),
+        vtable: &'static ShapeVTable,
+    }
+
+    impl DynShape {
+        pub fn new<T: Shape>(value: T) -> DynShape {
+            DynShape {
+                data: Box::into_raw(Box::new(value)) as *mut (),
+                //constant promotion: &CONST is a &'static
+                vtable: &<T as HasVTable>::VTABLE,
+            }
+        }
+
+        pub fn area(&self) -> f64 {
+            unsafe { (self.vtable.area)(self.data) }
+        }
+
+        pub fn name(&self) -> String {
+            unsafe { (self.vtable.name)(self.data) }
+        }
+
+        pub fn size(&self) -> usize {
+            self.vtable.layout.size()
+        }
+    }
+
+    impl Drop for DynShape {
+        fn drop(&mut self) {
+            unsafe { (self.vtable.drop)(self.data) }
+        }
+    }
+}
+
+fn hand_built_vtable() {
+    use manual_vtable::DynShape;
+    use std::mem::size_of;
+
+    let shapes = [
+        DynShape::new(Circle { radius: 1.0 }),
+        DynShape::new(Rectangle {
+            width: 2.0,
+            height: 3.0,
+        }),
+    ];
+    assert_eq!(shapes[0].name(), "circle r=1");
+    assert_eq!(shapes[1].area(), 6.0);
+    assert_eq!(shapes[0].size(), size_of::<Circle>());
+    assert_eq!(shapes[1].size(), size_of::<Rectangle>());
+    //same size as the compiler's fat pointer
+    assert_eq!(size_of::<DynShape>(), size_of::<Box<dyn Shape>>());
+    //dropping the array runs drop_box::<Circle> and drop_box::<Rectangle>
+}
+
+/*
+ * Static vs dynamic dispatch, using HasArea from generics::bounds
+ * Static: call is resolved at compile time, can be inlined
+ * Dynamic: indirect call through the vtable, can't be inlined across it
+ * Rough numbers only: use a benchmark harness (criterion, cargo bench) for real measurements
+ */
+fn dispatch_benchmark() {
+    use std::hint::black_box;
+    use std::time::{Duration, Instant};
+
+    struct Tile {
+        side: f64,
+    }
+
+    impl HasArea for Tile {
+        fn area(&self) -> f64 {
+            self.side * self.side
+        }
+    }
+
+    fn total_static<T: HasArea>(items: &[T]) -> f64 {
+        items.iter().map(HasArea::area).sum()
+    }
+
+    fn total_dynamic(items: &[Box<dyn HasArea>]) -> f64 {
+        items.iter().map(|item| item.area()).sum()
+    }
+
+    fn measure<F: FnMut() -> f64>(rounds: u32, mut f: F) -> (f64, Duration) {
+        let start = Instant::now();
+        let mut result = 0.0;
+        for _ in 0..rounds {
+            result = black_box(f());
+        }
+        (result, start.elapsed())
+    }
+
+    let sides: Vec<f64> = (0..1_000).map(|i| (i % 10) as f64).collect();
+    let tiles: Vec<Tile> = sides.iter().map(|&side| Tile { side }).collect();
+    let boxed: Vec<Box<dyn HasArea>> = sides
+        .iter()
+        .map(|&side| Box::new(Tile { side }) as Box<dyn HasArea>)
+        .collect();
+
+    let (static_total, static_time) = measure(200, || total_static(black_box(&tiles)));
+    let (dynamic_total, dynamic_time) = measure(200, || total_dynamic(black_box(&boxed)));
+    assert_eq!(static_total, dynamic_total);
+    println!(
+        "static dispatch: {:?}, dynamic dispatch: {:?}",
+        static_time, dynamic_time
+    );
+}
+
+pub fn main() {
+    heterogeneous_collections();
+    dyn_compatibility();
+    upcasting_and_downcasting();
+    hand_built_vtable();
+    dispatch_benchmark();
+}
@@ -24,10 +24,7 @@ fn idea() {
 
     impl Animal for Sheep {
         fn new(name: &'static str) -> Self {
-            Sheep {
-                name: name,
-                naked: false,
-            }
+            Sheep { name, naked: false }
         }
         fn noise(&self) -> &'static str {
             "blaaaah"
@@ -38,7 +35,7 @@ fn idea() {
     }
 
     let dolly: Sheep = Animal::new("Dolly");
-    assert_eq!(dolly.is_naked(), false);
+    assert!(!dolly.is_naked());
     assert_eq!(dolly.name, "Dolly");
 
     dolly.noise();
@@ -51,7 +48,7 @@ fn derive() {
 
     let x1 = Centimiters(1.0);
     let x2 = Centimiters(2.0);
-    assert_eq!(x1 < x2, true);
+    assert!(x1 < x2);
 }
 
 fn operator_overloading() {
@@ -71,23 +68,23 @@ fn operator_overloading() {
     #[derive(PartialEq, Debug)]
     struct FooBar;
 
-    let foo = Foo;
-    let foobar = foo + Bar;
+    let foobar = Foo + Bar;
 
     assert_eq!(foobar, FooBar);
+    //more operators, reference operands and algebraic laws: see operator_overloading.rs
 }
 
-fn drop() {
-    struct Droppable {
-        name: &'static str,
-    };
+pub(crate) struct Droppable {
+    pub(crate) name: &'static str,
+}
 
-    impl Drop for Droppable {
-        fn drop(&mut self) {
-            println!("> Dropping {}", self.name);
-        }
+impl Drop for Droppable {
+    fn drop(&mut self) {
+        println!("> Dropping {}", self.name);
     }
+}
 
+fn drop() {
     let _d = Droppable { name: "asd" };
 }
 
@@ -147,11 +144,11 @@ fn impl_trait() {
         v: Vec<i32>,
         u: Vec<i32>,
     ) -> iter::Cycle<iter::Chain<IntoIter<i32>, IntoIter<i32>>> {
-        v.into_iter().chain(u.into_iter()).cycle()
+        v.into_iter().chain(u).cycle()
     }
 
     fn combine_vecs_implicit(v: Vec<i32>, u: Vec<i32>) -> impl Iterator<Item = i32> {
-        v.into_iter().chain(u.into_iter()).cycle()
+        v.into_iter().chain(u).cycle()
     }
 
     //2.1 types that can't be written: closures
@@ -162,16 +159,15 @@ fn impl_trait() {
     assert_eq!(v3.next().unwrap(), 1);
 
     fn make_adder_function(y: i32) -> impl Fn(i32) -> i32 {
-        let closure = move |x: i32| x + y;
-        closure
+        move |x: i32| x + y
     }
     assert_eq!(make_adder_function(5)(4), 9);
 
     //2.2 types that can't be written: intermediate iterators
-    fn double_positives<'a>(numbers: &'a Vec<i32>) -> impl Iterator<Item = i32> + 'a {
+    fn double_positives<'a>(numbers: &'a [i32]) -> impl Iterator<Item = i32> + 'a {
         numbers.iter().filter(|x| x > &&0).map(|x| x * 2)
     }
-    assert_eq!(double_positives(&vec![1, 2, 3]).nth(1).unwrap(), 4);
+    assert_eq!(double_positives(&[1, 2, 3]).nth(1).unwrap(), 4);
 }
 
 fn clone() {
@@ -189,7 +185,7 @@ fn clone() {
     //Can use unit21: it was copied, not moved
     assert_eq!(unit21, Unit2);
 
-    #[derive(Debug, PartialEq, Clone, Copy)]
+    #[derive(Debug, PartialEq, Clone)]
     struct Unit3;
     let unit31 = Unit3;
     let _unit32 = unit31.clone();
@@ -197,6 +193,8 @@ fn clone() {
     assert_eq!(unit31, Unit3);
 }
 
+//the traits are only used through _cake, which nothing calls
+#[allow(dead_code)]
 fn supertraits() {
     trait Person {
         fn name(&self) -> String;
@@ -1,4 +1,4 @@
-/**
+/*
  * mut means mutable
  * _x: suppress 'unused variable' warning
  * might be declared and initialized later
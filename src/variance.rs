@@ -0,0 +1,118 @@
+/*
+ * Continuation of lifetimes.rs
+ * Variance: when a type with a longer lifetime can be used where a shorter one is expected
+ * - &'a T: covariant in 'a and T
+ * - &'a mut T: covariant in 'a, invariant in T
+ * - Cell<T>, RefCell<T>, UnsafeCell<T>: invariant in T
+ * - fn(T): contravariant in T
+ *
+ * The rejected conversions are checked by compile_fail doc tests: cargo test --doc
+ */
+
+use std::cell::Cell;
+
+/// `&'a T` is covariant in `'a`: a `&'static str` can be returned as a `&'short str`.
+pub fn shorten<'short>(long: &'static str, _: &'short str) -> &'short str {
+    long
+}
+
+/// Covariant in `T` too: `&'a &'static str` as `&'a &'a str`.
+///
+/// Not through `&mut`, which is invariant in `T`:
+///
+/// ```compile_fail
+/// fn shrink_inner_mut<'a>(x: &'a mut &'static str) -> &'a mut &'a str {
+///     x
+/// }
+/// ```
+pub fn shrink_inner<'a>(x: &'a &'static str) -> &'a &'a str {
+    x
+}
+
+/// Writes `value` where `input` points.
+///
+/// If `&mut &'static str` could become `&mut &'a str`, a short-lived string could be written
+/// into a place that is still typed as `'static`:
+///
+/// ```compile_fail,E0597
+/// use rust_by_example::variance::assign;
+///
+/// let mut hello: &'static str = "hello";
+/// {
+///     let world = String::from("world");
+///     assign(&mut hello, &world);
+/// }
+/// println!("{}", hello);
+/// ```
+pub fn assign<T>(input: &mut T, value: T) {
+    *input = value;
+}
+
+/// Stores `value` in a cell of strings that live at least `'a`.
+///
+/// `Cell<T>` is invariant in `T` for the same reason as `&mut T`: `set` writes through `&self`.
+/// A `Cell<&'static str>` can't be used as a `Cell<&'a str>`:
+///
+/// ```compile_fail
+/// use std::cell::Cell;
+///
+/// fn shrink_cell<'a>(x: &'a Cell<&'static str>) -> &'a Cell<&'a str> {
+///     x
+/// }
+/// ```
+///
+/// so it only ever accepts `'static` strings:
+///
+/// ```compile_fail,E0597
+/// use rust_by_example::variance::store;
+/// use std::cell::Cell;
+///
+/// let cell: Cell<&'static str> = Cell::new("static");
+/// {
+///     let local = String::from("local");
+///     store(&cell, &local);
+/// }
+/// println!("{}", cell.get());
+/// ```
+pub fn store<'a>(cell: &Cell<&'a str>, value: &'a str) {
+    cell.set(value);
+}
+
+/// Accepts a string of any lifetime.
+///
+/// `fn(T)` is contravariant: `length` can be used where only `'static` strings are passed,
+/// but not the other way around:
+///
+/// ```compile_fail,E0308
+/// fn only_static(s: &'static str) -> usize {
+///     s.len()
+/// }
+/// let _any: for<'a> fn(&'a str) -> usize = only_static;
+/// ```
+pub fn length(s: &str) -> usize {
+    s.len()
+}
+
+pub fn main() {
+    let local = String::from("local");
+    assert_eq!(shorten("static", &local), "static");
+    let fixed: &'static str = "fixed";
+    assert_eq!(*shrink_inner(&fixed), "fixed");
+
+    //invariant, but the value itself is still a plain &'static str
+    let mut hello: &'static str = "hello";
+    assign(&mut hello, "world");
+    assert_eq!(hello, "world");
+
+    let cell: Cell<&'static str> = Cell::new("static");
+    store(&cell, "still static");
+    assert_eq!(cell.get(), "still static");
+
+    //a cell of a short lifetime accepts long-lived values: &'static str -> &'a str at set()
+    let short_cell = Cell::new(local.as_str());
+    store(&short_cell, "static");
+    assert_eq!(short_cell.get(), "static");
+
+    let only_static: fn(&'static str) -> usize = length;
+    assert_eq!(only_static("four"), 4);
+}
@@ -0,0 +1,204 @@
+//integration test: a chat server on a loopback port, clients as plain TcpStreams in this process
+mod common;
+
+use common::RunningServer;
+use rust_by_example::capstone_chat::{Server, WELCOME};
+use std::io::{BufRead, BufReader, Lines, Write};
+use std::net::TcpStream;
+use std::thread;
+use std::time::Duration;
+
+//Running server; shuts down on drop
+struct TestServer {
+    running: RunningServer,
+}
+
+impl TestServer {
+    fn start() -> TestServer {
+        let server = Server::bind("127.0.0.1:0").unwrap();
+        let running = RunningServer::start(
+            server.local_addr().unwrap(),
+            server.shutdown_handle().unwrap(),
+            move || server.serve(),
+        );
+        TestServer { running }
+    }
+
+    //connected and greeted, no nickname yet
+    fn connect(&self) -> Client {
+        let stream = TcpStream::connect(self.running.address).unwrap();
+        //a missing line fails the test instead of hanging it
+        stream
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+        let mut client = Client {
+            lines: BufReader::new(stream.try_clone().unwrap()).lines(),
+            stream,
+        };
+        client.expect(WELCOME);
+        client
+    }
+
+    fn join(&self, nick: &str) -> Client {
+        let mut client = self.connect();
+        client.send(nick);
+        client.expect(&format!("* {} joined", nick));
+        client
+    }
+}
+
+struct Client {
+    stream: TcpStream,
+    lines: Lines<BufReader<TcpStream>>,
+}
+
+impl Client {
+    fn send(&self, line: &str) {
+        writeln!(&self.stream, "{}", line).unwrap();
+    }
+
+    fn next(&mut self) -> String {
+        self.lines.next().expect("connection closed").unwrap()
+    }
+
+    fn expect(&mut self, line: &str) {
+        assert_eq!(self.next(), line);
+    }
+
+    fn expect_closed(&mut self) {
+        assert!(self.lines.next().is_none());
+    }
+}
+
+#[test]
+fn joins_are_announced_to_everyone_already_in() {
+    let server = TestServer::start();
+    let mut alice = server.join("alice");
+    let mut bob = server.join("bob");
+    let _carol = server.join("carol");
+    alice.expect("* bob joined");
+    alice.expect("* carol joined");
+    bob.expect("* carol joined");
+}
+
+#[test]
+fn fan_out_keeps_one_order_for_everyone() {
+    let server = TestServer::start();
+    let mut alice = server.join("alice");
+    let mut bob = server.join("bob");
+    let mut carol = server.join("carol");
+    alice.expect("* bob joined");
+    alice.expect("* carol joined");
+    bob.expect("* carol joined");
+
+    //three senders at once: the interleaving is up to the hub, but the same for all
+    const COUNT: usize = 25;
+    let senders: Vec<_> = ["alice", "bob", "carol"]
+        .iter()
+        .zip([&alice, &bob, &carol])
+        .map(|(nick, client)| {
+            let stream = client.stream.try_clone().unwrap();
+            thread::spawn(move || {
+                for i in 0..COUNT {
+                    writeln!(&stream, "{} {}", nick, i).unwrap();
+                }
+            })
+        })
+        .collect();
+    for sender in senders {
+        sender.join().unwrap();
+    }
+
+    let received: Vec<Vec<String>> = [&mut alice, &mut bob, &mut carol]
+        .iter_mut()
+        .map(|client| (0..3 * COUNT).map(|_| client.next()).collect())
+        .collect();
+    assert_eq!(received[0], received[1]);
+    assert_eq!(received[0], received[2]);
+
+    //and every sender's own messages stay in the order they were sent
+    for nick in ["alice", "bob", "carol"] {
+        let prefix = format!("<{}> ", nick);
+        let own: Vec<&str> = received[0]
+            .iter()
+            .filter_map(|line| line.strip_prefix(&prefix))
+            .collect();
+        let sent: Vec<String> = (0..COUNT).map(|i| format!("{} {}", nick, i)).collect();
+        assert_eq!(own, sent);
+    }
+}
+
+#[test]
+fn nicknames_must_be_valid_and_unique() {
+    let server = TestServer::start();
+    let _alice = server.join("alice");
+
+    let mut other = server.connect();
+    other.send("alice");
+    other.expect("! nickname alice is taken");
+    other.send("two words");
+    other.expect("! nickname may only contain letters, digits, _ and -");
+    other.send("");
+    other.expect("! empty nickname");
+    other.send("alice2");
+    other.expect("* alice2 joined");
+}
+
+#[test]
+fn list_and_unknown_commands_answer_only_the_sender() {
+    let server = TestServer::start();
+    let mut alice = server.join("alice");
+    let mut bob = server.join("bob");
+    alice.expect("* bob joined");
+
+    bob.send("/list");
+    bob.expect("* online: alice, bob");
+    bob.send("/shout hi");
+    bob.expect("! unknown command /shout hi");
+
+    //alice saw none of that: her next line is this message
+    bob.send("still here");
+    alice.expect("<bob> still here");
+    bob.expect("<bob> still here");
+}
+
+#[test]
+fn quit_and_disconnect_are_announced() {
+    let server = TestServer::start();
+    let mut alice = server.join("alice");
+    let mut bob = server.join("bob");
+    let carol = server.join("carol");
+    alice.expect("* bob joined");
+    alice.expect("* carol joined");
+    bob.expect("* carol joined");
+
+    bob.send("/quit");
+    bob.expect("* bye");
+    bob.expect_closed();
+    alice.expect("* bob left");
+
+    //no /quit, the connection just goes away
+    drop(carol);
+    alice.expect("* carol left");
+
+    //a nickname is free again once its owner left
+    let _bob = server.join("bob");
+    alice.expect("* bob joined");
+}
+
+#[test]
+fn shutdown_says_goodbye_and_closes_every_client() {
+    let mut server = TestServer::start();
+    let mut alice = server.join("alice");
+    let mut bob = server.join("bob");
+    alice.expect("* bob joined");
+    //connected but never picked a nickname: not in the room, still closed
+    let mut lurker = server.connect();
+
+    server.running.stop();
+    alice.expect("* server shutting down");
+    alice.expect_closed();
+    bob.expect("* server shutting down");
+    bob.expect_closed();
+    lurker.expect_closed();
+}
@@ -0,0 +1,167 @@
+//integration test: a real server on a loopback port, raw HTTP over TcpStream
+mod common;
+
+use common::{RunningServer, TempDir};
+use rust_by_example::capstone_http::Server;
+use std::fs;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::thread;
+use std::time::Duration;
+
+//Running server over its own root directory; shuts down and cleans up on drop
+struct TestServer {
+    running: RunningServer,
+    //dropped after `running`: the directory goes once the server has stopped
+    _dir: TempDir,
+}
+
+impl TestServer {
+    fn start(name: &str, workers: usize) -> TestServer {
+        let dir = TempDir::new(&format!("capstone-{}", name));
+        let root = &dir.root;
+        fs::create_dir_all(root.join("public/css")).unwrap();
+        fs::write(root.join("public/index.html"), "<p>home</p>").unwrap();
+        fs::write(root.join("public/css/site.css"), "body {}").unwrap();
+        fs::write(root.join("secret.txt"), "outside the root").unwrap();
+
+        let server = Server::bind("127.0.0.1:0", &root.join("public"), workers).unwrap();
+        let running = RunningServer::start(
+            server.local_addr().unwrap(),
+            server.shutdown_handle().unwrap(),
+            move || server.serve(),
+        );
+        TestServer { running, _dir: dir }
+    }
+
+    fn request(&self, raw: &[u8]) -> String {
+        let mut stream = TcpStream::connect(self.running.address).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+        stream.write_all(raw).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    fn get(&self, path: &str) -> String {
+        self.request(format!("GET {} HTTP/1.1\r\nHost: test\r\n\r\n", path).as_bytes())
+    }
+}
+
+fn status_line(response: &str) -> &str {
+    response.lines().next().unwrap_or_default()
+}
+
+#[test]
+fn serves_index_and_files() {
+    let server = TestServer::start("files", 2);
+
+    let index = server.get("/");
+    assert_eq!(status_line(&index), "HTTP/1.1 200 OK");
+    assert!(index.contains("Content-Type: text/html; charset=utf-8\r\n"));
+    assert!(index.contains("Content-Length: 11\r\n"));
+    assert!(index.contains("Connection: close\r\n"));
+    assert!(index.ends_with("\r\n\r\n<p>home</p>"));
+
+    let css = server.get("/css/site.css");
+    assert!(css.contains("Content-Type: text/css\r\n"));
+    assert!(css.ends_with("body {}"));
+}
+
+#[test]
+fn error_statuses() {
+    let server = TestServer::start("errors", 2);
+
+    assert_eq!(
+        status_line(&server.get("/missing.html")),
+        "HTTP/1.1 404 Not Found"
+    );
+    assert_eq!(
+        status_line(&server.request(b"POST / HTTP/1.1\r\nContent-Length: 0\r\n\r\n")),
+        "HTTP/1.1 405 Method Not Allowed"
+    );
+    assert_eq!(
+        status_line(&server.request(b"this is not http\r\n\r\n")),
+        "HTTP/1.1 400 Bad Request"
+    );
+    assert_eq!(
+        status_line(&server.request(b"GET / HTTP/2.0\r\n\r\n")),
+        "HTTP/1.1 505 HTTP Version Not Supported"
+    );
+}
+
+#[test]
+fn path_traversal_is_blocked() {
+    let server = TestServer::start("traversal", 2);
+    for path in ["/../secret.txt", "/css/../../secret.txt"] {
+        let response = server.get(path);
+        assert_eq!(status_line(&response), "HTTP/1.1 403 Forbidden");
+        assert!(!response.contains("outside the root"));
+    }
+}
+
+#[test]
+fn head_has_headers_but_no_body() {
+    let server = TestServer::start("head", 2);
+    let response = server.request(b"HEAD /index.html HTTP/1.1\r\n\r\n");
+    assert_eq!(status_line(&response), "HTTP/1.1 200 OK");
+    assert!(response.contains("Content-Length: 11\r\n"));
+    assert!(response.ends_with("\r\n\r\n"));
+}
+
+#[test]
+fn stalled_client_does_not_block_other_workers() {
+    let server = TestServer::start("stalled", 2);
+
+    //connects and sends half a request: one worker waits on it
+    let mut stalled = TcpStream::connect(server.running.address).unwrap();
+    stalled.write_all(b"GET / HTTP/1.1\r\n").unwrap();
+
+    //the other worker still answers
+    assert_eq!(status_line(&server.get("/")), "HTTP/1.1 200 OK");
+
+    //finishing the stalled request gets it answered too
+    stalled.write_all(b"\r\n").unwrap();
+    let mut response = String::new();
+    stalled.read_to_string(&mut response).unwrap();
+    assert_eq!(status_line(&response), "HTTP/1.1 200 OK");
+}
+
+#[test]
+fn concurrent_requests_are_all_answered() {
+    let server = TestServer::start("concurrent", 4);
+    let address = server.running.address;
+    let clients: Vec<_> = (0..16)
+        .map(|i| {
+            thread::spawn(move || {
+                let path = if i % 2 == 0 { "/" } else { "/missing" };
+                let mut stream = TcpStream::connect(address).unwrap();
+                write!(stream, "GET {} HTTP/1.1\r\n\r\n", path).unwrap();
+                let mut response = String::new();
+                stream.read_to_string(&mut response).unwrap();
+                (i, response)
+            })
+        })
+        .collect();
+    for client in clients {
+        let (i, response) = client.join().unwrap();
+        let expected = if i % 2 == 0 {
+            "200 OK"
+        } else {
+            "404 Not Found"
+        };
+        assert!(response.starts_with(&format!("HTTP/1.1 {}", expected)));
+    }
+}
+
+#[test]
+fn shutdown_stops_accepting() {
+    let mut server = TestServer::start("shutdown", 1);
+    assert_eq!(status_line(&server.get("/")), "HTTP/1.1 200 OK");
+
+    server.running.stop();
+    //serve() returned and dropped the listener: connections are refused
+    assert!(TcpStream::connect(server.running.address).is_err());
+}
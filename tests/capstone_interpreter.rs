@@ -0,0 +1,53 @@
+//integration test: runs every tests/expr/*.expr program, compares with the .out file next to it
+use rust_by_example::capstone_interpreter::run_program;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn programs() -> Vec<PathBuf> {
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/expr");
+    let mut programs: Vec<PathBuf> = fs::read_dir(&directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "expr"))
+        .collect();
+    programs.sort();
+    programs
+}
+
+#[test]
+fn expr_files_match_expected_output() {
+    let programs = programs();
+    assert!(!programs.is_empty(), "no .expr files found");
+
+    //run all of them, report every mismatch at once
+    let mut failures = Vec::new();
+    for program in &programs {
+        let source = fs::read_to_string(program).unwrap();
+        let expected = fs::read_to_string(program.with_extension("out"))
+            .unwrap_or_else(|_| panic!("{} has no .out file", program.display()));
+        let actual = run_program(&source);
+        if actual != expected {
+            failures.push(format!(
+                "{}\n--- expected\n{}--- actual\n{}",
+                program.display(),
+                expected,
+                actual
+            ));
+        }
+    }
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
+
+#[test]
+fn every_error_program_fails() {
+    for program in programs() {
+        let name = program.file_stem().unwrap().to_string_lossy().into_owned();
+        let output = run_program(&fs::read_to_string(&program).unwrap());
+        assert_eq!(
+            name.starts_with("error_"),
+            output.contains("error at "),
+            "{}",
+            name
+        );
+    }
+}
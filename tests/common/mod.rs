@@ -0,0 +1,86 @@
+/*
+ * Shared helpers for integration tests
+ * tests/common/mod.rs instead of tests/common.rs: cargo doesn't compile it as a test crate,
+ * every test file pulls it in with `mod common;`
+ */
+//helpers are compiled into every test crate, and not every crate uses all of them
+#![allow(dead_code)]
+
+use rust_by_example::capstone_http::ShutdownHandle;
+use rust_by_example::testing::Rectangle;
+use std::fs;
+use std::io;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::thread::{self, JoinHandle};
+
+pub fn squares(sides: &[u32]) -> Vec<Rectangle> {
+    sides
+        .iter()
+        .map(|&side| Rectangle::new(side, side))
+        .collect()
+}
+
+pub fn percents(inputs: &[&str]) -> Vec<String> {
+    inputs.iter().map(|input| format!("{}%", input)).collect()
+}
+
+/// A fresh directory under the system temp dir, removed again on drop.
+pub struct TempDir {
+    pub root: PathBuf,
+}
+
+impl TempDir {
+    //every test its own directory: tests run in parallel, `name` has to be unique among them
+    pub fn new(name: &str) -> TempDir {
+        let root = std::env::temp_dir().join(format!("rbe-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        TempDir { root }
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
+/// A server's `serve()` running on its own thread, shut down and joined on drop.
+pub struct RunningServer {
+    pub address: SocketAddr,
+    handle: ShutdownHandle,
+    thread: Option<JoinHandle<io::Result<()>>>,
+}
+
+impl RunningServer {
+    //`address` and `handle` belong to the server that `serve` runs
+    pub fn start<F>(address: SocketAddr, handle: ShutdownHandle, serve: F) -> RunningServer
+    where
+        F: FnOnce() -> io::Result<()> + Send + 'static,
+    {
+        RunningServer {
+            address,
+            handle,
+            thread: Some(thread::spawn(serve)),
+        }
+    }
+
+    /// Shuts the server down and waits for `serve()`, which has to return Ok.
+    pub fn stop(&mut self) {
+        self.handle.shutdown();
+        if let Some(running) = self.thread.take() {
+            let result = running.join();
+            //don't panic again while a failed test is unwinding
+            if !thread::panicking() {
+                result.unwrap().unwrap();
+            }
+        }
+    }
+}
+
+impl Drop for RunningServer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
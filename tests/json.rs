@@ -0,0 +1,179 @@
+//integration test: conformance of the json parser and printer on tricky inputs
+use rust_by_example::json::{parse, ErrorKind, FromJson, ToJson, Value, MAX_DEPTH};
+use std::collections::HashMap;
+
+//input, then its compact output: what the parser understood, written back out
+const ACCEPTED: &[(&str, &str)] = &[
+    //escapes, every short one and \u
+    (r#""\"\\\/\b\f\n\r\t""#, r#""\"\\/\b\f\n\r\t""#),
+    (r#""Aé中""#, r#""Aé中""#),
+    (r#""\u0000\u001f""#, r#""\u0000\u001f""#),
+    //\u007f isn't a control character for JSON: printed as is
+    (r#""\u007f""#, "\"\u{7f}\""),
+    //surrogate pairs: one character outside the Basic Multilingual Plane
+    (r#""\ud83e\udd80""#, "\"🦀\""),
+    (r#""\ud834\udd1e clef""#, "\"𝄞 clef\""),
+    (r#""\udbff\udfff""#, "\"\u{10ffff}\""),
+    //raw UTF-8 passes through untouched
+    (r#""ключ 🦀 é""#, r#""ключ 🦀 é""#),
+    //numbers
+    ("0", "0"),
+    ("-0", "-0"),
+    ("1E2", "100"),
+    ("1e-2", "0.01"),
+    ("-12.500", "-12.5"),
+    ("1.5e300", "1.5e300"),
+    ("4.9e-324", "5e-324"),
+    ("123456789012345678901234567890", "1.2345678901234568e29"),
+    //whitespace of all four kinds, anywhere between tokens
+    (" \t\r\n[ 1 ,\n2 ] \n", "[1,2]"),
+    ("{ \"a\" : [ ] , \"b\" : { } }", r#"{"a":[],"b":{}}"#),
+    //a repeated key: the last one wins
+    (r#"{"k": 1, "k": 2}"#, r#"{"k":2}"#),
+    //keys are sorted on output and may be empty
+    (r#"{"b": 1, "": 0, "a": null}"#, r#"{"":0,"a":null,"b":1}"#),
+    (
+        r#"[true, false, null, "", [], {}]"#,
+        r#"[true,false,null,"",[],{}]"#,
+    ),
+];
+
+//input, line, column, error kind
+fn rejected() -> Vec<(&'static str, usize, usize, ErrorKind)> {
+    use ErrorKind::*;
+    vec![
+        ("", 1, 1, UnexpectedEnd),
+        ("   ", 1, 4, UnexpectedEnd),
+        ("[1,]", 1, 4, UnexpectedChar(']')),
+        (r#"{"a": 1,}"#, 1, 9, UnexpectedChar('}')),
+        ("[1 2]", 1, 4, Expected("`,` or `]`")),
+        (r#"{"a" 1}"#, 1, 6, Expected("`:`")),
+        ("{a: 1}", 1, 2, UnexpectedChar('a')),
+        ("['single']", 1, 2, UnexpectedChar('\'')),
+        ("nul", 1, 1, UnexpectedChar('n')),
+        ("True", 1, 1, UnexpectedChar('T')),
+        ("NaN", 1, 1, UnexpectedChar('N')),
+        ("[1] // comment", 1, 5, TrailingCharacters),
+        ("1 2", 1, 3, TrailingCharacters),
+        //numbers
+        ("01", 1, 1, InvalidNumber),
+        ("-", 1, 1, InvalidNumber),
+        ("1.", 1, 1, InvalidNumber),
+        ("[.5]", 1, 2, UnexpectedChar('.')),
+        ("+1", 1, 1, UnexpectedChar('+')),
+        ("1e", 1, 1, InvalidNumber),
+        ("1e+", 1, 1, InvalidNumber),
+        ("0x10", 1, 2, TrailingCharacters),
+        ("[1e400]", 1, 2, NumberOutOfRange),
+        //strings
+        (r#""abc"#, 1, 5, UnexpectedEnd),
+        (r#""\x""#, 1, 2, InvalidEscape),
+        (r#""\u12""#, 1, 4, InvalidEscape),
+        (r#""\uZZZZ""#, 1, 4, InvalidEscape),
+        ("\"tab\there\"", 1, 5, ControlCharacter),
+        ("\"line\nbreak\"", 1, 6, ControlCharacter),
+        //half a surrogate pair, alone or with the wrong partner
+        (r#""\ud83e""#, 1, 2, LoneSurrogate(0xd83e)),
+        (r#""\udd80\ud83e""#, 1, 2, LoneSurrogate(0xdd80)),
+        (r#""x\ud83eA""#, 1, 3, LoneSurrogate(0xd83e)),
+        (r#""\ud83e\ud83e""#, 1, 2, LoneSurrogate(0xd83e)),
+        //positions on later lines count characters, not bytes
+        (
+            "{\n  \"é\": [1,\n         2,,]\n}",
+            3,
+            12,
+            UnexpectedChar(','),
+        ),
+    ]
+}
+
+#[test]
+fn accepted_inputs_print_as_expected() {
+    for (input, output) in ACCEPTED {
+        let value = parse(input).unwrap_or_else(|err| panic!("{:?}: {}", input, err));
+        assert_eq!(value.to_string(), *output, "input {:?}", input);
+    }
+}
+
+#[test]
+fn rejected_inputs_say_where_and_why() {
+    for (input, line, column, kind) in rejected() {
+        let err = parse(input).expect_err(input);
+        assert_eq!(
+            (err.line, err.column, &err.kind),
+            (line, column, &kind),
+            "input {:?}",
+            input
+        );
+    }
+}
+
+#[test]
+fn output_parses_back_to_the_same_value() {
+    for (input, _) in ACCEPTED {
+        let value = parse(input).unwrap();
+        assert_eq!(
+            parse(&value.to_string()).unwrap(),
+            value,
+            "compact {:?}",
+            input
+        );
+        assert_eq!(
+            parse(&format!("{:#}", value)).unwrap(),
+            value,
+            "pretty {:?}",
+            input
+        );
+    }
+    //every control character and both halves of the escape table survive the trip
+    let all: String = (0u32..0x80).filter_map(char::from_u32).collect();
+    let value = Value::String(all);
+    assert_eq!(parse(&value.to_string()).unwrap(), value);
+}
+
+#[test]
+fn deep_nesting() {
+    let nested = |open: &str, close: &str, depth: usize| open.repeat(depth) + &close.repeat(depth);
+    assert!(parse(&nested("[", "]", MAX_DEPTH)).is_ok());
+    let objects = format!("{}0{}", "{\"a\":".repeat(MAX_DEPTH), "}".repeat(MAX_DEPTH));
+    assert!(parse(&objects).is_ok());
+
+    let err = parse(&nested("[", "]", MAX_DEPTH + 1)).unwrap_err();
+    assert_eq!((err.kind, err.column), (ErrorKind::TooDeep, MAX_DEPTH + 1));
+    //far beyond the limit: an error, not a stack overflow
+    let err = parse(&nested("[", "]", 100_000)).unwrap_err();
+    assert_eq!(err.kind, ErrorKind::TooDeep);
+    let err = parse(&"{\"a\":[".repeat(50_000)).unwrap_err();
+    assert_eq!(err.kind, ErrorKind::TooDeep);
+
+    //printing a deep value back out
+    let value = parse(&nested("[", "]", MAX_DEPTH)).unwrap();
+    assert_eq!(value.to_string(), nested("[", "]", MAX_DEPTH));
+    let pretty = format!("{:#}", value);
+    assert_eq!(pretty.lines().count(), 2 * MAX_DEPTH - 1);
+    assert_eq!(parse(&pretty).unwrap(), value);
+}
+
+#[test]
+fn typed_round_trips() {
+    let mut inventory: HashMap<String, Vec<Option<u32>>> = HashMap::new();
+    inventory.insert("crabs".to_string(), vec![Some(1), None, Some(u32::MAX)]);
+    inventory.insert("with \"quotes\"\n".to_string(), vec![]);
+    let text = format!("{:#}", inventory.to_json());
+    let back = HashMap::<String, Vec<Option<u32>>>::from_json(&parse(&text).unwrap()).unwrap();
+    assert_eq!(back, inventory);
+
+    //2^53 is still exact in an f64, i64::MAX is not
+    let exact = 9_007_199_254_740_992i64;
+    assert_eq!(
+        i64::from_json(&parse(&exact.to_string()).unwrap()),
+        Ok(exact)
+    );
+    assert!(i8::from_json(&parse("-129").unwrap()).is_err());
+    assert_eq!(
+        Vec::<String>::from_json(&parse(r#"["a", 1]"#).unwrap())
+            .unwrap_err()
+            .to_string(),
+        "$[1]: expected string, found number"
+    );
+}
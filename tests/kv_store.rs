@@ -0,0 +1,245 @@
+//integration test: crash recovery of the kv_store log, and the kvs binary on top of it
+mod common;
+
+use common::TempDir;
+use rust_by_example::kv_store::{Error, KvStore, Record, MAX_PAYLOAD};
+use std::fs::{self, OpenOptions};
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+const KVS: &str = env!("CARGO_BIN_EXE_kvs");
+
+fn log_path(dir: &TempDir) -> PathBuf {
+    dir.root.join("kvs.log")
+}
+
+fn kvs(dir: &TempDir, args: &[&str]) -> Output {
+    Command::new(KVS)
+        .args(args)
+        .current_dir(&dir.root)
+        .output()
+        .unwrap()
+}
+
+fn truncate(path: &PathBuf, len: u64) {
+    OpenOptions::new()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_len(len)
+        .unwrap();
+}
+
+fn record_len(key: &str, value: &str) -> u64 {
+    Record::Set {
+        key: key.into(),
+        value: value.into(),
+    }
+    .encode()
+    .unwrap()
+    .len() as u64
+}
+
+//three records, the log length after each one
+fn write_three(path: &PathBuf) -> [u64; 3] {
+    let mut store = KvStore::open(path).unwrap();
+    let mut ends = [0; 3];
+    for (i, (key, value)) in [("a", "first"), ("b", "second"), ("c", "third")]
+        .iter()
+        .enumerate()
+    {
+        store.set(key, value).unwrap();
+        ends[i] = store.stats().log_bytes;
+    }
+    ends
+}
+
+#[test]
+fn torn_last_record_is_dropped() {
+    let dir = TempDir::new("kv-store-torn");
+    let log = log_path(&dir);
+    let ends = write_three(&log);
+    assert_eq!(ends[2] - ends[1], record_len("c", "third"));
+
+    //cut in the middle of the last payload
+    truncate(&log, ends[2] - 3);
+    let mut store = KvStore::open(&log).unwrap();
+    let recovery = store.recovery().unwrap().clone();
+    assert_eq!(recovery.valid_len, ends[1]);
+    assert_eq!(recovery.dropped_bytes, ends[2] - 3 - ends[1]);
+    assert_eq!(recovery.reason, "truncated payload");
+    assert_eq!(fs::metadata(&log).unwrap().len(), ends[1]);
+    assert_eq!(store.keys(), ["a", "b"]);
+    assert_eq!(store.get("b").unwrap().as_deref(), Some("second"));
+
+    //new writes go right after the last good record and survive a reopen
+    store.set("d", "fourth").unwrap();
+    drop(store);
+    let store = KvStore::open(&log).unwrap();
+    assert_eq!(store.recovery(), None);
+    assert_eq!(store.keys(), ["a", "b", "d"]);
+    assert_eq!(store.get("d").unwrap().as_deref(), Some("fourth"));
+}
+
+#[test]
+fn every_cut_keeps_a_prefix() {
+    let dir = TempDir::new("kv-store-every-cut");
+    let log = log_path(&dir);
+    let ends = write_three(&log);
+    let full = fs::read(&log).unwrap();
+
+    for len in 0..=ends[2] {
+        fs::write(&log, &full[..len as usize]).unwrap();
+        let store = KvStore::open(&log).unwrap();
+        //records that end before the cut are kept, the rest is gone
+        let kept = ends.iter().filter(|&&end| end <= len).count();
+        let expected = &["a", "b", "c"][..kept];
+        assert_eq!(store.keys(), expected, "cut at {}", len);
+
+        let valid = if kept == 0 { 0 } else { ends[kept - 1] };
+        assert_eq!(store.recovery().is_some(), len != valid, "cut at {}", len);
+        drop(store);
+        assert_eq!(fs::metadata(&log).unwrap().len(), valid);
+    }
+}
+
+#[test]
+fn flipped_byte_before_the_end_is_an_error() {
+    let dir = TempDir::new("kv-store-flipped");
+    let log = log_path(&dir);
+    let ends = write_three(&log);
+    let full = fs::read(&log).unwrap();
+
+    //last byte of "second": the records after it are intact and must not be cut off
+    let mut bytes = full.clone();
+    bytes[ends[1] as usize - 1] ^= 0x01;
+    fs::write(&log, &bytes).unwrap();
+    match KvStore::open(&log) {
+        Err(Error::Corrupt { offset, reason }) => {
+            assert_eq!((offset, reason), (ends[0], "checksum mismatch"))
+        }
+        other => panic!("expected Corrupt, got {:?}", other.map(|_| ())),
+    }
+    assert_eq!(fs::read(&log).unwrap(), bytes);
+
+    //last byte of "third": a record that ends the file is what a crash leaves, so it goes
+    let mut bytes = full;
+    bytes[ends[2] as usize - 1] ^= 0x01;
+    fs::write(&log, &bytes).unwrap();
+    let store = KvStore::open(&log).unwrap();
+    let recovery = store.recovery().unwrap();
+    assert_eq!(recovery.reason, "checksum mismatch");
+    assert_eq!(recovery.valid_len, ends[1]);
+    assert_eq!(store.keys(), ["a", "b"]);
+}
+
+#[test]
+fn oversized_value_is_refused_before_writing() {
+    let dir = TempDir::new("kv-store-oversized");
+    let log = log_path(&dir);
+    let mut store = KvStore::open(&log).unwrap();
+    store.set("before", "small").unwrap();
+    let len = store.stats().log_bytes;
+
+    let huge = "x".repeat(MAX_PAYLOAD as usize + 1);
+    assert!(matches!(
+        store.set("huge", &huge),
+        Err(Error::TooLarge { .. })
+    ));
+    assert_eq!(fs::metadata(&log).unwrap().len(), len);
+    assert_eq!(store.get("huge").unwrap(), None);
+
+    //a record written after the refused one is not lost on reopen
+    store.set("after", "small").unwrap();
+    drop(store);
+    let store = KvStore::open(&log).unwrap();
+    assert_eq!(store.recovery(), None);
+    assert_eq!(store.keys(), ["after", "before"]);
+    assert_eq!(store.get("after").unwrap().as_deref(), Some("small"));
+}
+
+#[test]
+fn removals_and_compaction_survive_reopen() {
+    let dir = TempDir::new("kv-store-compaction");
+    let log = log_path(&dir);
+    {
+        let mut store = KvStore::open(&log).unwrap();
+        for i in 0..20 {
+            store.set("counter", &i.to_string()).unwrap();
+        }
+        store.set("gone", "soon").unwrap();
+        store.remove("gone").unwrap();
+        store.compact().unwrap();
+    }
+    assert_eq!(
+        fs::metadata(&log).unwrap().len(),
+        record_len("counter", "19")
+    );
+
+    //a compaction that died before its rename left its temp file behind
+    let leftover = dir.root.join("kvs.log.compact");
+    fs::write(&leftover, b"half a compaction").unwrap();
+    let store = KvStore::open(&log).unwrap();
+    assert!(!leftover.exists());
+    assert_eq!(store.keys(), ["counter"]);
+    assert_eq!(store.get("counter").unwrap().as_deref(), Some("19"));
+}
+
+#[test]
+fn command_line() {
+    let dir = TempDir::new("kv-store-cli");
+    assert_eq!(kvs(&dir, &["set", "name", "ferris"]).status.code(), Some(0));
+    assert_eq!(kvs(&dir, &["set", "crab", "yes"]).status.code(), Some(0));
+
+    let get = kvs(&dir, &["get", "name"]);
+    assert_eq!(get.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&get.stdout), "ferris\n");
+    let list = kvs(&dir, &["list"]);
+    assert_eq!(String::from_utf8_lossy(&list.stdout), "crab\nname\n");
+
+    assert_eq!(kvs(&dir, &["rm", "name"]).status.code(), Some(0));
+    let missing = kvs(&dir, &["get", "name"]);
+    assert_eq!(missing.status.code(), Some(1));
+    assert!(missing.stdout.is_empty());
+    assert_eq!(
+        String::from_utf8_lossy(&missing.stderr),
+        "kvs: key not found: name\n"
+    );
+    assert_eq!(kvs(&dir, &["rm", "name"]).status.code(), Some(1));
+    assert_eq!(kvs(&dir, &["compact"]).status.code(), Some(0));
+    assert_eq!(
+        fs::metadata(log_path(&dir)).unwrap().len(),
+        record_len("crab", "yes")
+    );
+
+    //a separate file, and usage errors before anything is opened
+    assert_eq!(
+        kvs(&dir, &["--file", "other.log", "set", "k", "v"])
+            .status
+            .code(),
+        Some(0)
+    );
+    assert!(dir.root.join("other.log").exists());
+    let usage = kvs(&dir, &["--file", "typo.log", "sett", "k", "v"]);
+    assert_eq!(usage.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&usage.stderr).starts_with("kvs: bad arguments for sett\n"));
+    assert!(!dir.root.join("typo.log").exists());
+
+    //an open that fails says why, not only "log file error"
+    let missing = kvs(&dir, &["--file", "no/such/dir/x.log", "list"]);
+    assert_eq!(missing.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&missing.stderr);
+    assert!(stderr.contains("No such file or directory"), "{}", stderr);
+}
+
+#[test]
+fn command_line_reports_recovery() {
+    let dir = TempDir::new("kv-store-cli-recovery");
+    let ends = write_three(&log_path(&dir));
+    truncate(&log_path(&dir), ends[2] - 1);
+    let output = kvs(&dir, &["get", "b"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "second\n");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("dropped"), "{}", stderr);
+    assert!(stderr.contains("truncated payload"), "{}", stderr);
+}
@@ -0,0 +1,29 @@
+//exported macros from another crate: expansions only work because of $crate paths
+use rust_by_example::{bitflags, count_tts, hashmap};
+
+#[test]
+fn hashmap_from_outside() {
+    //no HashMap import needed: expansion uses $crate::macro_rules_advanced::__private
+    let map = hashmap! { "a" => 1, ..vec![("b", 2)] };
+    assert_eq!(map.len(), 2);
+}
+
+#[test]
+fn count_from_outside() {
+    const N: usize = count_tts!(a b c d);
+    assert_eq!(N, 4);
+}
+
+bitflags! {
+    pub struct Mode: u8 {
+        const FAST = 1;
+        const SAFE = 2;
+    }
+}
+
+#[test]
+fn bitflags_from_outside() {
+    let mode = Mode::FAST | Mode::SAFE;
+    assert_eq!(mode, Mode::all());
+    assert_eq!(format!("{:?}", mode), "Mode(FAST | SAFE)");
+}
@@ -0,0 +1,197 @@
+//integration test: runs the minigrep binary built by cargo for this test
+mod common;
+
+use common::TempDir;
+use std::fs;
+use std::process::{Command, Output};
+
+//cargo sets CARGO_BIN_EXE_<name> for every binary of the package
+const MINIGREP: &str = env!("CARGO_BIN_EXE_minigrep");
+
+struct Tree {
+    dir: TempDir,
+}
+
+impl Tree {
+    fn new(name: &str) -> Tree {
+        let dir = TempDir::new(&format!("minigrep-{}", name));
+        let root = &dir.root;
+        fs::create_dir_all(root.join("poems/short")).unwrap();
+        fs::write(
+            root.join("poems/frog.txt"),
+            "I'm nobody! Who are you?\nAre you nobody, too?\nThen there's a pair of us - don't tell!\nThey'd banish us, you know.\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("poems/short/haiku.txt"),
+            "an old silent pond\nA frog jumps into the pond\nsplash! Silence again\n",
+        )
+        .unwrap();
+        fs::write(root.join("notes.txt"), "nothing to see\n").unwrap();
+        Tree { dir }
+    }
+
+    fn minigrep(&self, args: &[&str]) -> Output {
+        Command::new(MINIGREP)
+            .args(args)
+            .current_dir(&self.dir.root)
+            .env_remove("IGNORE_CASE")
+            .output()
+            .unwrap()
+    }
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn single_file_with_line_numbers() {
+    let tree = Tree::new("single");
+    let output = tree.minigrep(&["-n", "nobody", "poems/frog.txt"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        stdout(&output),
+        "1:I'm nobody! Who are you?\n2:Are you nobody, too?\n"
+    );
+}
+
+#[test]
+fn recursive_search_prefixes_paths() {
+    let tree = Tree::new("recursive");
+    let output = tree.minigrep(&["-r", "pond", "."]);
+    assert_eq!(
+        stdout(&output),
+        "./poems/short/haiku.txt:an old silent pond\n./poems/short/haiku.txt:A frog jumps into the pond\n"
+    );
+}
+
+#[test]
+fn ignore_case_from_the_environment() {
+    let tree = Tree::new("env");
+    let sensitive = tree.minigrep(&["silence", "poems/short/haiku.txt"]);
+    assert_eq!(sensitive.status.code(), Some(1));
+    assert!(sensitive.stdout.is_empty());
+
+    let insensitive = Command::new(MINIGREP)
+        .args(["silence", "poems/short/haiku.txt"])
+        .current_dir(&tree.dir.root)
+        .env("IGNORE_CASE", "1")
+        .output()
+        .unwrap();
+    assert_eq!(stdout(&insensitive), "splash! Silence again\n");
+}
+
+#[test]
+fn context_lines_and_separators() {
+    let tree = Tree::new("context");
+    let output = tree.minigrep(&["-n", "-C", "1", "nobody", "poems/frog.txt"]);
+    assert_eq!(
+        stdout(&output),
+        "1:I'm nobody! Who are you?\n2:Are you nobody, too?\n3-Then there's a pair of us - don't tell!\n"
+    );
+    let output = tree.minigrep(&["-n", "-C1", "pair", "poems/frog.txt"]);
+    assert_eq!(
+        stdout(&output),
+        "2-Are you nobody, too?\n3:Then there's a pair of us - don't tell!\n4-They'd banish us, you know.\n"
+    );
+    //groups that don't touch are separated by --
+    fs::write(tree.dir.root.join("gap.txt"), "x\n1\n2\n3\nx\n").unwrap();
+    let output = tree.minigrep(&["-C1", "x", "gap.txt"]);
+    assert_eq!(stdout(&output), "x\n1\n--\n3\nx\n");
+}
+
+#[test]
+fn colour_only_when_asked() {
+    let tree = Tree::new("colour");
+    //piped output: auto means no colour
+    let plain = tree.minigrep(&["frog", "poems/short/haiku.txt"]);
+    assert!(!stdout(&plain).contains('\x1b'));
+    let coloured = tree.minigrep(&["--color=always", "frog", "poems/short/haiku.txt"]);
+    //bold, red foreground, the match, default foreground, reset
+    assert_eq!(
+        stdout(&coloured),
+        "A \x1b[1m\x1b[38;5;1mfrog\x1b[39m\x1b[m jumps into the pond\n"
+    );
+}
+
+#[test]
+fn errors_are_reported_and_searching_continues() {
+    let tree = Tree::new("errors");
+    let output = tree.minigrep(&["nothing", "missing.txt", "poems", "notes.txt"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(stdout(&output), "notes.txt:nothing to see\n");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("minigrep: missing.txt: No such file or directory"));
+    assert!(stderr.contains("minigrep: poems: is a directory (use -r)"));
+
+    let usage = tree.minigrep(&["-z", "query"]);
+    assert_eq!(usage.status.code(), Some(2));
+    assert!(
+        String::from_utf8_lossy(&usage.stderr).starts_with("minigrep: unknown option -z\nusage:")
+    );
+}
+
+#[test]
+fn reads_standard_input() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let mut child = Command::new(MINIGREP)
+        .arg("b")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"abc\ndef\ncba\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(stdout(&output), "abc\ncba\n");
+}
+
+#[test]
+fn dash_reads_standard_input_among_files() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let tree = Tree::new("dash");
+    let mut child = Command::new(MINIGREP)
+        .args(["see", "notes.txt", "-"])
+        .current_dir(&tree.dir.root)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(b"seen\n").unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        stdout(&output),
+        "notes.txt:nothing to see\n(standard input):seen\n"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn recursive_search_skips_symlinks() {
+    use std::os::unix::fs::symlink;
+
+    let tree = Tree::new("symlinks");
+    //a loop back up the tree, and a second name for a file that is searched anyway
+    symlink("..", tree.dir.root.join("poems/up")).unwrap();
+    symlink(
+        "short/haiku.txt",
+        tree.dir.root.join("poems/haiku-link.txt"),
+    )
+    .unwrap();
+    let output = tree.minigrep(&["-r", "pond", "poems"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        stdout(&output),
+        "poems/short/haiku.txt:an old silent pond\npoems/short/haiku.txt:A frog jumps into the pond\n"
+    );
+}
@@ -0,0 +1,42 @@
+//integration test: separate crate, only the public API of the library is visible
+use rust_by_example::testing::{self, parse_percent, PercentError, Rectangle};
+
+mod common;
+
+#[test]
+fn nested_squares_hold_each_other() {
+    let squares = common::squares(&[1, 2, 3, 4]);
+    for pair in squares.windows(2) {
+        assert!(pair[1].can_hold(&pair[0]));
+        assert!(!pair[0].can_hold(&pair[1]));
+    }
+}
+
+#[test]
+fn area_of_squares() {
+    let areas: Vec<u32> = common::squares(&[1, 2, 3])
+        .iter()
+        .map(Rectangle::area)
+        .collect();
+    assert_eq!(areas, [1, 4, 9]);
+}
+
+#[test]
+fn parses_formatted_percents() -> Result<(), PercentError> {
+    for (input, expected) in common::percents(&["0", "50", "100"])
+        .iter()
+        .zip([0, 50, 100])
+    {
+        assert_eq!(parse_percent(input)?, expected);
+    }
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "Divide-by-zero error: 7 / 0")]
+fn divide_by_zero() {
+    testing::divide(7, 0);
+}
+
+//Not allowed: private items are not part of the public API
+// testing::strip_percent("42%");